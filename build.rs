@@ -1,5 +1,14 @@
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    fs,
+    path::{Path, PathBuf},
+};
+
 use vergen::Config;
 
+/// The file that the error codes are registered in.
+const REGISTRY: &str = "libs/calypso_diagnostic/src/types.rs";
+
 fn main() {
     let mut config = Config::default();
 
@@ -9,4 +18,94 @@ fn main() {
     *config.git_mut().commit_timestamp_mut() = false;
 
     vergen::vergen(config).unwrap();
+
+    check_error_codes();
+}
+
+/// Check that every error code that is used in the source is registered,
+/// and that every registered error code is used somewhere.
+fn check_error_codes() {
+    println!("cargo:rerun-if-changed=src");
+    println!("cargo:rerun-if-changed=libs");
+
+    let registry = fs::read_to_string(REGISTRY).unwrap();
+    let registered = registry
+        .lines()
+        .filter_map(|line| line.trim().split_once(':'))
+        .map(|(code, _)| code)
+        .filter(|code| is_error_code(code))
+        .map(ToString::to_string)
+        .collect::<BTreeSet<_>>();
+
+    let mut files = Vec::new();
+    source_files(Path::new("src"), &mut files);
+    for lib in fs::read_dir("libs").unwrap() {
+        source_files(&lib.unwrap().path().join("src"), &mut files);
+    }
+    let mut used = BTreeMap::<String, PathBuf>::new();
+    for file in files {
+        if file == Path::new(REGISTRY) {
+            continue;
+        }
+        let source = fs::read_to_string(&file).unwrap();
+        for code in error_codes(&source) {
+            used.entry(code).or_insert_with(|| file.clone());
+        }
+    }
+
+    let mut problems = Vec::new();
+    for (code, file) in &used {
+        if !registered.contains(code) {
+            problems.push(format!(
+                "error code {code} is used in `{}`, but is not registered in `{REGISTRY}`",
+                file.display()
+            ));
+        }
+    }
+    for code in &registered {
+        if !used.contains_key(code) {
+            problems.push(format!(
+                "error code {code} is registered in `{REGISTRY}`, but is never used"
+            ));
+        }
+    }
+    if !problems.is_empty() {
+        panic!("{}", problems.join("\n"));
+    }
+}
+
+/// Collect the Rust source files in `dir`, recursively.
+fn source_files(dir: &Path, files: &mut Vec<PathBuf>) {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return;
+    };
+    for entry in entries {
+        let path = entry.unwrap().path();
+        if path.is_dir() {
+            source_files(&path, files);
+        } else if path.extension().map_or(false, |ext| ext == "rs") {
+            files.push(path);
+        }
+    }
+}
+
+/// Find the error codes that are used in `source`, i.e. string literals such
+/// as `"E0001"` and uses of the `err!` macro such as `err!(E0001, ...)`.
+fn error_codes(source: &str) -> Vec<String> {
+    let mut codes = Vec::new();
+    for (prefix, suffix) in [("\"", "\""), ("err!(", "")] {
+        for (idx, _) in source.match_indices(prefix) {
+            let rest = &source[idx + prefix.len()..];
+            if let Some(code) = rest.get(..5) {
+                if is_error_code(code) && rest[5..].starts_with(suffix) {
+                    codes.push(code.to_string());
+                }
+            }
+        }
+    }
+    codes
+}
+
+fn is_error_code(code: &str) -> bool {
+    code.len() == 5 && code.starts_with('E') && code[1..].bytes().all(|byte| byte.is_ascii_digit())
 }
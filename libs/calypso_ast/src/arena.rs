@@ -0,0 +1,114 @@
+//! Arena allocation for the AST.
+//!
+//! AST nodes refer to their children using `&'ast mut` references into an
+//! [`Arena`], instead of each node owning its own heap allocation. This means
+//! that a whole tree is freed at once when the arena is dropped, and that
+//! nodes that are allocated close together in time (e.g. during parsing) are
+//! also close together in memory.
+
+use calypso_base::{span::Spanned, symbol::Symbol};
+
+use crate::{
+    expr::{Arm, Attr, Expr, Param},
+    pat::Pat,
+    ty::Ty,
+};
+
+/// An arena that AST nodes can be allocated in.
+///
+/// ```rust
+/// # use calypso_ast::{arena::Arena, expr::{Expr, ExprKind, Primary}, node::NodeId};
+/// # use calypso_base::span::{Span, Spanned};
+/// let arena = Arena::new();
+/// let expr = arena.alloc_expr(Expr::new(
+///     NodeId::DUMMY,
+///     ExprKind::Primary(Spanned::new(Span::new(0, 4), Primary::Bool(true))),
+/// ));
+/// assert_eq!(expr.id, NodeId::DUMMY);
+/// ```
+#[derive(Default)]
+pub struct Arena<'ast> {
+    exprs: typed_arena::Arena<Expr<'ast>>,
+    tys: typed_arena::Arena<Ty<'ast>>,
+    expr_lists: typed_arena::Arena<Spanned<Expr<'ast>>>,
+    ty_lists: typed_arena::Arena<Spanned<Ty<'ast>>>,
+    pat_lists: typed_arena::Arena<Spanned<Pat<'ast>>>,
+    arm_lists: typed_arena::Arena<Spanned<Arm<'ast>>>,
+    attr_lists: typed_arena::Arena<Spanned<Attr<'ast>>>,
+    param_lists: typed_arena::Arena<Spanned<Param<'ast>>>,
+    symbol_lists: typed_arena::Arena<Spanned<Symbol>>,
+}
+
+impl<'ast> Arena<'ast> {
+    /// Create a new, empty arena.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Allocate an expression in the arena.
+    pub fn alloc_expr(&'ast self, expr: Expr<'ast>) -> &'ast mut Expr<'ast> {
+        self.exprs.alloc(expr)
+    }
+
+    /// Allocate a type in the arena.
+    pub fn alloc_ty(&'ast self, ty: Ty<'ast>) -> &'ast mut Ty<'ast> {
+        self.tys.alloc(ty)
+    }
+
+    /// Allocate a list of expressions in the arena, contiguously.
+    pub fn alloc_exprs(
+        &'ast self,
+        exprs: impl IntoIterator<Item = Spanned<Expr<'ast>>>,
+    ) -> &'ast mut [Spanned<Expr<'ast>>] {
+        self.expr_lists.alloc_extend(exprs)
+    }
+
+    /// Allocate a list of types in the arena, contiguously.
+    pub fn alloc_tys(
+        &'ast self,
+        tys: impl IntoIterator<Item = Spanned<Ty<'ast>>>,
+    ) -> &'ast mut [Spanned<Ty<'ast>>] {
+        self.ty_lists.alloc_extend(tys)
+    }
+
+    /// Allocate a list of patterns in the arena, contiguously.
+    pub fn alloc_pats(
+        &'ast self,
+        pats: impl IntoIterator<Item = Spanned<Pat<'ast>>>,
+    ) -> &'ast mut [Spanned<Pat<'ast>>] {
+        self.pat_lists.alloc_extend(pats)
+    }
+
+    /// Allocate a list of `case` arms in the arena, contiguously.
+    pub fn alloc_arms(
+        &'ast self,
+        arms: impl IntoIterator<Item = Spanned<Arm<'ast>>>,
+    ) -> &'ast mut [Spanned<Arm<'ast>>] {
+        self.arm_lists.alloc_extend(arms)
+    }
+
+    /// Allocate a list of attributes in the arena, contiguously.
+    pub fn alloc_attrs(
+        &'ast self,
+        attrs: impl IntoIterator<Item = Spanned<Attr<'ast>>>,
+    ) -> &'ast mut [Spanned<Attr<'ast>>] {
+        self.attr_lists.alloc_extend(attrs)
+    }
+
+    /// Allocate a list of function parameters in the arena, contiguously.
+    pub fn alloc_params(
+        &'ast self,
+        params: impl IntoIterator<Item = Spanned<Param<'ast>>>,
+    ) -> &'ast mut [Spanned<Param<'ast>>] {
+        self.param_lists.alloc_extend(params)
+    }
+
+    /// Allocate a list of symbols in the arena, contiguously.
+    pub fn alloc_symbols(
+        &'ast self,
+        symbols: impl IntoIterator<Item = Spanned<Symbol>>,
+    ) -> &'ast mut [Spanned<Symbol>] {
+        self.symbol_lists.alloc_extend(symbols)
+    }
+}
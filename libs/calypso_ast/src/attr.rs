@@ -0,0 +1,128 @@
+//! Attributes on expressions.
+//!
+//! The only attributes currently are the lint attributes `@allow`, `@warn`,
+//! `@deny` and `@forbid`, which set the level of the lints named by their
+//! arguments within the expression they are attached to, e.g.
+//! `@allow(unused_mut) let mut x = 1 in x`.
+
+use calypso_base::span::{Span, Spanned};
+use calypso_diagnostic::{
+    lint::{Level, LevelSource},
+    prelude::*,
+    report::GlobalReportingCtxt,
+};
+
+use crate::{
+    expr::{Attr, Expr, ExprKind},
+    traverse::{walk_expr, Visitor},
+};
+
+/// Register the lint levels set by the attributes in a sequence of
+/// expressions with `grcx`. Unknown attributes, and attributes that lower
+/// the level of a forbidden lint, are reported as synchronizable errors.
+/// Unknown lints are reported with the
+/// [`unknown_lints`](lint::UNKNOWN_LINTS) lint.
+pub fn register_lint_levels(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Spanned<Expr>],
+) {
+    let mut collector = LintLevelCollector { grcx, file_id };
+    // The collector never errors, and it does not need the source.
+    let _ = exprs
+        .iter()
+        .try_for_each(|expr| collector.visit_expr("", expr.as_ref()));
+}
+
+struct LintLevelCollector<'grcx> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+}
+
+impl LintLevelCollector<'_> {
+    fn register(&mut self, attr: Spanned<&Attr>, scope: Span) {
+        let name = attr.value().name;
+        let Some(level) = Level::from_name(name.value().as_str()) else {
+            self.grcx.report_syncd(
+                EnsembleBuilder::new()
+                    .error(|b| {
+                        b.code("E0017")
+                            .short(err!(E0017, name.value()))
+                            .label(
+                                LabelStyle::Primary,
+                                Some("unknown attribute"),
+                                self.file_id,
+                                name.span(),
+                            )
+                            .note(
+                                "the available attributes are `@allow`, `@warn`, `@deny` and \
+                                 `@forbid`",
+                            )
+                    })
+                    .build(),
+            );
+            return;
+        };
+
+        for arg in attr.value().args.iter() {
+            let Some(lint) = lint::find(arg.value().as_str()) else {
+                self.grcx
+                    .report_lint(&lint::UNKNOWN_LINTS, self.file_id, arg.span(), |b| {
+                        b.short(format!("unknown lint: `{}`", arg.value())).label(
+                            LabelStyle::Primary,
+                            None,
+                            self.file_id,
+                            arg.span(),
+                        )
+                    });
+                continue;
+            };
+
+            let pushed =
+                self.grcx
+                    .lints_mut()
+                    .push_scope(lint, level, self.file_id, scope, attr.span());
+            if let Err(source) = pushed {
+                self.report_forbidden(attr.span(), level, lint.name, source);
+            }
+        }
+    }
+
+    fn report_forbidden(&mut self, span: Span, level: Level, name: &str, source: LevelSource) {
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    let b = b.code("E0018").short(err!(E0018, name)).label(
+                        LabelStyle::Primary,
+                        Some(&format!("overruled by this `@{level}`")),
+                        self.file_id,
+                        span,
+                    );
+                    match source {
+                        LevelSource::Attribute(file_id, attr) => b.label(
+                            LabelStyle::Secondary,
+                            Some("the lint is forbidden here"),
+                            file_id,
+                            attr,
+                        ),
+                        LevelSource::CommandLine => b.note(format!(
+                            "the lint is forbidden on the command line with `-F {name}`"
+                        )),
+                        LevelSource::Default => b,
+                    }
+                })
+                .build(),
+        );
+    }
+}
+
+impl Visitor for LintLevelCollector<'_> {
+    fn visit_expr(&mut self, src: &str, expr: Spanned<&Expr>) -> CalResult<()> {
+        if let ExprKind::Attributed(attrs, _) = &expr.value().kind {
+            for attr in attrs.iter() {
+                self.register(attr.as_ref(), expr.span());
+            }
+        }
+        walk_expr(self, src, expr)
+    }
+}
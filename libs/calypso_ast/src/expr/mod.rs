@@ -2,30 +2,155 @@ use std::fmt::{self, Display};
 
 use calypso_base::{span::Spanned, symbol::Symbol};
 
-use crate::ty::Ty;
+use crate::{node::NodeId, pat::Pat, ty::Ty};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Expr {
-    BinOp(Spanned<Box<Expr>>, Spanned<BinOpKind>, Spanned<Box<Expr>>),
-    UnOp(Spanned<UnOpKind>, Spanned<Box<Expr>>),
+/// An expression.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Expr<'ast> {
+    /// The unique ID of this expression
+    pub id: NodeId,
+    /// The kind of expression
+    pub kind: ExprKind<'ast>,
+}
+
+impl<'ast> Expr<'ast> {
+    /// Create a new expression with the given ID.
+    #[must_use]
+    pub fn new(id: NodeId, kind: ExprKind<'ast>) -> Self {
+        Self { id, kind }
+    }
+
+    /// The precedence of the expression. See [`prec`] for the levels.
+    #[must_use]
+    pub fn precedence(&self) -> u8 {
+        match &self.kind {
+            ExprKind::Let(..)
+            | ExprKind::Assign(..)
+            | ExprKind::Attributed(..)
+            | ExprKind::Fn(..)
+            | ExprKind::Break(_) => prec::LET,
+            ExprKind::BinOp(_, op, _) => op.value().precedence(),
+            ExprKind::UnOp(..) => prec::UNARY,
+            ExprKind::Call(..) => prec::CALL,
+            ExprKind::Primary(_)
+            | ExprKind::Block(_)
+            | ExprKind::Tuple(_)
+            | ExprKind::Case(..)
+            | ExprKind::Loop(_) => prec::TERM,
+        }
+    }
+}
+
+/// Precedence levels of expressions. Higher binds tighter. Binary operators
+/// are between [`LET`](prec::LET) and [`UNARY`](prec::UNARY), see
+/// [`BinOpKind::precedence`].
+pub mod prec {
+    /// The precedence of `let` expressions, assignments, attributed
+    /// expressions, function items, and `break`, which bind the loosest.
+    pub const LET: u8 = 0;
+    /// The precedence of unary expressions.
+    pub const UNARY: u8 = 12;
+    /// The precedence of function calls.
+    pub const CALL: u8 = 13;
+    /// The precedence of primaries, blocks, `case` and `loop` expressions,
+    /// tuples, and parenthesized expressions.
+    pub const TERM: u8 = 14;
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum ExprKind<'ast> {
+    BinOp(
+        Spanned<&'ast mut Expr<'ast>>,
+        Spanned<BinOpKind>,
+        Spanned<&'ast mut Expr<'ast>>,
+    ),
+    UnOp(Spanned<UnOpKind>, Spanned<&'ast mut Expr<'ast>>),
     Primary(Spanned<Primary>),
-    Block(Vec<Spanned<Expr>>),
+    Block(&'ast mut [Spanned<Expr<'ast>>]),
     Let(
         Mutability,
         Spanned<Symbol>,
-        Option<Spanned<Ty>>,
-        Spanned<Box<Expr>>,
-        Spanned<Box<Expr>>,
+        Option<Spanned<Ty<'ast>>>,
+        Spanned<&'ast mut Expr<'ast>>,
+        Spanned<&'ast mut Expr<'ast>>,
+    ),
+    /// `<name> = <value>`
+    Assign(Spanned<Symbol>, Spanned<&'ast mut Expr<'ast>>),
+    /// `(<expr>, ...)`. A tuple with one element is written with a trailing
+    /// comma, e.g. `(x,)`.
+    Tuple(&'ast mut [Spanned<Expr<'ast>>]),
+    /// `case <scrutinee> do <arm>, ... end`. There is always at least one
+    /// arm.
+    Case(
+        Spanned<&'ast mut Expr<'ast>>,
+        &'ast mut [Spanned<Arm<'ast>>],
+    ),
+    /// `<attr> ... <expr>`. The attributes apply to the whole expression.
+    /// There is always at least one attribute.
+    Attributed(
+        &'ast mut [Spanned<Attr<'ast>>],
+        Spanned<&'ast mut Expr<'ast>>,
+    ),
+    /// `<callee>(<arg>, ...)`
+    Call(
+        Spanned<&'ast mut Expr<'ast>>,
+        &'ast mut [Spanned<Expr<'ast>>],
+    ),
+    /// `with(<ty param>, ...) fn <name>(<param>, ...): <ret> -> <body>`. The
+    /// `with` clause and the return type are optional.
+    ///
+    /// Function items can only appear directly in a sequence of expressions,
+    /// i.e. at the top level or in a block.
+    Fn(
+        &'ast mut [Spanned<Symbol>],
+        Spanned<Symbol>,
+        &'ast mut [Spanned<Param<'ast>>],
+        Option<Spanned<Ty<'ast>>>,
+        Spanned<&'ast mut Expr<'ast>>,
     ),
+    /// `loop do <expr>; ... end`. The body is always a block.
+    Loop(Spanned<&'ast mut Expr<'ast>>),
+    /// `break <value>`. The value is optional.
+    Break(Option<Spanned<&'ast mut Expr<'ast>>>),
+}
+
+/// A parameter of a function item, `mut? <name>: <ty>`. The type is
+/// optional.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Param<'ast> {
+    pub mutability: Mutability,
+    pub name: Spanned<Symbol>,
+    pub ty: Option<Spanned<Ty<'ast>>>,
+}
+
+/// An arm of a `case` expression, `<pat> -> <body>`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Arm<'ast> {
+    pub pat: Spanned<Pat<'ast>>,
+    pub body: Spanned<Expr<'ast>>,
+}
+
+/// An attribute, `@<name>(<arg>, ...)`.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Attr<'ast> {
+    pub name: Spanned<Symbol>,
+    pub args: &'ast mut [Spanned<Symbol>],
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Mutability {
     Mut,
     Immut,
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum BinOpKind {
     Add,
     Subtract,
@@ -48,6 +173,39 @@ pub enum BinOpKind {
     GtEq,
 }
 
+impl BinOpKind {
+    /// The precedence of the operator. Higher binds tighter.
+    #[must_use]
+    pub fn precedence(self) -> u8 {
+        match self {
+            Self::LogicalOr => 1,
+            Self::LogicalAnd => 2,
+            Self::BitOr => 3,
+            Self::BitXor => 4,
+            Self::BitAnd => 5,
+            Self::Equal | Self::NotEqual => 6,
+            Self::Lt | Self::Gt | Self::LtEq | Self::GtEq => 7,
+            Self::BitShiftLeft | Self::BitShiftRight => 8,
+            Self::Add | Self::Subtract => 9,
+            Self::Multiply | Self::Divide | Self::Modulo => 10,
+            Self::Exponent => 11,
+        }
+    }
+
+    /// The minimum precedences of the left and right operands of the
+    /// operator. `**` is right-associative, and its left operand is a unary
+    /// expression. Everything else is left-associative.
+    #[must_use]
+    pub fn operand_precedences(self) -> (u8, u8) {
+        let prec = self.precedence();
+        if self == Self::Exponent {
+            (prec::UNARY, prec)
+        } else {
+            (prec, prec + 1)
+        }
+    }
+}
+
 impl Display for BinOpKind {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         match self {
@@ -75,6 +233,7 @@ impl Display for BinOpKind {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum UnOpKind {
     Negative,
     UnaryNot,
@@ -90,6 +249,7 @@ impl Display for UnOpKind {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Primary {
     Number(Numeral),
     Bool(bool),
@@ -97,6 +257,7 @@ pub enum Primary {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Number radixes.
 pub enum Radix {
     /// No prefix (`0d` by default)
@@ -136,6 +297,7 @@ impl Display for Radix {
 }
 
 #[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 /// Number suffixes.
 pub enum Suffix {
     /// `u`
@@ -154,6 +316,7 @@ impl Display for Suffix {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum Numeral {
     Integer {
         suffix: Option<Suffix>,
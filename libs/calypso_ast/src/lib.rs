@@ -3,7 +3,15 @@
 
 // todo(@ThePuzzlemaker: parse|frame):
 //   add span information to AST and parser
+pub mod arena;
+pub mod attr;
 pub mod expr;
+pub mod node;
+pub mod parens;
+pub mod pat;
 pub mod pretty;
+pub mod sexpr;
+pub mod spanless;
 pub mod traverse;
 pub mod ty;
+pub mod validate;
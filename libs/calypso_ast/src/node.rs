@@ -0,0 +1,301 @@
+//! Node identities and the side table mapping them to spans and parents.
+
+use std::cell::Cell;
+use std::fmt::{self, Display};
+
+use calypso_base::span::{Span, Spanned};
+use calypso_error::CalResult;
+
+use crate::{
+    expr::Expr,
+    pat::Pat,
+    traverse::{self, Visitor},
+    ty::Ty,
+};
+
+/// A unique identifier for a node in the AST. These are assigned during
+/// parsing, and are unique within a single parsed source file.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize), serde(transparent))]
+pub struct NodeId(u32);
+
+impl NodeId {
+    /// A placeholder ID for nodes that have not (yet) been assigned one, e.g.
+    /// nodes that were constructed by hand.
+    pub const DUMMY: NodeId = NodeId(u32::MAX);
+
+    /// Create a node ID from its raw index.
+    #[must_use]
+    pub fn from_u32(idx: u32) -> Self {
+        Self(idx)
+    }
+
+    /// Get the raw index of a node ID.
+    #[must_use]
+    pub fn as_u32(self) -> u32 {
+        self.0
+    }
+
+    /// Returns `true` if this is [`NodeId::DUMMY`].
+    #[must_use]
+    pub fn is_dummy(self) -> bool {
+        self == Self::DUMMY
+    }
+}
+
+impl Display for NodeId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "#{}", self.0)
+    }
+}
+
+/// A generator for fresh [`NodeId`]s.
+#[derive(Debug, Default)]
+pub struct NodeIdGen(Cell<u32>);
+
+impl NodeIdGen {
+    /// Create a new generator, starting at `#0`.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Get a fresh node ID.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the generator runs out of IDs.
+    pub fn next_id(&self) -> NodeId {
+        let id = self.0.get();
+        assert!(id < NodeId::DUMMY.0, "ran out of node IDs");
+        self.0.set(id + 1);
+        NodeId(id)
+    }
+
+    /// The number of node IDs that have been generated so far.
+    #[must_use]
+    pub fn len(&self) -> usize {
+        self.0.get() as usize
+    }
+
+    /// Returns `true` if no node IDs have been generated yet.
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.0.get() == 0
+    }
+}
+
+/// The kind of a node in the AST.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum NodeKind {
+    /// An expression
+    Expr,
+    /// A pattern
+    Pat,
+    /// A type
+    Ty,
+}
+
+/// Information about a node, as stored in the [`NodeTable`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct NodeInfo {
+    /// The kind of node
+    pub kind: NodeKind,
+    /// The span of the node
+    pub span: Span,
+    /// The node that directly contains this node, if any
+    pub parent: Option<NodeId>,
+}
+
+/// A side table mapping [`NodeId`]s to their [`NodeInfo`], so that analyses
+/// can walk upwards in the tree and refer to nodes without holding borrows
+/// into the AST.
+#[derive(Clone, Debug, Default)]
+pub struct NodeTable {
+    nodes: Vec<Option<NodeInfo>>,
+}
+
+impl NodeTable {
+    /// Create a new, empty node table.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Build a node table from a list of top-level expressions.
+    ///
+    /// # Errors
+    ///
+    /// This function does not currently error, but it is implemented using a
+    /// [`Visitor`], which may.
+    pub fn build(src: &str, exprs: &[Spanned<Expr>]) -> CalResult<Self> {
+        let mut builder = TableBuilder {
+            table: Self::new(),
+            parents: Vec::new(),
+        };
+        for expr in exprs {
+            builder.visit_expr(src, expr.as_ref())?;
+        }
+        Ok(builder.table)
+    }
+
+    /// Record information about a node. Nodes with a dummy ID are ignored.
+    pub fn insert(&mut self, id: NodeId, info: NodeInfo) {
+        if id.is_dummy() {
+            return;
+        }
+        let idx = id.as_u32() as usize;
+        if idx >= self.nodes.len() {
+            self.nodes.resize(idx + 1, None);
+        }
+        self.nodes[idx] = Some(info);
+    }
+
+    /// Get the information about a node, if it is known.
+    #[must_use]
+    pub fn get(&self, id: NodeId) -> Option<&NodeInfo> {
+        self.nodes.get(id.as_u32() as usize)?.as_ref()
+    }
+
+    /// Get the span of a node, if it is known.
+    #[must_use]
+    pub fn span(&self, id: NodeId) -> Option<Span> {
+        self.get(id).map(|info| info.span)
+    }
+
+    /// Get the parent of a node, if it is known and the node has one.
+    #[must_use]
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.get(id).and_then(|info| info.parent)
+    }
+
+    /// Iterate over the ancestors of a node, starting with its parent and
+    /// ending with the top-level node containing it.
+    pub fn ancestors(&self, id: NodeId) -> impl Iterator<Item = NodeId> + '_ {
+        std::iter::successors(self.parent(id), move |&id| self.parent(id))
+    }
+
+    /// Iterate over all known nodes and their information, in order of ID.
+    pub fn iter(&self) -> impl Iterator<Item = (NodeId, &NodeInfo)> + '_ {
+        self.nodes.iter().enumerate().filter_map(|(idx, info)| {
+            // `insert` guarantees that indices fit in a `u32`.
+            #[allow(clippy::cast_possible_truncation)]
+            info.as_ref().map(|info| (NodeId(idx as u32), info))
+        })
+    }
+}
+
+struct TableBuilder {
+    table: NodeTable,
+    parents: Vec<NodeId>,
+}
+
+impl TableBuilder {
+    fn record(&mut self, id: NodeId, kind: NodeKind, span: Span) {
+        let parent = self.parents.last().copied();
+        self.table.insert(id, NodeInfo { kind, span, parent });
+    }
+}
+
+impl Visitor for TableBuilder {
+    fn visit_expr(&mut self, src: &str, expr: Spanned<&Expr>) -> CalResult<()> {
+        let id = expr.value().id;
+        self.record(id, NodeKind::Expr, expr.span());
+        self.parents.push(id);
+        traverse::walk_expr(self, src, expr)?;
+        self.parents.pop();
+        Ok(())
+    }
+
+    fn visit_pat(&mut self, src: &str, pat: Spanned<&Pat>) -> CalResult<()> {
+        let id = pat.value().id;
+        self.record(id, NodeKind::Pat, pat.span());
+        self.parents.push(id);
+        traverse::walk_pat(self, src, pat)?;
+        self.parents.pop();
+        Ok(())
+    }
+
+    fn visit_ty(&mut self, src: &str, ty: Spanned<&Ty>) -> CalResult<()> {
+        let id = ty.value().id;
+        self.record(id, NodeKind::Ty, ty.span());
+        self.parents.push(id);
+        traverse::walk_ty(self, src, ty)?;
+        self.parents.pop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arena::Arena,
+        expr::{ExprKind, Primary, UnOpKind},
+        pat::PatKind,
+        sexpr,
+    };
+
+    use calypso_base::symbol::Symbol;
+
+    #[test]
+    fn table_records_spans_and_parents() {
+        let arena = Arena::new();
+        let ids = NodeIdGen::new();
+        // `-a`
+        let inner = Expr::new(
+            ids.next_id(),
+            ExprKind::Primary(Spanned::new(
+                Span::new(1, 2),
+                Primary::Symbol(Symbol::intern("a")),
+            )),
+        );
+        let inner_id = inner.id;
+        let outer = Expr::new(
+            ids.next_id(),
+            ExprKind::UnOp(
+                Spanned::new(Span::new(0, 1), UnOpKind::Negative),
+                Spanned::new(Span::new(1, 2), arena.alloc_expr(inner)),
+            ),
+        );
+        let outer_id = outer.id;
+
+        let table = NodeTable::build("-a", &[Spanned::new(Span::new(0, 2), outer)]).unwrap();
+        assert_eq!(ids.len(), 2);
+        assert_eq!(table.span(inner_id), Some(Span::new(1, 2)));
+        assert_eq!(table.parent(inner_id), Some(outer_id));
+        assert_eq!(table.parent(outer_id), None);
+        assert_eq!(
+            table.ancestors(inner_id).collect::<Vec<_>>(),
+            vec![outer_id]
+        );
+    }
+
+    #[test]
+    fn table_records_patterns() {
+        let arena = Arena::new();
+        let src = "(case a ((tuple x _) x))";
+        let exprs = sexpr::read_exprs(src, &NodeIdGen::new(), &arena).unwrap();
+        let table = NodeTable::build(src, &exprs).unwrap();
+
+        let ExprKind::Case(_, arms) = &exprs[0].value().kind else {
+            unreachable!()
+        };
+        let arm = arms[0].value();
+        let pat = &arm.pat;
+        let PatKind::Tuple(fields) = &pat.value().kind else {
+            unreachable!()
+        };
+        let info = table.get(pat.value().id).unwrap();
+        assert_eq!(info.kind, NodeKind::Pat);
+        assert_eq!(info.span, pat.span());
+        assert_eq!(info.parent, Some(exprs[0].value().id));
+        for field in fields.iter() {
+            let info = table.get(field.value().id).unwrap();
+            assert_eq!(info.kind, NodeKind::Pat);
+            assert_eq!(info.parent, Some(pat.value().id));
+        }
+        // The body of the arm is not inside the pattern.
+        assert_eq!(table.parent(arm.body.value().id), Some(exprs[0].value().id));
+    }
+}
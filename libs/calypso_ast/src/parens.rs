@@ -0,0 +1,205 @@
+//! Detection of unnecessary parentheses.
+//!
+//! Parentheses around an expression are not part of the AST, but the span
+//! of a parenthesized expression includes them. Parentheses are unnecessary
+//! if the expression inside them would be parsed the same way without them,
+//! i.e. if its precedence (see [`prec`]) is at least the precedence needed
+//! at its position. These are reported with the
+//! [`unused_parens`](lint::UNUSED_PARENS) lint, once for each unnecessary
+//! pair, so `((x))` is reported twice.
+
+use calypso_base::span::{Span, Spanned};
+use calypso_diagnostic::{
+    diagnostic::{Applicability, Edit},
+    prelude::*,
+    report::GlobalReportingCtxt,
+};
+
+use crate::expr::{prec, Expr, ExprKind};
+
+/// Check for unnecessary parentheses in a sequence of expressions, parsed
+/// from `src`.
+pub fn check_parens(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    src: &str,
+    exprs: &[Spanned<Expr>],
+) {
+    let mut checker = ParenChecker { grcx, file_id, src };
+    for expr in exprs {
+        checker.check(expr.as_ref(), prec::LET);
+    }
+}
+
+struct ParenChecker<'grcx, 'src> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    src: &'src str,
+}
+
+/// Returns `true` if `text` is wrapped in a pair of parentheses, i.e. it
+/// starts with `(` and the matching `)` is at its end.
+fn is_parenthesized(text: &str) -> bool {
+    if !text.starts_with('(') || !text.ends_with(')') {
+        return false;
+    }
+
+    let mut depth = 0_usize;
+    let mut chars = text.char_indices().peekable();
+    while let Some((idx, ch)) = chars.next() {
+        match ch {
+            '(' => depth += 1,
+            ')' => {
+                depth -= 1;
+                if depth == 0 {
+                    return idx == text.len() - 1;
+                }
+            }
+            '/' if matches!(chars.peek(), Some((_, '/'))) => {
+                // Skip line comments, which may contain parentheses.
+                while chars.next_if(|&(_, ch)| ch != '\n').is_some() {}
+            }
+            _ => {}
+        }
+    }
+    false
+}
+
+fn is_ident_char(ch: char) -> bool {
+    ch.is_ascii_alphanumeric() || ch == '_'
+}
+
+fn is_op_char(ch: char) -> bool {
+    "+-*/%&|^!<>=@".contains(ch)
+}
+
+/// Returns `true` if the characters `a` and `b` need a space between them
+/// when they become adjacent, so that they are not lexed as one token.
+fn needs_space(a: Option<char>, b: Option<char>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            (is_ident_char(a) && is_ident_char(b)) || (is_op_char(a) && is_op_char(b))
+        }
+        _ => false,
+    }
+}
+
+impl ParenChecker<'_, '_> {
+    /// Check an expression at a position that needs an expression of at
+    /// least precedence `min_prec`, and its children.
+    fn check(&mut self, expr: Spanned<&Expr>, min_prec: u8) {
+        let mut span = expr.span();
+        // If the expression binds too loosely for its position, the
+        // outermost pair of parentheses is needed, but any others are not.
+        if expr.value().precedence() < min_prec && self.is_parenthesized(span) {
+            span = self.inner(span);
+        }
+        // The innermost pair of parentheses around a tuple is part of it.
+        let is_tuple = matches!(expr.value().kind, ExprKind::Tuple(_));
+        let outer = span;
+        while self.is_parenthesized(span) {
+            let inner = self.inner(span);
+            if is_tuple && !self.is_parenthesized(inner) {
+                break;
+            }
+            self.report(outer, span, inner);
+            span = inner;
+        }
+
+        match &expr.value().kind {
+            ExprKind::BinOp(left, op, right) => {
+                let (left_prec, right_prec) = op.value().operand_precedences();
+                self.check(left.as_ref().map(|x| &**x), left_prec);
+                self.check(right.as_ref().map(|x| &**x), right_prec);
+            }
+            ExprKind::UnOp(_, operand) => {
+                self.check(operand.as_ref().map(|x| &**x), prec::UNARY);
+            }
+            ExprKind::Primary(_) => {}
+            ExprKind::Block(exprs) | ExprKind::Tuple(exprs) => {
+                for expr in exprs.iter() {
+                    self.check(expr.as_ref(), prec::LET);
+                }
+            }
+            ExprKind::Let(_, _, _, value, body) => {
+                // The value of a `let` cannot itself be a `let`.
+                self.check(value.as_ref().map(|x| &**x), prec::LET + 1);
+                self.check(body.as_ref().map(|x| &**x), prec::LET);
+            }
+            ExprKind::Assign(_, value) => self.check(value.as_ref().map(|x| &**x), prec::LET),
+            ExprKind::Case(scrutinee, arms) => {
+                self.check(scrutinee.as_ref().map(|x| &**x), prec::LET);
+                for arm in arms.iter() {
+                    self.check(arm.value().body.as_ref(), prec::LET);
+                }
+            }
+            ExprKind::Attributed(_, expr) => self.check(expr.as_ref().map(|x| &**x), prec::LET),
+            ExprKind::Call(callee, args) => {
+                self.check(callee.as_ref().map(|x| &**x), prec::CALL);
+                for arg in args.iter() {
+                    self.check(arg.as_ref(), prec::LET);
+                }
+            }
+            ExprKind::Fn(.., body) | ExprKind::Loop(body) => {
+                self.check(body.as_ref().map(|x| &**x), prec::LET);
+            }
+            ExprKind::Break(value) => {
+                if let Some(value) = value {
+                    self.check(value.as_ref().map(|x| &**x), prec::LET);
+                }
+            }
+        }
+    }
+
+    fn is_parenthesized(&self, span: Span) -> bool {
+        is_parenthesized(&self.src[span.into_range()])
+    }
+
+    /// The span inside the parentheses at the ends of `span`, without
+    /// surrounding whitespace.
+    fn inner(&self, span: Span) -> Span {
+        let text = &self.src[span.lo() as usize + 1..span.hi() as usize - 1];
+        let len = |text: &str| {
+            u32::try_from(text.len()).expect("source files are at most u32::MAX bytes")
+        };
+        let leading = len(text) - len(text.trim_start());
+        let trailing = len(text) - len(text.trim_end());
+        Span::new(span.lo() + 1 + leading, span.hi() - 1 - trailing)
+    }
+
+    /// Report the parentheses at the ends of `span` around `inner`. `outer`
+    /// is the span of the outermost unnecessary pair around `inner`, which
+    /// is what ends up next to `inner` once they are all removed.
+    fn report(&mut self, outer: Span, span: Span, inner: Span) {
+        let text = &self.src[inner.into_range()];
+        let before = self.src[..outer.lo() as usize].chars().next_back();
+        let after = self.src[outer.hi() as usize..].chars().next();
+        let spacing = |a, b| if needs_space(a, b) { " " } else { "" };
+        let edits = vec![
+            Edit {
+                file_id: self.file_id,
+                span: Span::new(span.lo(), inner.lo()),
+                replacement: spacing(before, text.chars().next()).to_string(),
+            },
+            Edit {
+                file_id: self.file_id,
+                span: Span::new(inner.hi(), span.hi()),
+                replacement: spacing(text.chars().next_back(), after).to_string(),
+            },
+        ];
+
+        let open = Span::new(span.lo(), span.lo() + 1);
+        let close = Span::new(span.hi() - 1, span.hi());
+        self.grcx
+            .report_lint(&lint::UNUSED_PARENS, self.file_id, span, |b| {
+                b.short("unnecessary parentheses")
+                    .label(LabelStyle::Primary, None, self.file_id, open)
+                    .label(LabelStyle::Primary, None, self.file_id, close)
+                    .multipart_suggestion(
+                        edits,
+                        Applicability::MachineApplicable,
+                        "remove these parentheses",
+                    )
+            });
+    }
+}
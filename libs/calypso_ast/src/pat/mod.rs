@@ -0,0 +1,44 @@
+use calypso_base::{span::Spanned, symbol::Symbol};
+
+use crate::{
+    expr::{Mutability, Numeral},
+    node::NodeId,
+};
+
+/// A pattern.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Pat<'ast> {
+    /// The unique ID of this pattern
+    pub id: NodeId,
+    /// The kind of pattern
+    pub kind: PatKind<'ast>,
+}
+
+impl<'ast> Pat<'ast> {
+    /// Create a new pattern with the given ID.
+    #[must_use]
+    pub fn new(id: NodeId, kind: PatKind<'ast>) -> Self {
+        Self { id, kind }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum PatKind<'ast> {
+    /// `_`
+    Wild,
+    /// `mut? <name>`
+    Binding(Mutability, Spanned<Symbol>),
+    /// `true` or `false`
+    Bool(bool),
+    /// A number literal
+    Number(Spanned<Numeral>),
+    /// `-<number>`. The span is that of the number, without the `-`.
+    Neg(Spanned<Numeral>),
+    /// `(<pat>, ...)`
+    Tuple(&'ast mut [Spanned<Pat<'ast>>]),
+    /// `<name>(<pat>, ...)`. There is always at least one field; constructors
+    /// without fields are written as a bare name, which parses as a binding.
+    Ctor(Spanned<Symbol>, &'ast mut [Spanned<Pat<'ast>>]),
+}
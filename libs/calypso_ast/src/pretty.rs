@@ -1,11 +1,12 @@
 use std::fmt::{self, Display, Write};
 
 use crate::{
-    expr::{Expr, Mutability, Primary},
+    expr::{Expr, ExprKind, Mutability, Param, Primary},
+    pat::{Pat, PatKind},
     traverse::Visitor,
-    ty::Ty,
+    ty::{Ty, TyKind},
 };
-use calypso_base::span::Spanned;
+use calypso_base::{span::Spanned, symbol::Symbol};
 use calypso_error::CalResult;
 
 #[derive(Clone, Debug, Default, PartialEq, Eq)]
@@ -18,6 +19,62 @@ impl Display for PrettyPrinter {
     }
 }
 
+impl PrettyPrinter {
+    /// Write the type parameters, parameters and return type of a function
+    /// item, each followed by a space.
+    fn write_fn_sig(
+        &mut self,
+        src: &str,
+        ty_params: &[Spanned<Symbol>],
+        params: &[Spanned<Param>],
+        ret: Option<&Spanned<Ty>>,
+    ) -> CalResult<()> {
+        if !ty_params.is_empty() {
+            write!(self.0, "(with")?;
+            for ty_param in ty_params {
+                write!(self.0, " {}", ty_param.value())?;
+            }
+            write!(self.0, ") ")?;
+        }
+        write!(self.0, "(params")?;
+        for param in params {
+            let param = param.value();
+            write!(self.0, " ")?;
+            match (param.mutability, &param.ty) {
+                (Mutability::Immut, None) => write!(self.0, "{}", param.name.value())?,
+                (Mutability::Mut, None) => write!(self.0, "(mut {})", param.name.value())?,
+                (mutability, Some(ty)) => {
+                    write!(self.0, "(")?;
+                    if mutability == Mutability::Mut {
+                        write!(self.0, "mut ")?;
+                    }
+                    write!(self.0, "{} (ty ", param.name.value())?;
+                    self.visit_ty(src, ty.as_ref())?;
+                    write!(self.0, "))")?;
+                }
+            }
+        }
+        write!(self.0, ") ")?;
+        if let Some(ret) = ret {
+            write!(self.0, "(ty ")?;
+            self.visit_ty(src, ret.as_ref())?;
+            write!(self.0, ") ")?;
+        }
+        Ok(())
+    }
+
+    /// Write `(<head> <expr> ...)`.
+    fn write_list(&mut self, src: &str, head: &str, exprs: &[Spanned<Expr>]) -> CalResult<()> {
+        write!(self.0, "({head}")?;
+        for expr in exprs {
+            write!(self.0, " ")?;
+            self.visit_expr(src, expr.as_ref())?;
+        }
+        write!(self.0, ")")?;
+        Ok(())
+    }
+}
+
 impl Visitor for PrettyPrinter {
     fn visit_primary<'src>(&mut self, src: &'src str, x: Spanned<&Primary>) -> CalResult<()> {
         match *x.value() {
@@ -29,29 +86,22 @@ impl Visitor for PrettyPrinter {
     }
 
     fn visit_expr<'src>(&mut self, src: &'src str, x: Spanned<&Expr>) -> CalResult<()> {
-        match x.value() {
-            Expr::BinOp(left, op, right) => {
+        match &x.value().kind {
+            ExprKind::BinOp(left, op, right) => {
                 write!(self.0, "({} ", op.value())?;
-                self.visit_expr(src, left.as_ref().map(AsRef::as_ref))?;
+                self.visit_expr(src, left.as_ref().map(|x| &**x))?;
                 write!(self.0, " ")?;
-                self.visit_expr(src, right.as_ref().map(AsRef::as_ref))?;
+                self.visit_expr(src, right.as_ref().map(|x| &**x))?;
                 write!(self.0, ")")?;
             }
-            Expr::Primary(primary) => self.visit_primary(src, primary.as_ref())?,
-            Expr::UnOp(op, expr) => {
+            ExprKind::Primary(primary) => self.visit_primary(src, primary.as_ref())?,
+            ExprKind::UnOp(op, expr) => {
                 write!(self.0, "({} ", op.value())?;
-                self.visit_expr(src, expr.as_ref().map(AsRef::as_ref))?;
+                self.visit_expr(src, expr.as_ref().map(|x| &**x))?;
                 write!(self.0, ")")?;
             }
-            Expr::Block(exprs) => {
-                write!(self.0, "(block")?;
-                for expr in exprs {
-                    write!(self.0, " ")?;
-                    self.visit_expr(src, expr.as_ref())?;
-                }
-                write!(self.0, ")")?;
-            }
-            Expr::Let(is_mut, sym, ty, val, expr_in) => {
+            ExprKind::Block(exprs) => self.write_list(src, "block", exprs)?,
+            ExprKind::Let(is_mut, sym, ty, val, expr_in) => {
                 write!(self.0, "(let ")?;
                 if let Mutability::Mut = is_mut {
                     write!(self.0, "mut ")?;
@@ -62,9 +112,68 @@ impl Visitor for PrettyPrinter {
                     self.visit_ty(src, ty.as_ref())?;
                     write!(self.0, ") ")?;
                 }
-                self.visit_expr(src, val.as_ref().map(AsRef::as_ref))?;
+                self.visit_expr(src, val.as_ref().map(|x| &**x))?;
                 write!(self.0, " in ")?;
-                self.visit_expr(src, expr_in.as_ref().map(AsRef::as_ref))?;
+                self.visit_expr(src, expr_in.as_ref().map(|x| &**x))?;
+                write!(self.0, ")")?;
+            }
+            ExprKind::Assign(sym, val) => {
+                write!(self.0, "(assign {} ", sym.value_owned())?;
+                self.visit_expr(src, val.as_ref().map(|x| &**x))?;
+                write!(self.0, ")")?;
+            }
+            ExprKind::Tuple(exprs) => self.write_list(src, "tuple", exprs)?,
+            ExprKind::Case(scrutinee, arms) => {
+                write!(self.0, "(case ")?;
+                self.visit_expr(src, scrutinee.as_ref().map(|x| &**x))?;
+                for arm in arms.iter() {
+                    write!(self.0, " (")?;
+                    self.visit_pat(src, arm.value().pat.as_ref())?;
+                    write!(self.0, " ")?;
+                    self.visit_expr(src, arm.value().body.as_ref())?;
+                    write!(self.0, ")")?;
+                }
+                write!(self.0, ")")?;
+            }
+            ExprKind::Attributed(attrs, expr) => {
+                write!(self.0, "(attrs")?;
+                for attr in attrs.iter() {
+                    write!(self.0, " ({}", attr.value().name.value())?;
+                    for arg in attr.value().args.iter() {
+                        write!(self.0, " {}", arg.value())?;
+                    }
+                    write!(self.0, ")")?;
+                }
+                write!(self.0, " ")?;
+                self.visit_expr(src, expr.as_ref().map(|x| &**x))?;
+                write!(self.0, ")")?;
+            }
+            ExprKind::Call(callee, args) => {
+                write!(self.0, "(call ")?;
+                self.visit_expr(src, callee.as_ref().map(|x| &**x))?;
+                for arg in args.iter() {
+                    write!(self.0, " ")?;
+                    self.visit_expr(src, arg.as_ref())?;
+                }
+                write!(self.0, ")")?;
+            }
+            ExprKind::Fn(ty_params, name, params, ret, body) => {
+                write!(self.0, "(fn {} ", name.value())?;
+                self.write_fn_sig(src, ty_params, params, ret.as_ref())?;
+                self.visit_expr(src, body.as_ref().map(|x| &**x))?;
+                write!(self.0, ")")?;
+            }
+            ExprKind::Loop(body) => {
+                write!(self.0, "(loop ")?;
+                self.visit_expr(src, body.as_ref().map(|x| &**x))?;
+                write!(self.0, ")")?;
+            }
+            ExprKind::Break(value) => {
+                write!(self.0, "(break")?;
+                if let Some(value) = value {
+                    write!(self.0, " ")?;
+                    self.visit_expr(src, value.as_ref().map(|x| &**x))?;
+                }
                 write!(self.0, ")")?;
             }
         }
@@ -72,23 +181,31 @@ impl Visitor for PrettyPrinter {
     }
 
     fn visit_ty<'src>(&mut self, src: &'src str, x: Spanned<&Ty>) -> CalResult<()> {
-        match x.value() {
-            Ty::Symbol(sym) => {
+        match &x.value().kind {
+            TyKind::Symbol(sym) => {
                 write!(self.0, "{}", sym.value())?;
             }
-            Ty::IndefArray(ty) => {
+            TyKind::Apply(sym, tys) => {
+                write!(self.0, "(apply {}", sym.value())?;
+                for ty in tys.iter() {
+                    write!(self.0, " ")?;
+                    self.visit_ty(src, ty.as_ref())?;
+                }
+                write!(self.0, ")")?;
+            }
+            TyKind::IndefArray(ty) => {
                 write!(self.0, "(indef-arr ")?;
-                self.visit_ty(src, ty.as_ref().map(AsRef::as_ref))?;
+                self.visit_ty(src, ty.as_ref().map(|x| &**x))?;
                 write!(self.0, ")")?;
             }
-            Ty::DefArray(ty, num) => {
+            TyKind::DefArray(ty, num) => {
                 write!(self.0, "(def-arr ")?;
-                self.visit_ty(src, ty.as_ref().map(AsRef::as_ref))?;
+                self.visit_ty(src, ty.as_ref().map(|x| &**x))?;
                 write!(self.0, " {})", &src[num.span().into_range()])?;
             }
-            Ty::Tuple(tys) => {
+            TyKind::Tuple(tys) => {
                 write!(self.0, "(tuple")?;
-                for ty in tys {
+                for ty in tys.iter() {
                     write!(self.0, " ")?;
                     self.visit_ty(src, ty.as_ref())?;
                 }
@@ -97,4 +214,32 @@ impl Visitor for PrettyPrinter {
         }
         Ok(())
     }
+
+    fn visit_pat(&mut self, src: &str, x: Spanned<&Pat>) -> CalResult<()> {
+        match &x.value().kind {
+            PatKind::Wild => write!(self.0, "_")?,
+            PatKind::Binding(Mutability::Immut, sym) => write!(self.0, "{}", sym.value())?,
+            PatKind::Binding(Mutability::Mut, sym) => write!(self.0, "(mut {})", sym.value())?,
+            PatKind::Bool(b) => write!(self.0, "{b}")?,
+            PatKind::Number(num) => write!(self.0, "{}", &src[num.span().into_range()])?,
+            PatKind::Neg(num) => write!(self.0, "(- {})", &src[num.span().into_range()])?,
+            PatKind::Tuple(pats) => {
+                write!(self.0, "(tuple")?;
+                for pat in pats.iter() {
+                    write!(self.0, " ")?;
+                    self.visit_pat(src, pat.as_ref())?;
+                }
+                write!(self.0, ")")?;
+            }
+            PatKind::Ctor(name, pats) => {
+                write!(self.0, "(ctor {}", name.value())?;
+                for pat in pats.iter() {
+                    write!(self.0, " ")?;
+                    self.visit_pat(src, pat.as_ref())?;
+                }
+                write!(self.0, ")")?;
+            }
+        }
+        Ok(())
+    }
 }
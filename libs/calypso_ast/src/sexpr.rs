@@ -0,0 +1,717 @@
+//! A reader for the S-expression format emitted by
+//! [`PrettyPrinter`](crate::pretty::PrettyPrinter), e.g.
+//! `(let mut x (ty (indef-arr uint)) (+ 1 2) in (block x))`.
+//!
+//! Function items are written as
+//! `(fn <name> (with <ty param> ...) (params <param> ...) (ty <ret>) <body>)`,
+//! where the `with` clause and the return type are optional, and each
+//! parameter is `<name>`, `(mut <name>)`, or `(mut? <name> (ty <ty>))`.
+//! Negative number patterns are written as `(- <number>)`, and `loop` and
+//! `break` as `(loop <body>)` and `(break <value>?)`.
+//!
+//! This is mainly intended for writing test fixtures. Spans in the resulting
+//! AST point into the S-expression source rather than into any Calypso
+//! source, so printing the result using the S-expression source gives back
+//! the input (modulo whitespace). Use [`SpanlessEq`](crate::spanless::SpanlessEq)
+//! to compare the result with a parsed AST.
+
+use std::fmt::Display;
+
+use calypso_base::{
+    span::{Span, Spanned},
+    symbol::Symbol,
+};
+use calypso_error::{eyre::eyre, CalResult};
+
+use crate::{
+    arena::Arena,
+    expr::{
+        Arm, Attr, BinOpKind, Expr, ExprKind, Mutability, Numeral, Param, Primary, Radix, Suffix,
+        UnOpKind,
+    },
+    node::NodeIdGen,
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+
+/// Read a sequence of expressions in S-expression form, allocating them in
+/// the given arena. Node IDs are assigned in the same order that the parser
+/// assigns them, i.e. children before their parents.
+///
+/// ```rust
+/// # use calypso_ast::{arena::Arena, node::NodeIdGen, pretty::PrettyPrinter, sexpr, traverse::Visitor};
+/// let src = "(let x (+ 1 2) in (- x))";
+/// let arena = Arena::new();
+/// let exprs = sexpr::read_exprs(src, &NodeIdGen::new(), &arena).unwrap();
+///
+/// let mut printer = PrettyPrinter::default();
+/// printer.visit_expr(src, exprs[0].as_ref()).unwrap();
+/// assert_eq!(printer.to_string(), src);
+/// ```
+///
+/// # Errors
+///
+/// This function will error if the input is not well-formed, or if it does
+/// not describe a valid expression.
+pub fn read_exprs<'ast>(
+    src: &str,
+    ids: &NodeIdGen,
+    arena: &'ast Arena<'ast>,
+) -> CalResult<Vec<Spanned<Expr<'ast>>>> {
+    let reader = Reader { ids, arena };
+    read_sexprs(src)?
+        .iter()
+        .map(|sexpr| reader.expr(sexpr))
+        .collect()
+}
+
+#[derive(Debug)]
+enum SExpr<'src> {
+    Atom(Span, &'src str),
+    List(Span, Vec<SExpr<'src>>),
+}
+
+impl SExpr<'_> {
+    fn span(&self) -> Span {
+        match self {
+            SExpr::Atom(span, _) | SExpr::List(span, _) => *span,
+        }
+    }
+}
+
+fn error<T>(span: Span, msg: impl Display) -> CalResult<T> {
+    Err(eyre!("{}..{}: {}", span.lo(), span.hi(), msg).into())
+}
+
+fn span(lo: usize, hi: usize) -> CalResult<Span> {
+    match (u32::try_from(lo), u32::try_from(hi)) {
+        (Ok(lo), Ok(hi)) => Ok(Span::new(lo, hi)),
+        _ => Err(eyre!("S-expression source is too long").into()),
+    }
+}
+
+fn read_sexprs(src: &str) -> CalResult<Vec<SExpr<'_>>> {
+    // The lists that are currently open, along with their start index.
+    let mut open = Vec::new();
+    let mut current = Vec::new();
+    let mut chars = src.char_indices().peekable();
+
+    while let Some((lo, ch)) = chars.next() {
+        match ch {
+            '(' => open.push((lo, std::mem::take(&mut current))),
+            ')' => {
+                let Some((start, parent)) = open.pop() else {
+                    return error(span(lo, lo + 1)?, "unexpected `)`");
+                };
+                let list = SExpr::List(span(start, lo + 1)?, current);
+                current = parent;
+                current.push(list);
+            }
+            ch if ch.is_whitespace() => {}
+            _ => {
+                let mut hi = lo + ch.len_utf8();
+                while let Some(&(idx, ch)) = chars.peek() {
+                    if ch.is_whitespace() || ch == '(' || ch == ')' {
+                        break;
+                    }
+                    hi = idx + ch.len_utf8();
+                    chars.next();
+                }
+                current.push(SExpr::Atom(span(lo, hi)?, &src[lo..hi]));
+            }
+        }
+    }
+
+    if let Some((start, _)) = open.pop() {
+        return error(span(start, start + 1)?, "unclosed `(`");
+    }
+    Ok(current)
+}
+
+struct Reader<'ids, 'ast> {
+    ids: &'ids NodeIdGen,
+    arena: &'ast Arena<'ast>,
+}
+
+impl<'ast> Reader<'_, 'ast> {
+    fn alloc_expr(&self, expr: Spanned<Expr<'ast>>) -> Spanned<&'ast mut Expr<'ast>> {
+        expr.map(|expr| self.arena.alloc_expr(expr))
+    }
+
+    fn alloc_ty(&self, ty: Spanned<Ty<'ast>>) -> Spanned<&'ast mut Ty<'ast>> {
+        ty.map(|ty| self.arena.alloc_ty(ty))
+    }
+
+    fn expr(&self, sexpr: &SExpr) -> CalResult<Spanned<Expr<'ast>>> {
+        let (span, items) = match sexpr {
+            SExpr::Atom(span, atom) => {
+                let primary = Spanned::new(*span, primary(*span, atom)?);
+                return Ok(Spanned::new(
+                    *span,
+                    Expr::new(self.ids.next_id(), ExprKind::Primary(primary)),
+                ));
+            }
+            SExpr::List(span, items) => (*span, items),
+        };
+
+        let Some((SExpr::Atom(head_span, head), args)) = items.split_first() else {
+            return error(span, "expected a form starting with an atom");
+        };
+        let head_span = *head_span;
+        let kind = match (*head, args) {
+            ("block", exprs) => {
+                let exprs = exprs
+                    .iter()
+                    .map(|expr| self.expr(expr))
+                    .collect::<CalResult<Vec<_>>>()?;
+                ExprKind::Block(self.arena.alloc_exprs(exprs))
+            }
+            ("tuple", exprs) => {
+                let exprs = exprs
+                    .iter()
+                    .map(|expr| self.expr(expr))
+                    .collect::<CalResult<Vec<_>>>()?;
+                ExprKind::Tuple(self.arena.alloc_exprs(exprs))
+            }
+            ("case", [scrutinee, arms @ ..]) if !arms.is_empty() => {
+                let scrutinee = self.alloc_expr(self.expr(scrutinee)?);
+                let arms = arms
+                    .iter()
+                    .map(|arm| self.arm(arm))
+                    .collect::<CalResult<Vec<_>>>()?;
+                ExprKind::Case(scrutinee, self.arena.alloc_arms(arms))
+            }
+            ("attrs", [attrs @ .., expr]) if !attrs.is_empty() => {
+                let attrs = attrs
+                    .iter()
+                    .map(|attr| self.attr(attr))
+                    .collect::<CalResult<Vec<_>>>()?;
+                let expr = self.alloc_expr(self.expr(expr)?);
+                ExprKind::Attributed(self.arena.alloc_attrs(attrs), expr)
+            }
+            ("let", args) => self.let_expr(span, args)?,
+            ("call", [callee, args @ ..]) => {
+                let callee = self.alloc_expr(self.expr(callee)?);
+                let args = args
+                    .iter()
+                    .map(|arg| self.expr(arg))
+                    .collect::<CalResult<Vec<_>>>()?;
+                ExprKind::Call(callee, self.arena.alloc_exprs(args))
+            }
+            ("fn", args) => self.fn_item(span, args)?,
+            ("loop", [body]) => ExprKind::Loop(self.alloc_expr(self.expr(body)?)),
+            ("break", []) => ExprKind::Break(None),
+            ("break", [value]) => ExprKind::Break(Some(self.alloc_expr(self.expr(value)?))),
+            ("assign", [SExpr::Atom(name_span, name), value]) if is_ident(name) => {
+                let name = Spanned::new(*name_span, Symbol::intern(name));
+                ExprKind::Assign(name, self.alloc_expr(self.expr(value)?))
+            }
+            (op, [expr]) if unop(op).is_some() => {
+                let op = Spanned::new(head_span, unop(op).unwrap());
+                ExprKind::UnOp(op, self.alloc_expr(self.expr(expr)?))
+            }
+            (op, [left, right]) if binop(op).is_some() => {
+                let op = Spanned::new(head_span, binop(op).unwrap());
+                let left = self.alloc_expr(self.expr(left)?);
+                let right = self.alloc_expr(self.expr(right)?);
+                ExprKind::BinOp(left, op, right)
+            }
+            (head, args) => {
+                return error(
+                    span,
+                    format_args!(
+                        "unknown expression form `{head}` with {} argument(s)",
+                        args.len()
+                    ),
+                )
+            }
+        };
+        Ok(Spanned::new(span, Expr::new(self.ids.next_id(), kind)))
+    }
+
+    /// `(let mut? <name> (ty <ty>)? <value> in <body>)`
+    fn let_expr(&self, span: Span, args: &[SExpr]) -> CalResult<ExprKind<'ast>> {
+        let mut args = args.iter().peekable();
+
+        let mutability = if let Some(SExpr::Atom(_, "mut")) = args.peek() {
+            args.next();
+            Mutability::Mut
+        } else {
+            Mutability::Immut
+        };
+
+        let name = match args.next() {
+            Some(SExpr::Atom(span, name)) if is_ident(name) => {
+                Spanned::new(*span, Symbol::intern(name))
+            }
+            Some(other) => return error(other.span(), "expected a binding name"),
+            None => return error(span, "expected a binding name"),
+        };
+
+        let ty = match args.peek() {
+            Some(SExpr::List(ty_span, items))
+                if matches!(items.first(), Some(SExpr::Atom(_, "ty"))) =>
+            {
+                args.next();
+                match &items[1..] {
+                    [ty] => Some(self.ty(ty)?),
+                    _ => return error(*ty_span, "expected exactly one type"),
+                }
+            }
+            _ => None,
+        };
+
+        let Some(value) = args.next() else {
+            return error(span, "expected a value");
+        };
+        let value = self.alloc_expr(self.expr(value)?);
+
+        match args.next() {
+            Some(SExpr::Atom(_, "in")) => {}
+            Some(other) => return error(other.span(), "expected `in`"),
+            None => return error(span, "expected `in`"),
+        }
+
+        let Some(body) = args.next() else {
+            return error(span, "expected a body");
+        };
+        let body = self.alloc_expr(self.expr(body)?);
+
+        if let Some(extra) = args.next() {
+            return error(extra.span(), "unexpected argument to `let`");
+        }
+
+        Ok(ExprKind::Let(mutability, name, ty, value, body))
+    }
+
+    /// `(fn <name> (with <ty param> ...)? (params <param> ...) (ty <ret>)? <body>)`
+    fn fn_item(&self, span: Span, args: &[SExpr]) -> CalResult<ExprKind<'ast>> {
+        let mut args = args.iter().peekable();
+
+        let name = match args.next() {
+            Some(SExpr::Atom(span, name)) if is_ident(name) => ident(*span, name),
+            Some(other) => return error(other.span(), "expected a function name"),
+            None => return error(span, "expected a function name"),
+        };
+
+        let mut ty_params = Vec::new();
+        if let Some(SExpr::List(_, items)) = args.peek() {
+            if let Some((SExpr::Atom(_, "with"), names)) = items.split_first() {
+                args.next();
+                for name in names {
+                    match name {
+                        SExpr::Atom(span, name) if is_ident(name) => {
+                            ty_params.push(ident(*span, name));
+                        }
+                        other => return error(other.span(), "expected a type parameter"),
+                    }
+                }
+            }
+        }
+
+        let params = match args.next() {
+            Some(SExpr::List(_, items))
+                if matches!(items.first(), Some(SExpr::Atom(_, "params"))) =>
+            {
+                items[1..]
+                    .iter()
+                    .map(|param| self.param(param))
+                    .collect::<CalResult<Vec<_>>>()?
+            }
+            Some(other) => return error(other.span(), "expected a parameter list"),
+            None => return error(span, "expected a parameter list"),
+        };
+
+        let ret = match args.peek() {
+            Some(SExpr::List(ty_span, items))
+                if matches!(items.first(), Some(SExpr::Atom(_, "ty"))) =>
+            {
+                args.next();
+                match &items[1..] {
+                    [ty] => Some(self.ty(ty)?),
+                    _ => return error(*ty_span, "expected exactly one type"),
+                }
+            }
+            _ => None,
+        };
+
+        let Some(body) = args.next() else {
+            return error(span, "expected a body");
+        };
+        let body = self.alloc_expr(self.expr(body)?);
+
+        if let Some(extra) = args.next() {
+            return error(extra.span(), "unexpected argument to `fn`");
+        }
+
+        Ok(ExprKind::Fn(
+            self.arena.alloc_symbols(ty_params),
+            name,
+            self.arena.alloc_params(params),
+            ret,
+            body,
+        ))
+    }
+
+    /// `<name>`, `(mut <name>)`, or `(mut? <name> (ty <ty>))`
+    fn param(&self, sexpr: &SExpr) -> CalResult<Spanned<Param<'ast>>> {
+        let (span, items) = match sexpr {
+            SExpr::Atom(span, name) if is_ident(name) => {
+                let param = Param {
+                    mutability: Mutability::Immut,
+                    name: ident(*span, name),
+                    ty: None,
+                };
+                return Ok(Spanned::new(*span, param));
+            }
+            SExpr::Atom(span, _) => return error(*span, "expected a parameter"),
+            SExpr::List(span, items) => (*span, &items[..]),
+        };
+
+        let (mutability, items) = match items.split_first() {
+            Some((SExpr::Atom(_, "mut"), rest)) => (Mutability::Mut, rest),
+            _ => (Mutability::Immut, items),
+        };
+        let (name, ty) = match items {
+            [SExpr::Atom(name_span, name)] if is_ident(name) => (ident(*name_span, name), None),
+            [SExpr::Atom(name_span, name), SExpr::List(_, ty)] if is_ident(name) => match &ty[..] {
+                [SExpr::Atom(_, "ty"), ty] => (ident(*name_span, name), Some(self.ty(ty)?)),
+                _ => return error(span, "expected a parameter type"),
+            },
+            _ => return error(span, "expected a parameter"),
+        };
+        Ok(Spanned::new(
+            span,
+            Param {
+                mutability,
+                name,
+                ty,
+            },
+        ))
+    }
+
+    /// `(<name> <arg> ...)`
+    fn attr(&self, sexpr: &SExpr) -> CalResult<Spanned<Attr<'ast>>> {
+        let ident = |sexpr: &SExpr| match sexpr {
+            SExpr::Atom(span, name) if is_ident(name) => {
+                Ok(Spanned::new(*span, Symbol::intern(name)))
+            }
+            other => error(other.span(), "expected an identifier"),
+        };
+        match sexpr {
+            SExpr::List(span, items) => match items.split_first() {
+                Some((name, args)) => {
+                    let name = ident(name)?;
+                    let args = args.iter().map(ident).collect::<CalResult<Vec<_>>>()?;
+                    let args = self.arena.alloc_symbols(args);
+                    Ok(Spanned::new(*span, Attr { name, args }))
+                }
+                None => error(*span, "expected an attribute name"),
+            },
+            SExpr::Atom(span, _) => error(*span, "expected an attribute"),
+        }
+    }
+
+    /// `(<pat> <body>)`
+    fn arm(&self, sexpr: &SExpr) -> CalResult<Spanned<Arm<'ast>>> {
+        match sexpr {
+            SExpr::List(span, items) => match &items[..] {
+                [pat, body] => {
+                    let pat = self.pat(pat)?;
+                    let body = self.expr(body)?;
+                    Ok(Spanned::new(*span, Arm { pat, body }))
+                }
+                _ => error(*span, "expected a pattern and a body"),
+            },
+            SExpr::Atom(span, _) => error(*span, "expected a `case` arm"),
+        }
+    }
+
+    fn pat(&self, sexpr: &SExpr) -> CalResult<Spanned<Pat<'ast>>> {
+        let (span, kind) = match sexpr {
+            SExpr::Atom(span, atom) => {
+                let kind = if *atom == "_" {
+                    PatKind::Wild
+                } else {
+                    match primary(*span, atom)? {
+                        Primary::Bool(b) => PatKind::Bool(b),
+                        Primary::Number(numeral) => PatKind::Number(Spanned::new(*span, numeral)),
+                        Primary::Symbol(sym) => {
+                            PatKind::Binding(Mutability::Immut, Spanned::new(*span, sym))
+                        }
+                    }
+                };
+                (*span, kind)
+            }
+            SExpr::List(span, items) => {
+                let kind = match items.split_first() {
+                    Some((SExpr::Atom(_, "mut"), [SExpr::Atom(name_span, name)]))
+                        if is_ident(name) =>
+                    {
+                        let name = Spanned::new(*name_span, Symbol::intern(name));
+                        PatKind::Binding(Mutability::Mut, name)
+                    }
+                    Some((SExpr::Atom(_, "tuple"), pats)) => {
+                        let pats = pats
+                            .iter()
+                            .map(|pat| self.pat(pat))
+                            .collect::<CalResult<Vec<_>>>()?;
+                        PatKind::Tuple(self.arena.alloc_pats(pats))
+                    }
+                    Some((SExpr::Atom(_, "-"), [SExpr::Atom(num_span, num)])) => {
+                        match primary(*num_span, num)? {
+                            Primary::Number(numeral) => {
+                                PatKind::Neg(Spanned::new(*num_span, numeral))
+                            }
+                            _ => return error(*num_span, "expected a number"),
+                        }
+                    }
+                    Some((SExpr::Atom(_, "ctor"), [SExpr::Atom(name_span, name), pats @ ..]))
+                        if is_ident(name) && !pats.is_empty() =>
+                    {
+                        let pats = pats
+                            .iter()
+                            .map(|pat| self.pat(pat))
+                            .collect::<CalResult<Vec<_>>>()?;
+                        PatKind::Ctor(ident(*name_span, name), self.arena.alloc_pats(pats))
+                    }
+                    _ => return error(*span, "unknown pattern form"),
+                };
+                (*span, kind)
+            }
+        };
+        Ok(Spanned::new(span, Pat::new(self.ids.next_id(), kind)))
+    }
+
+    fn ty(&self, sexpr: &SExpr) -> CalResult<Spanned<Ty<'ast>>> {
+        let (span, items) = match sexpr {
+            SExpr::Atom(span, name) if is_ident(name) => {
+                let kind = TyKind::Symbol(Spanned::new(*span, Symbol::intern(name)));
+                return Ok(Spanned::new(*span, Ty::new(self.ids.next_id(), kind)));
+            }
+            SExpr::Atom(span, name) => return error(*span, format_args!("invalid type `{name}`")),
+            SExpr::List(span, items) => (*span, items),
+        };
+
+        let kind = match items.split_first() {
+            Some((SExpr::Atom(_, "apply"), [SExpr::Atom(name_span, name), tys @ ..]))
+                if is_ident(name) && !tys.is_empty() =>
+            {
+                let tys = tys
+                    .iter()
+                    .map(|ty| self.ty(ty))
+                    .collect::<CalResult<Vec<_>>>()?;
+                TyKind::Apply(
+                    Spanned::new(*name_span, Symbol::intern(name)),
+                    self.arena.alloc_tys(tys),
+                )
+            }
+            Some((SExpr::Atom(_, "indef-arr"), [ty])) => {
+                TyKind::IndefArray(self.alloc_ty(self.ty(ty)?))
+            }
+            Some((SExpr::Atom(_, "def-arr"), [ty, SExpr::Atom(len_span, len)])) => {
+                let ty = self.alloc_ty(self.ty(ty)?);
+                // Any numeral is accepted here, as the AST allows it. See
+                // `validate` for the stricter check.
+                match numeral(len) {
+                    Some(len) => TyKind::DefArray(ty, Spanned::new(*len_span, len)),
+                    None => return error(*len_span, "expected a numeric array length"),
+                }
+            }
+            Some((SExpr::Atom(_, "tuple"), tys)) => {
+                let tys = tys
+                    .iter()
+                    .map(|ty| self.ty(ty))
+                    .collect::<CalResult<Vec<_>>>()?;
+                TyKind::Tuple(self.arena.alloc_tys(tys))
+            }
+            _ => return error(span, "unknown type form"),
+        };
+        Ok(Spanned::new(span, Ty::new(self.ids.next_id(), kind)))
+    }
+}
+
+fn primary(span: Span, atom: &str) -> CalResult<Primary> {
+    match atom {
+        "true" => Ok(Primary::Bool(true)),
+        "false" => Ok(Primary::Bool(false)),
+        _ if atom.starts_with(|ch: char| ch.is_ascii_digit()) => match numeral(atom) {
+            Some(numeral) => Ok(Primary::Number(numeral)),
+            None => error(span, format_args!("invalid number `{atom}`")),
+        },
+        _ if is_ident(atom) => Ok(Primary::Symbol(Symbol::intern(atom))),
+        _ => error(span, format_args!("unexpected atom `{atom}`")),
+    }
+}
+
+fn ident(span: Span, name: &str) -> Spanned<Symbol> {
+    Spanned::new(span, Symbol::intern(name))
+}
+
+/// Check that a string is a valid identifier, using the same rules as the
+/// lexer.
+pub(crate) fn is_ident(atom: &str) -> bool {
+    let mut chars = atom.chars();
+    let valid_start = match chars.next() {
+        Some('_') => atom.len() > 1,
+        Some(ch) => ch.is_ascii_alphabetic(),
+        None => false,
+    };
+    valid_start
+        && chars.all(|ch| ch.is_ascii_alphanumeric() || ch == '_')
+        && !Symbol::intern(atom).is_keyword()
+}
+
+/// Classify a number literal, using the same rules as the lexer.
+fn numeral(atom: &str) -> Option<Numeral> {
+    let (radix, digits) = match atom.get(..2) {
+        Some("0x") => (Radix::Hexadecimal, &atom[2..]),
+        Some("0o") => (Radix::Octal, &atom[2..]),
+        Some("0b") => (Radix::Binary, &atom[2..]),
+        Some("0d") => (Radix::Decimal, &atom[2..]),
+        _ => (Radix::None, atom),
+    };
+
+    let (suffix, digits) = match digits.as_bytes().last() {
+        Some(b's') => (Some(Suffix::Sint), &digits[..digits.len() - 1]),
+        Some(b'u') => (Some(Suffix::Uint), &digits[..digits.len() - 1]),
+        Some(b'f') if radix == Radix::None => {
+            let digits = &digits[..digits.len() - 1];
+            return is_digits(digits, 10).then_some(Numeral::Float { from_integer: true });
+        }
+        _ => (None, digits),
+    };
+
+    if is_digits(digits, radix.radix()) {
+        return Some(Numeral::Integer { suffix, radix });
+    }
+
+    // Floats: `1.0`, `1.0e5`, `1e-5`
+    if radix != Radix::None || suffix.is_some() {
+        return None;
+    }
+    let (mantissa, exponent) = match digits.split_once('e') {
+        Some((mantissa, exponent)) => (mantissa, Some(exponent)),
+        None => (digits, None),
+    };
+    let valid_mantissa = match mantissa.split_once('.') {
+        Some((int, frac)) => is_digits(int, 10) && is_digits(frac, 10),
+        None => exponent.is_some() && is_digits(mantissa, 10),
+    };
+    let valid_exponent = exponent.map_or(true, |exponent| {
+        is_digits(exponent.trim_start_matches(['+', '-']), 10)
+    });
+    (valid_mantissa && valid_exponent).then_some(Numeral::Float {
+        from_integer: false,
+    })
+}
+
+/// Check that a string is a non-empty sequence of digits (and underscores,
+/// but not at the start) in the given radix.
+fn is_digits(digits: &str, radix: u32) -> bool {
+    digits.starts_with(|ch: char| ch.is_digit(radix))
+        && digits.chars().all(|ch| ch == '_' || ch.is_digit(radix))
+}
+
+fn unop(op: &str) -> Option<UnOpKind> {
+    Some(match op {
+        "-" => UnOpKind::Negative,
+        "!" => UnOpKind::UnaryNot,
+        _ => return None,
+    })
+}
+
+fn binop(op: &str) -> Option<BinOpKind> {
+    Some(match op {
+        "+" => BinOpKind::Add,
+        "-" => BinOpKind::Subtract,
+        "*" => BinOpKind::Multiply,
+        "/" => BinOpKind::Divide,
+        "%" => BinOpKind::Modulo,
+        "**" => BinOpKind::Exponent,
+        "||" => BinOpKind::LogicalOr,
+        "&&" => BinOpKind::LogicalAnd,
+        "|" => BinOpKind::BitOr,
+        "^" => BinOpKind::BitXor,
+        "&" => BinOpKind::BitAnd,
+        "<<" => BinOpKind::BitShiftLeft,
+        ">>" => BinOpKind::BitShiftRight,
+        "==" => BinOpKind::Equal,
+        "!=" => BinOpKind::NotEqual,
+        "<" => BinOpKind::Lt,
+        ">" => BinOpKind::Gt,
+        "<=" => BinOpKind::LtEq,
+        ">=" => BinOpKind::GtEq,
+        _ => return None,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{pretty::PrettyPrinter, traverse::Visitor};
+
+    fn round_trip(src: &str) -> String {
+        let arena = Arena::new();
+        let exprs = read_exprs(src, &NodeIdGen::new(), &arena).unwrap();
+        exprs
+            .iter()
+            .map(|expr| {
+                let mut printer = PrettyPrinter::default();
+                printer.visit_expr(src, expr.as_ref()).unwrap();
+                printer.to_string()
+            })
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    #[test]
+    fn reads_printer_output() {
+        for src in [
+            "(let x (ty (def-arr (tuple uint sint) 3)) (+ 1 (** 2 (** 3 4))) in (block x (- x) (! true)))",
+            "(let mut y (ty (indef-arr bool)) (<= 0x1fu 1.5e-3) in (- a b))",
+            "(let z (ty (apply Result (indef-arr T) (tuple))) z in z)",
+            "(block)\n_x",
+            "(case (tuple x (tuple) (tuple 1)) ((tuple _ (tuple) (tuple (mut y))) y) (z (! z)))",
+            "(case b (true 0x1u) (false 2))",
+            "(attrs (allow unused_mut) (deny a b) (let mut x 1 in x))",
+            "(block (fn id (with T) (params (x (ty T))) (ty T) x) (call id (call id 1)))",
+            "(fn f (params x (mut y) (mut z (ty (apply Option uint)))) (+ x y))",
+            "(case x ((ctor Some (ctor Ok y)) y) (None 0))",
+            "(case x ((- 1) 0) ((tuple (- 0x2s) (- 1.5)) 1) (_ 2))",
+            "(loop (block (break) (break (+ x 1))))",
+        ] {
+            assert_eq!(round_trip(src), src);
+        }
+    }
+
+    #[test]
+    fn rejects_malformed_input() {
+        let arena = Arena::new();
+        for src in [
+            "(+ 1",
+            ")",
+            "(frob 1)",
+            "(let in 1 2)",
+            "(- 1 2 3)",
+            "1x",
+            "(case x)",
+            "(case x (+ 1))",
+            "(attrs x)",
+            "(attrs (1) x)",
+            "(fn f x)",
+            "(fn (params) x)",
+            "(fn f (params 1) x)",
+            "(fn f (params) (ty uint) x y)",
+            "(case x ((ctor Some) 1))",
+            "(case x ((- y) 1))",
+            "(loop)",
+            "(break 1 2)",
+        ] {
+            assert!(
+                read_exprs(src, &NodeIdGen::new(), &arena).is_err(),
+                "{src:?} should not be accepted"
+            );
+        }
+    }
+}
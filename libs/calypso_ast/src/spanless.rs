@@ -0,0 +1,211 @@
+//! Structural equality of ASTs, ignoring spans and node IDs.
+
+use calypso_base::span::Spanned;
+
+use crate::{
+    expr::{Expr, ExprKind, Numeral, Primary},
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+
+/// Compares two ASTs structurally, ignoring spans and node IDs. This is
+/// useful for comparing a parsed AST with one read from an S-expression
+/// fixture (see [`sexpr`](crate::sexpr)).
+///
+/// As the AST does not store the values of number literals, these are
+/// compared using their text in the respective sources.
+#[derive(Copy, Clone, Debug)]
+pub struct SpanlessEq<'src> {
+    left_src: &'src str,
+    right_src: &'src str,
+}
+
+impl<'src> SpanlessEq<'src> {
+    /// Create a comparator for ASTs from the given sources.
+    #[must_use]
+    pub fn new(left_src: &'src str, right_src: &'src str) -> Self {
+        Self {
+            left_src,
+            right_src,
+        }
+    }
+
+    /// Compare two lists of expressions.
+    #[must_use]
+    pub fn eq_exprs(self, left: &[Spanned<Expr>], right: &[Spanned<Expr>]) -> bool {
+        left.len() == right.len()
+            && left
+                .iter()
+                .zip(right)
+                .all(|(left, right)| self.eq_expr(left.value(), right.value()))
+    }
+
+    /// Compare two expressions.
+    #[must_use]
+    pub fn eq_expr(self, left: &Expr, right: &Expr) -> bool {
+        match (&left.kind, &right.kind) {
+            (ExprKind::BinOp(ll, lop, lr), ExprKind::BinOp(rl, rop, rr)) => {
+                lop.value() == rop.value()
+                    && self.eq_expr(ll.value(), rl.value())
+                    && self.eq_expr(lr.value(), rr.value())
+            }
+            (ExprKind::UnOp(lop, le), ExprKind::UnOp(rop, re)) => {
+                lop.value() == rop.value() && self.eq_expr(le.value(), re.value())
+            }
+            (ExprKind::Primary(left), ExprKind::Primary(right)) => self.eq_primary(*left, *right),
+            (ExprKind::Block(left), ExprKind::Block(right))
+            | (ExprKind::Tuple(left), ExprKind::Tuple(right)) => self.eq_exprs(left, right),
+            (
+                ExprKind::Let(lmut, lsym, lty, lval, lbody),
+                ExprKind::Let(rmut, rsym, rty, rval, rbody),
+            ) => {
+                lmut == rmut
+                    && lsym.value() == rsym.value()
+                    && self.eq_opt_ty(lty.as_ref(), rty.as_ref())
+                    && self.eq_expr(lval.value(), rval.value())
+                    && self.eq_expr(lbody.value(), rbody.value())
+            }
+            (ExprKind::Assign(lsym, lval), ExprKind::Assign(rsym, rval)) => {
+                lsym.value() == rsym.value() && self.eq_expr(lval.value(), rval.value())
+            }
+            (ExprKind::Case(lscrut, larms), ExprKind::Case(rscrut, rarms)) => {
+                self.eq_expr(lscrut.value(), rscrut.value())
+                    && larms.len() == rarms.len()
+                    && larms.iter().zip(rarms.iter()).all(|(left, right)| {
+                        self.eq_pat(left.value().pat.value(), right.value().pat.value())
+                            && self.eq_expr(left.value().body.value(), right.value().body.value())
+                    })
+            }
+            (ExprKind::Attributed(lattrs, le), ExprKind::Attributed(rattrs, re)) => {
+                lattrs.len() == rattrs.len()
+                    && lattrs.iter().zip(rattrs.iter()).all(|(left, right)| {
+                        let (left, right) = (left.value(), right.value());
+                        left.name.value() == right.name.value()
+                            && left.args.len() == right.args.len()
+                            && left
+                                .args
+                                .iter()
+                                .zip(right.args.iter())
+                                .all(|(left, right)| left.value() == right.value())
+                    })
+                    && self.eq_expr(le.value(), re.value())
+            }
+            (ExprKind::Call(lcallee, largs), ExprKind::Call(rcallee, rargs)) => {
+                self.eq_expr(lcallee.value(), rcallee.value()) && self.eq_exprs(largs, rargs)
+            }
+            (
+                ExprKind::Fn(lgenerics, lname, lparams, lret, lbody),
+                ExprKind::Fn(rgenerics, rname, rparams, rret, rbody),
+            ) => {
+                lname.value() == rname.value()
+                    && lgenerics.len() == rgenerics.len()
+                    && lgenerics
+                        .iter()
+                        .zip(rgenerics.iter())
+                        .all(|(left, right)| left.value() == right.value())
+                    && lparams.len() == rparams.len()
+                    && lparams.iter().zip(rparams.iter()).all(|(left, right)| {
+                        let (left, right) = (left.value(), right.value());
+                        left.mutability == right.mutability
+                            && left.name.value() == right.name.value()
+                            && self.eq_opt_ty(left.ty.as_ref(), right.ty.as_ref())
+                    })
+                    && self.eq_opt_ty(lret.as_ref(), rret.as_ref())
+                    && self.eq_expr(lbody.value(), rbody.value())
+            }
+            (ExprKind::Loop(lbody), ExprKind::Loop(rbody)) => {
+                self.eq_expr(lbody.value(), rbody.value())
+            }
+            (ExprKind::Break(lvalue), ExprKind::Break(rvalue)) => match (lvalue, rvalue) {
+                (Some(left), Some(right)) => self.eq_expr(left.value(), right.value()),
+                (None, None) => true,
+                _ => false,
+            },
+            _ => false,
+        }
+    }
+
+    fn eq_opt_ty(self, left: Option<&Spanned<Ty>>, right: Option<&Spanned<Ty>>) -> bool {
+        match (left, right) {
+            (Some(left), Some(right)) => self.eq_ty(left.value(), right.value()),
+            (None, None) => true,
+            _ => false,
+        }
+    }
+
+    /// Compare two types.
+    #[must_use]
+    pub fn eq_ty(self, left: &Ty, right: &Ty) -> bool {
+        match (&left.kind, &right.kind) {
+            (TyKind::Symbol(left), TyKind::Symbol(right)) => left.value() == right.value(),
+            (TyKind::Apply(lsym, left), TyKind::Apply(rsym, right)) => {
+                lsym.value() == rsym.value()
+                    && left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(left, right)| self.eq_ty(left.value(), right.value()))
+            }
+            (TyKind::IndefArray(left), TyKind::IndefArray(right)) => {
+                self.eq_ty(left.value(), right.value())
+            }
+            (TyKind::DefArray(lty, llen), TyKind::DefArray(rty, rlen)) => {
+                self.eq_numeral(*llen, *rlen) && self.eq_ty(lty.value(), rty.value())
+            }
+            (TyKind::Tuple(left), TyKind::Tuple(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(left, right)| self.eq_ty(left.value(), right.value()))
+            }
+            _ => false,
+        }
+    }
+
+    /// Compare two patterns.
+    #[must_use]
+    pub fn eq_pat(self, left: &Pat, right: &Pat) -> bool {
+        match (&left.kind, &right.kind) {
+            (PatKind::Wild, PatKind::Wild) => true,
+            (PatKind::Binding(lmut, lsym), PatKind::Binding(rmut, rsym)) => {
+                lmut == rmut && lsym.value() == rsym.value()
+            }
+            (PatKind::Bool(left), PatKind::Bool(right)) => left == right,
+            (PatKind::Number(lnum), PatKind::Number(rnum))
+            | (PatKind::Neg(lnum), PatKind::Neg(rnum)) => self.eq_numeral(*lnum, *rnum),
+            (PatKind::Ctor(lname, left), PatKind::Ctor(rname, right)) => {
+                lname.value() == rname.value()
+                    && left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(left, right)| self.eq_pat(left.value(), right.value()))
+            }
+            (PatKind::Tuple(left), PatKind::Tuple(right)) => {
+                left.len() == right.len()
+                    && left
+                        .iter()
+                        .zip(right.iter())
+                        .all(|(left, right)| self.eq_pat(left.value(), right.value()))
+            }
+            _ => false,
+        }
+    }
+
+    fn eq_primary(self, left: Spanned<Primary>, right: Spanned<Primary>) -> bool {
+        match (left.value_owned(), right.value_owned()) {
+            (Primary::Number(lnum), Primary::Number(rnum)) => self.eq_numeral(
+                Spanned::new(left.span(), lnum),
+                Spanned::new(right.span(), rnum),
+            ),
+            (left, right) => left == right,
+        }
+    }
+
+    fn eq_numeral(self, left: Spanned<Numeral>, right: Spanned<Numeral>) -> bool {
+        left.value() == right.value()
+            && self.left_src.get(left.span().into_range())
+                == self.right_src.get(right.span().into_range())
+    }
+}
@@ -1,39 +1,762 @@
+//! Traversal of the AST.
+//!
+//! There are three ways of traversing the AST, each with a trait:
+//!
+//! - [`Visitor`]: walk the AST by shared reference.
+//! - [`VisitorMut`]: walk the AST by mutable reference, in order to rewrite
+//!   nodes in-place.
+//! - [`Folder`]: consume the AST and rebuild it.
+//!
+//! Each method of these traits has a default implementation that recurses
+//! into the children of the node using the corresponding `walk_*` (or
+//! `noop_fold_*`) function. Implementations can override only the nodes they
+//! care about, and call the `walk_*` function themselves in order to keep
+//! recursing.
+
+use std::mem;
+
 use crate::{
-    expr::{Expr, Primary},
-    ty::Ty,
+    expr::{Expr, ExprKind, Primary},
+    node::NodeId,
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
 };
-use calypso_base::span::Spanned;
+use calypso_base::span::{Span, Spanned};
 use calypso_error::CalResult;
 
 pub trait Visitor {
-    /// Visit an expression.
+    /// Visit an expression. By default, this calls [`walk_expr`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_expr<'src>(&mut self, src: &'src str, expr: Spanned<&Expr>) -> CalResult<()> {
+        walk_expr(self, src, expr)
+    }
+
+    /// Visit a primary value. By default, this calls [`walk_primary`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_primary<'src>(&mut self, src: &'src str, primary: Spanned<&Primary>) -> CalResult<()> {
+        walk_primary(self, src, primary)
+    }
+
+    /// Visit a type. By default, this calls [`walk_ty`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_ty<'src>(&mut self, src: &'src str, ty: Spanned<&Ty>) -> CalResult<()> {
+        walk_ty(self, src, ty)
+    }
+
+    /// Visit a pattern. By default, this calls [`walk_pat`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_pat(&mut self, src: &str, pat: Spanned<&Pat>) -> CalResult<()> {
+        walk_pat(self, src, pat)
+    }
+}
+
+/// Visit all of the children of an expression, in source order.
+///
+/// # Errors
+///
+/// This function errors if visiting any of the children errors.
+pub fn walk_expr<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    src: &str,
+    expr: Spanned<&Expr>,
+) -> CalResult<()> {
+    match &expr.value_owned().kind {
+        ExprKind::BinOp(left, _, right) => {
+            visitor.visit_expr(src, left.as_ref().map(|x| &**x))?;
+            visitor.visit_expr(src, right.as_ref().map(|x| &**x))
+        }
+        ExprKind::UnOp(_, expr) => visitor.visit_expr(src, expr.as_ref().map(|x| &**x)),
+        ExprKind::Primary(primary) => visitor.visit_primary(src, primary.as_ref()),
+        ExprKind::Block(exprs) => exprs
+            .iter()
+            .try_for_each(|expr| visitor.visit_expr(src, expr.as_ref())),
+        ExprKind::Let(_, _, ty, val, expr_in) => {
+            if let Some(ty) = ty {
+                visitor.visit_ty(src, ty.as_ref())?;
+            }
+            visitor.visit_expr(src, val.as_ref().map(|x| &**x))?;
+            visitor.visit_expr(src, expr_in.as_ref().map(|x| &**x))
+        }
+        ExprKind::Assign(_, val) => visitor.visit_expr(src, val.as_ref().map(|x| &**x)),
+        ExprKind::Tuple(exprs) => exprs
+            .iter()
+            .try_for_each(|expr| visitor.visit_expr(src, expr.as_ref())),
+        ExprKind::Case(scrutinee, arms) => {
+            visitor.visit_expr(src, scrutinee.as_ref().map(|x| &**x))?;
+            arms.iter().try_for_each(|arm| {
+                visitor.visit_pat(src, arm.value().pat.as_ref())?;
+                visitor.visit_expr(src, arm.value().body.as_ref())
+            })
+        }
+        ExprKind::Attributed(_, expr) => visitor.visit_expr(src, expr.as_ref().map(|x| &**x)),
+        ExprKind::Call(callee, args) => {
+            visitor.visit_expr(src, callee.as_ref().map(|x| &**x))?;
+            args.iter()
+                .try_for_each(|arg| visitor.visit_expr(src, arg.as_ref()))
+        }
+        ExprKind::Fn(_, _, params, ret, body) => {
+            for ty in params.iter().filter_map(|param| param.value().ty.as_ref()) {
+                visitor.visit_ty(src, ty.as_ref())?;
+            }
+            if let Some(ret) = ret {
+                visitor.visit_ty(src, ret.as_ref())?;
+            }
+            visitor.visit_expr(src, body.as_ref().map(|x| &**x))
+        }
+        ExprKind::Loop(body) => visitor.visit_expr(src, body.as_ref().map(|x| &**x)),
+        ExprKind::Break(value) => value.as_ref().map_or(Ok(()), |value| {
+            visitor.visit_expr(src, value.as_ref().map(|x| &**x))
+        }),
+    }
+}
+
+/// Visit all of the children of a primary value. Primary values currently
+/// have no children, so this does nothing.
+///
+/// # Errors
+///
+/// This function does not currently error.
+pub fn walk_primary<V: Visitor + ?Sized>(
+    _visitor: &mut V,
+    _src: &str,
+    _primary: Spanned<&Primary>,
+) -> CalResult<()> {
+    Ok(())
+}
+
+/// Visit all of the children of a type, in source order.
+///
+/// # Errors
+///
+/// This function errors if visiting any of the children errors.
+pub fn walk_ty<V: Visitor + ?Sized>(visitor: &mut V, src: &str, ty: Spanned<&Ty>) -> CalResult<()> {
+    match &ty.value_owned().kind {
+        TyKind::Symbol(_) => Ok(()),
+        TyKind::IndefArray(ty) | TyKind::DefArray(ty, _) => {
+            visitor.visit_ty(src, ty.as_ref().map(|x| &**x))
+        }
+        TyKind::Apply(_, tys) | TyKind::Tuple(tys) => tys
+            .iter()
+            .try_for_each(|ty| visitor.visit_ty(src, ty.as_ref())),
+    }
+}
+
+/// Visit all of the children of a pattern, in source order.
+///
+/// # Errors
+///
+/// This function errors if visiting any of the children errors.
+pub fn walk_pat<V: Visitor + ?Sized>(
+    visitor: &mut V,
+    src: &str,
+    pat: Spanned<&Pat>,
+) -> CalResult<()> {
+    match &pat.value_owned().kind {
+        PatKind::Wild
+        | PatKind::Binding(..)
+        | PatKind::Bool(_)
+        | PatKind::Number(_)
+        | PatKind::Neg(_) => Ok(()),
+        PatKind::Tuple(pats) | PatKind::Ctor(_, pats) => pats
+            .iter()
+            .try_for_each(|pat| visitor.visit_pat(src, pat.as_ref())),
+    }
+}
+
+pub trait VisitorMut<'ast> {
+    /// Visit an expression mutably. By default, this calls
+    /// [`walk_expr_mut`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_expr(&mut self, src: &str, expr: Spanned<&mut Expr<'ast>>) -> CalResult<()> {
+        walk_expr_mut(self, src, expr)
+    }
+
+    /// Visit a primary value mutably. By default, this calls
+    /// [`walk_primary_mut`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_primary(&mut self, src: &str, primary: Spanned<&mut Primary>) -> CalResult<()> {
+        walk_primary_mut(self, src, primary)
+    }
+
+    /// Visit a type mutably. By default, this calls [`walk_ty_mut`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_ty(&mut self, src: &str, ty: Spanned<&mut Ty<'ast>>) -> CalResult<()> {
+        walk_ty_mut(self, src, ty)
+    }
+
+    /// Visit a pattern mutably. By default, this calls [`walk_pat_mut`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn visit_pat(&mut self, src: &str, pat: Spanned<&mut Pat<'ast>>) -> CalResult<()> {
+        walk_pat_mut(self, src, pat)
+    }
+}
+
+/// Mutably visit all of the children of an expression, in source order.
+///
+/// # Errors
+///
+/// This function errors if visiting any of the children errors.
+pub fn walk_expr_mut<'ast, V: VisitorMut<'ast> + ?Sized>(
+    visitor: &mut V,
+    src: &str,
+    expr: Spanned<&mut Expr<'ast>>,
+) -> CalResult<()> {
+    match &mut expr.value_owned().kind {
+        ExprKind::BinOp(left, _, right) => {
+            visitor.visit_expr(src, left.as_mut().map(|x| &mut **x))?;
+            visitor.visit_expr(src, right.as_mut().map(|x| &mut **x))
+        }
+        ExprKind::UnOp(_, expr) => visitor.visit_expr(src, expr.as_mut().map(|x| &mut **x)),
+        ExprKind::Primary(primary) => visitor.visit_primary(src, primary.as_mut()),
+        ExprKind::Block(exprs) => exprs
+            .iter_mut()
+            .try_for_each(|expr| visitor.visit_expr(src, expr.as_mut())),
+        ExprKind::Let(_, _, ty, val, expr_in) => {
+            if let Some(ty) = ty {
+                visitor.visit_ty(src, ty.as_mut())?;
+            }
+            visitor.visit_expr(src, val.as_mut().map(|x| &mut **x))?;
+            visitor.visit_expr(src, expr_in.as_mut().map(|x| &mut **x))
+        }
+        ExprKind::Assign(_, val) => visitor.visit_expr(src, val.as_mut().map(|x| &mut **x)),
+        ExprKind::Tuple(exprs) => exprs
+            .iter_mut()
+            .try_for_each(|expr| visitor.visit_expr(src, expr.as_mut())),
+        ExprKind::Case(scrutinee, arms) => {
+            visitor.visit_expr(src, scrutinee.as_mut().map(|x| &mut **x))?;
+            arms.iter_mut().try_for_each(|arm| {
+                let arm = arm.as_mut().value_owned();
+                visitor.visit_pat(src, arm.pat.as_mut())?;
+                visitor.visit_expr(src, arm.body.as_mut())
+            })
+        }
+        ExprKind::Attributed(_, expr) => visitor.visit_expr(src, expr.as_mut().map(|x| &mut **x)),
+        ExprKind::Call(callee, args) => {
+            visitor.visit_expr(src, callee.as_mut().map(|x| &mut **x))?;
+            args.iter_mut()
+                .try_for_each(|arg| visitor.visit_expr(src, arg.as_mut()))
+        }
+        ExprKind::Fn(_, _, params, ret, body) => {
+            for param in params.iter_mut() {
+                if let Some(ty) = &mut param.as_mut().value_owned().ty {
+                    visitor.visit_ty(src, ty.as_mut())?;
+                }
+            }
+            if let Some(ret) = ret {
+                visitor.visit_ty(src, ret.as_mut())?;
+            }
+            visitor.visit_expr(src, body.as_mut().map(|x| &mut **x))
+        }
+        ExprKind::Loop(body) => visitor.visit_expr(src, body.as_mut().map(|x| &mut **x)),
+        ExprKind::Break(value) => value.as_mut().map_or(Ok(()), |value| {
+            visitor.visit_expr(src, value.as_mut().map(|x| &mut **x))
+        }),
+    }
+}
+
+/// Mutably visit all of the children of a primary value. Primary values
+/// currently have no children, so this does nothing.
+///
+/// # Errors
+///
+/// This function does not currently error.
+pub fn walk_primary_mut<'ast, V: VisitorMut<'ast> + ?Sized>(
+    _visitor: &mut V,
+    _src: &str,
+    _primary: Spanned<&mut Primary>,
+) -> CalResult<()> {
+    Ok(())
+}
+
+/// Mutably visit all of the children of a type, in source order.
+///
+/// # Errors
+///
+/// This function errors if visiting any of the children errors.
+pub fn walk_ty_mut<'ast, V: VisitorMut<'ast> + ?Sized>(
+    visitor: &mut V,
+    src: &str,
+    ty: Spanned<&mut Ty<'ast>>,
+) -> CalResult<()> {
+    match &mut ty.value_owned().kind {
+        TyKind::Symbol(_) => Ok(()),
+        TyKind::IndefArray(ty) | TyKind::DefArray(ty, _) => {
+            visitor.visit_ty(src, ty.as_mut().map(|x| &mut **x))
+        }
+        TyKind::Apply(_, tys) | TyKind::Tuple(tys) => tys
+            .iter_mut()
+            .try_for_each(|ty| visitor.visit_ty(src, ty.as_mut())),
+    }
+}
+
+/// Mutably visit all of the children of a pattern, in source order.
+///
+/// # Errors
+///
+/// This function errors if visiting any of the children errors.
+pub fn walk_pat_mut<'ast, V: VisitorMut<'ast> + ?Sized>(
+    visitor: &mut V,
+    src: &str,
+    pat: Spanned<&mut Pat<'ast>>,
+) -> CalResult<()> {
+    match &mut pat.value_owned().kind {
+        PatKind::Wild
+        | PatKind::Binding(..)
+        | PatKind::Bool(_)
+        | PatKind::Number(_)
+        | PatKind::Neg(_) => Ok(()),
+        PatKind::Tuple(pats) | PatKind::Ctor(_, pats) => pats
+            .iter_mut()
+            .try_for_each(|pat| visitor.visit_pat(src, pat.as_mut())),
+    }
+}
+
+pub trait Folder<'ast> {
+    /// Fold an expression, returning the rebuilt expression. By default, this
+    /// calls [`noop_fold_expr`].
+    ///
+    /// New nodes can be allocated in the same [`Arena`](crate::arena::Arena)
+    /// as the tree being folded.
     ///
     /// # Errors
     ///
     /// This function may arbitrarily error depending on its implementation.
-    fn visit_expr<'src>(&mut self, _src: &'src str, _expr: Spanned<&Expr>) -> CalResult<()> {
-        Ok(())
+    fn fold_expr(
+        &mut self,
+        src: &str,
+        expr: Spanned<Expr<'ast>>,
+    ) -> CalResult<Spanned<Expr<'ast>>> {
+        noop_fold_expr(self, src, expr)
     }
 
-    /// Visit a primary value.
+    /// Fold a primary value, returning the rebuilt primary value. By default,
+    /// this calls [`noop_fold_primary`].
     ///
     /// # Errors
     ///
     /// This function may arbitrarily error depending on its implementation.
-    fn visit_primary<'src>(
+    fn fold_primary(
         &mut self,
-        _src: &'src str,
-        _primary: Spanned<&Primary>,
-    ) -> CalResult<()> {
-        Ok(())
+        src: &str,
+        primary: Spanned<Primary>,
+    ) -> CalResult<Spanned<Primary>> {
+        noop_fold_primary(self, src, primary)
+    }
+
+    /// Fold a type, returning the rebuilt type. By default, this calls
+    /// [`noop_fold_ty`].
+    ///
+    /// # Errors
+    ///
+    /// This function may arbitrarily error depending on its implementation.
+    fn fold_ty(&mut self, src: &str, ty: Spanned<Ty<'ast>>) -> CalResult<Spanned<Ty<'ast>>> {
+        noop_fold_ty(self, src, ty)
     }
 
-    /// Visit a type.
+    /// Fold a pattern, returning the rebuilt pattern. By default, this calls
+    /// [`noop_fold_pat`].
     ///
     /// # Errors
     ///
     /// This function may arbitrarily error depending on its implementation.
-    fn visit_ty<'src>(&mut self, _src: &'src str, _ty: Spanned<&Ty>) -> CalResult<()> {
-        Ok(())
+    fn fold_pat(&mut self, src: &str, pat: Spanned<Pat<'ast>>) -> CalResult<Spanned<Pat<'ast>>> {
+        noop_fold_pat(self, src, pat)
+    }
+}
+
+// Folding a child moves it out of its slot in the arena, so that it can be
+// consumed by the folder. These placeholders are left behind in the meantime,
+// and are overwritten once the child has been folded. They don't allocate, as
+// `&mut []` is promoted to `&'static mut []`.
+fn placeholder_expr<'ast>() -> Expr<'ast> {
+    Expr::new(NodeId::DUMMY, ExprKind::Block(&mut []))
+}
+
+fn placeholder_ty<'ast>() -> Ty<'ast> {
+    Ty::new(NodeId::DUMMY, TyKind::Tuple(&mut []))
+}
+
+fn placeholder_pat<'ast>() -> Pat<'ast> {
+    Pat::new(NodeId::DUMMY, PatKind::Wild)
+}
+
+/// Fold an expression in-place, keeping its slot in the arena.
+fn fold_expr_in_place<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    slot: &mut Spanned<Expr<'ast>>,
+) -> CalResult<()> {
+    let old = mem::replace(slot, Spanned::new(Span::new_dummy(), placeholder_expr()));
+    *slot = folder.fold_expr(src, old)?;
+    Ok(())
+}
+
+/// Fold an arena-allocated expression, keeping its slot in the arena.
+fn fold_expr_ref<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    expr: Spanned<&'ast mut Expr<'ast>>,
+) -> CalResult<Spanned<&'ast mut Expr<'ast>>> {
+    let span = expr.span();
+    let slot = expr.value_owned();
+    let old = mem::replace(slot, placeholder_expr());
+    let new = folder.fold_expr(src, Spanned::new(span, old))?;
+    let span = new.span();
+    *slot = new.value_owned();
+    Ok(Spanned::new(span, slot))
+}
+
+/// Fold a type in-place, keeping its slot in the arena.
+fn fold_ty_in_place<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    slot: &mut Spanned<Ty<'ast>>,
+) -> CalResult<()> {
+    let old = mem::replace(slot, Spanned::new(Span::new_dummy(), placeholder_ty()));
+    *slot = folder.fold_ty(src, old)?;
+    Ok(())
+}
+
+/// Fold an arena-allocated type, keeping its slot in the arena.
+fn fold_ty_ref<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    ty: Spanned<&'ast mut Ty<'ast>>,
+) -> CalResult<Spanned<&'ast mut Ty<'ast>>> {
+    let span = ty.span();
+    let slot = ty.value_owned();
+    let old = mem::replace(slot, placeholder_ty());
+    let new = folder.fold_ty(src, Spanned::new(span, old))?;
+    let span = new.span();
+    *slot = new.value_owned();
+    Ok(Spanned::new(span, slot))
+}
+
+/// Fold a pattern in-place, keeping its slot in the arena.
+fn fold_pat_in_place<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    slot: &mut Spanned<Pat<'ast>>,
+) -> CalResult<()> {
+    let old = mem::replace(slot, Spanned::new(Span::new_dummy(), placeholder_pat()));
+    *slot = folder.fold_pat(src, old)?;
+    Ok(())
+}
+
+/// Fold all of the children of an expression, in source order, and rebuild
+/// the expression from the folded children.
+///
+/// # Errors
+///
+/// This function errors if folding any of the children errors.
+pub fn noop_fold_expr<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    expr: Spanned<Expr<'ast>>,
+) -> CalResult<Spanned<Expr<'ast>>> {
+    let span = expr.span();
+    let Expr { id, kind } = expr.value_owned();
+    let kind = match kind {
+        ExprKind::BinOp(left, op, right) => {
+            let left = fold_expr_ref(folder, src, left)?;
+            let right = fold_expr_ref(folder, src, right)?;
+            ExprKind::BinOp(left, op, right)
+        }
+        ExprKind::UnOp(op, expr) => ExprKind::UnOp(op, fold_expr_ref(folder, src, expr)?),
+        ExprKind::Primary(primary) => ExprKind::Primary(folder.fold_primary(src, primary)?),
+        ExprKind::Block(exprs) => {
+            for expr in exprs.iter_mut() {
+                fold_expr_in_place(folder, src, expr)?;
+            }
+            ExprKind::Block(exprs)
+        }
+        ExprKind::Let(is_mut, sym, ty, val, expr_in) => {
+            let ty = ty.map(|ty| folder.fold_ty(src, ty)).transpose()?;
+            let val = fold_expr_ref(folder, src, val)?;
+            let expr_in = fold_expr_ref(folder, src, expr_in)?;
+            ExprKind::Let(is_mut, sym, ty, val, expr_in)
+        }
+        ExprKind::Assign(sym, val) => ExprKind::Assign(sym, fold_expr_ref(folder, src, val)?),
+        ExprKind::Tuple(exprs) => {
+            for expr in exprs.iter_mut() {
+                fold_expr_in_place(folder, src, expr)?;
+            }
+            ExprKind::Tuple(exprs)
+        }
+        ExprKind::Case(scrutinee, arms) => {
+            let scrutinee = fold_expr_ref(folder, src, scrutinee)?;
+            for arm in arms.iter_mut() {
+                let arm = arm.as_mut().value_owned();
+                fold_pat_in_place(folder, src, &mut arm.pat)?;
+                fold_expr_in_place(folder, src, &mut arm.body)?;
+            }
+            ExprKind::Case(scrutinee, arms)
+        }
+        ExprKind::Attributed(attrs, expr) => {
+            ExprKind::Attributed(attrs, fold_expr_ref(folder, src, expr)?)
+        }
+        ExprKind::Call(callee, args) => {
+            let callee = fold_expr_ref(folder, src, callee)?;
+            for arg in args.iter_mut() {
+                fold_expr_in_place(folder, src, arg)?;
+            }
+            ExprKind::Call(callee, args)
+        }
+        ExprKind::Fn(ty_params, name, params, ret, body) => {
+            for param in params.iter_mut() {
+                if let Some(ty) = &mut param.as_mut().value_owned().ty {
+                    fold_ty_in_place(folder, src, ty)?;
+                }
+            }
+            let ret = ret.map(|ret| folder.fold_ty(src, ret)).transpose()?;
+            let body = fold_expr_ref(folder, src, body)?;
+            ExprKind::Fn(ty_params, name, params, ret, body)
+        }
+        ExprKind::Loop(body) => ExprKind::Loop(fold_expr_ref(folder, src, body)?),
+        ExprKind::Break(value) => ExprKind::Break(
+            value
+                .map(|value| fold_expr_ref(folder, src, value))
+                .transpose()?,
+        ),
+    };
+    Ok(Spanned::new(span, Expr::new(id, kind)))
+}
+
+/// Fold all of the children of a primary value. Primary values currently
+/// have no children, so this returns the value unchanged.
+///
+/// # Errors
+///
+/// This function does not currently error.
+pub fn noop_fold_primary<'ast, F: Folder<'ast> + ?Sized>(
+    _folder: &mut F,
+    _src: &str,
+    primary: Spanned<Primary>,
+) -> CalResult<Spanned<Primary>> {
+    Ok(primary)
+}
+
+/// Fold all of the children of a type, in source order, and rebuild the type
+/// from the folded children.
+///
+/// # Errors
+///
+/// This function errors if folding any of the children errors.
+pub fn noop_fold_ty<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    ty: Spanned<Ty<'ast>>,
+) -> CalResult<Spanned<Ty<'ast>>> {
+    let span = ty.span();
+    let Ty { id, kind } = ty.value_owned();
+    let kind = match kind {
+        TyKind::Symbol(sym) => TyKind::Symbol(sym),
+        TyKind::IndefArray(ty) => TyKind::IndefArray(fold_ty_ref(folder, src, ty)?),
+        TyKind::DefArray(ty, num) => TyKind::DefArray(fold_ty_ref(folder, src, ty)?, num),
+        TyKind::Apply(sym, tys) => {
+            for ty in tys.iter_mut() {
+                fold_ty_in_place(folder, src, ty)?;
+            }
+            TyKind::Apply(sym, tys)
+        }
+        TyKind::Tuple(tys) => {
+            for ty in tys.iter_mut() {
+                fold_ty_in_place(folder, src, ty)?;
+            }
+            TyKind::Tuple(tys)
+        }
+    };
+    Ok(Spanned::new(span, Ty::new(id, kind)))
+}
+
+/// Fold all of the children of a pattern, in source order, and rebuild the
+/// pattern from the folded children.
+///
+/// # Errors
+///
+/// This function errors if folding any of the children errors.
+pub fn noop_fold_pat<'ast, F: Folder<'ast> + ?Sized>(
+    folder: &mut F,
+    src: &str,
+    pat: Spanned<Pat<'ast>>,
+) -> CalResult<Spanned<Pat<'ast>>> {
+    let span = pat.span();
+    let Pat { id, kind } = pat.value_owned();
+    let kind = match kind {
+        PatKind::Tuple(pats) => {
+            for pat in pats.iter_mut() {
+                fold_pat_in_place(folder, src, pat)?;
+            }
+            PatKind::Tuple(pats)
+        }
+        PatKind::Ctor(name, pats) => {
+            for pat in pats.iter_mut() {
+                fold_pat_in_place(folder, src, pat)?;
+            }
+            PatKind::Ctor(name, pats)
+        }
+        kind @ (PatKind::Wild
+        | PatKind::Binding(..)
+        | PatKind::Bool(_)
+        | PatKind::Number(_)
+        | PatKind::Neg(_)) => kind,
+    };
+    Ok(Spanned::new(span, Pat::new(id, kind)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        arena::Arena,
+        expr::{BinOpKind, UnOpKind},
+    };
+
+    use calypso_base::symbol::Symbol;
+
+    fn sp<T: std::fmt::Debug>(lo: u32, hi: u32, value: T) -> Spanned<T> {
+        Spanned::new(Span::new(lo, hi), value)
+    }
+
+    fn symbol<'ast>(
+        arena: &'ast Arena<'ast>,
+        lo: u32,
+        hi: u32,
+        sym: &str,
+    ) -> Spanned<&'ast mut Expr<'ast>> {
+        sp(
+            lo,
+            hi,
+            arena.alloc_expr(Expr::new(
+                NodeId::DUMMY,
+                ExprKind::Primary(sp(lo, hi, Primary::Symbol(Symbol::intern(sym)))),
+            )),
+        )
+    }
+
+    /// `-a + b`
+    fn tree<'ast>(arena: &'ast Arena<'ast>) -> Spanned<Expr<'ast>> {
+        let neg = arena.alloc_expr(Expr::new(
+            NodeId::DUMMY,
+            ExprKind::UnOp(sp(0, 1, UnOpKind::Negative), symbol(arena, 1, 2, "a")),
+        ));
+        sp(
+            0,
+            6,
+            Expr::new(
+                NodeId::DUMMY,
+                ExprKind::BinOp(
+                    sp(0, 2, neg),
+                    sp(3, 4, BinOpKind::Add),
+                    symbol(arena, 5, 6, "b"),
+                ),
+            ),
+        )
+    }
+
+    #[derive(Default)]
+    struct Symbols(Vec<Symbol>);
+
+    impl Visitor for Symbols {
+        fn visit_primary(&mut self, _src: &str, primary: Spanned<&Primary>) -> CalResult<()> {
+            if let Primary::Symbol(sym) = primary.value() {
+                self.0.push(*sym);
+            }
+            Ok(())
+        }
+    }
+
+    struct Rename;
+
+    impl VisitorMut<'_> for Rename {
+        fn visit_primary(&mut self, _src: &str, primary: Spanned<&mut Primary>) -> CalResult<()> {
+            let primary = primary.value_owned();
+            if let Primary::Symbol(sym) = primary {
+                *sym = Symbol::intern(&format!("{sym}_renamed"));
+            }
+            Ok(())
+        }
+    }
+
+    struct StripNegation;
+
+    impl<'ast> Folder<'ast> for StripNegation {
+        fn fold_expr(
+            &mut self,
+            src: &str,
+            expr: Spanned<Expr<'ast>>,
+        ) -> CalResult<Spanned<Expr<'ast>>> {
+            let span = expr.span();
+            let Expr { id, kind } = expr.value_owned();
+            match kind {
+                ExprKind::UnOp(_, expr) => {
+                    let span = expr.span();
+                    let expr = mem::replace(expr.value_owned(), placeholder_expr());
+                    self.fold_expr(src, Spanned::new(span, expr))
+                }
+                kind => noop_fold_expr(self, src, Spanned::new(span, Expr::new(id, kind))),
+            }
+        }
+    }
+
+    #[test]
+    fn visitor_walks_children() {
+        let arena = Arena::new();
+        let mut symbols = Symbols::default();
+        symbols.visit_expr("-a + b", tree(&arena).as_ref()).unwrap();
+        assert_eq!(symbols.0, vec![Symbol::intern("a"), Symbol::intern("b")]);
+    }
+
+    #[test]
+    fn visitor_mut_rewrites_in_place() {
+        let arena = Arena::new();
+        let mut tree = tree(&arena);
+        Rename.visit_expr("-a + b", tree.as_mut()).unwrap();
+        let mut symbols = Symbols::default();
+        symbols.visit_expr("-a + b", tree.as_ref()).unwrap();
+        assert_eq!(
+            symbols.0,
+            vec![Symbol::intern("a_renamed"), Symbol::intern("b_renamed")]
+        );
+    }
+
+    #[test]
+    fn folder_rebuilds() {
+        let arena = Arena::new();
+        let folded = StripNegation.fold_expr("-a + b", tree(&arena)).unwrap();
+        match &folded.value().kind {
+            ExprKind::BinOp(left, _, _) => {
+                assert_eq!(left.span(), Span::new(1, 2));
+                assert_eq!(
+                    left.value().kind,
+                    ExprKind::Primary(sp(1, 2, Primary::Symbol(Symbol::intern("a"))))
+                );
+            }
+            _ => panic!("expected a binary operation"),
+        }
     }
 }
@@ -1,11 +1,33 @@
 use calypso_base::{span::Spanned, symbol::Symbol};
 
-use crate::expr::Numeral;
+use crate::{expr::Numeral, node::NodeId};
 
-#[derive(Clone, Debug, PartialEq, Eq)]
-pub enum Ty {
+/// A type.
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub struct Ty<'ast> {
+    /// The unique ID of this type
+    pub id: NodeId,
+    /// The kind of type
+    pub kind: TyKind<'ast>,
+}
+
+impl<'ast> Ty<'ast> {
+    /// Create a new type with the given ID.
+    #[must_use]
+    pub fn new(id: NodeId, kind: TyKind<'ast>) -> Self {
+        Self { id, kind }
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
+pub enum TyKind<'ast> {
     Symbol(Spanned<Symbol>),
-    IndefArray(Spanned<Box<Ty>>),
-    DefArray(Spanned<Box<Ty>>, Spanned<Numeral>),
-    Tuple(Vec<Spanned<Ty>>),
+    /// A generic type applied to type arguments, e.g. `Option[uint]`. There
+    /// is always at least one argument.
+    Apply(Spanned<Symbol>, &'ast mut [Spanned<Ty<'ast>>]),
+    IndefArray(Spanned<&'ast mut Ty<'ast>>),
+    DefArray(Spanned<&'ast mut Ty<'ast>>, Spanned<Numeral>),
+    Tuple(&'ast mut [Spanned<Ty<'ast>>]),
 }
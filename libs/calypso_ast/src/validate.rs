@@ -0,0 +1,397 @@
+//! Validation of structural invariants of the AST that are not enforced by
+//! its types.
+//!
+//! The parser upholds most of these by construction, but ASTs may also be
+//! built by hand (or read with [`sexpr`](crate::sexpr)), so this is useful
+//! for both. The invariants checked are:
+//!
+//! - Lengths of array types are unsigned integer literals.
+//! - The empty tuple type is not used as the element type of an array.
+//! - `case` expressions have at least one arm.
+//! - Attributed expressions have at least one attribute.
+//! - Function items appear directly in a sequence of expressions, i.e. at
+//!   the top level or in a block.
+//! - Constructor patterns have at least one field.
+//! - Names of variables, bindings, functions, parameters and types are valid identifiers, and the
+//!   span of a name covers exactly that name.
+//! - The span of every node is within the span of its parent. Dummy spans
+//!   (see [`Span::new_dummy`]) are not checked, so that hand-built ASTs do
+//!   not need to provide spans.
+
+use calypso_base::{
+    span::{Span, Spanned},
+    symbol::Symbol,
+};
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+
+use crate::{
+    expr::{Expr, ExprKind, Numeral, Primary, Suffix},
+    pat::{Pat, PatKind},
+    sexpr::is_ident,
+    traverse::{walk_expr, walk_pat, walk_ty, Visitor},
+    ty::{Ty, TyKind},
+};
+
+/// Validate a sequence of expressions, reporting any violations to `grcx`
+/// as synchronizable errors. Returns `true` if the expressions are valid.
+pub fn validate(grcx: &mut GlobalReportingCtxt, file_id: usize, exprs: &[Spanned<Expr>]) -> bool {
+    let mut validator = Validator {
+        grcx,
+        file_id,
+        parents: Vec::new(),
+        in_sequence: true,
+        valid: true,
+    };
+    // The validator never errors, and it does not need the source.
+    let _ = exprs
+        .iter()
+        .try_for_each(|expr| validator.visit_expr("", expr.as_ref()));
+    validator.valid
+}
+
+struct Validator<'grcx> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    /// The spans of the nodes enclosing the current node
+    parents: Vec<Span>,
+    /// Whether the current expression is directly in a sequence of
+    /// expressions
+    in_sequence: bool,
+    valid: bool,
+}
+
+impl Validator<'_> {
+    fn report(&mut self, code: &'static str, short: &str, span: Span, message: &str) {
+        self.valid = false;
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code(code).short(short).label(
+                        LabelStyle::Primary,
+                        Some(message),
+                        self.file_id,
+                        span,
+                    )
+                })
+                .build(),
+        );
+    }
+
+    /// Check that `span` is well-formed and within the span of the parent
+    /// node.
+    fn check_span(&mut self, span: Span, what: &str) {
+        if span.lo() > span.hi() {
+            let message = format!("the span of this {what} ends before it starts");
+            self.report("E0004", err!(E0004), Span::new_shrunk(span.lo()), &message);
+            return;
+        }
+        if span == Span::new_dummy() {
+            return;
+        }
+
+        let parent = self
+            .parents
+            .iter()
+            .rev()
+            .find(|&&parent| parent != Span::new_dummy())
+            .copied();
+        if let Some(parent) = parent {
+            if parent.lo() <= parent.hi() && (span.lo() < parent.lo() || span.hi() > parent.hi()) {
+                let message = format!("this {what} is not within its parent node");
+                self.report("E0004", err!(E0004), span, &message);
+            }
+        }
+    }
+
+    /// Check that a name is a valid identifier, and that its span covers
+    /// exactly the name.
+    fn check_ident(&mut self, name: Spanned<Symbol>, what: &str) {
+        let span = name.span();
+        self.check_span(span, what);
+
+        let name = name.value_owned();
+        if !is_ident(name.as_str()) {
+            let message = format!("`{name}` is not a valid identifier");
+            self.report("E0004", err!(E0004), span, &message);
+        } else if span != Span::new_dummy()
+            && (span.hi() - span.lo()) as usize != name.as_str().len()
+        {
+            let message = format!("the span of this {what} does not cover exactly `{name}`");
+            self.report("E0004", err!(E0004), span, &message);
+        }
+    }
+
+    /// Check the element type of an array type.
+    fn check_array_elem(&mut self, elem: Spanned<&Ty>) {
+        if matches!(&elem.value().kind, TyKind::Tuple(elems) if elems.is_empty()) {
+            self.report(
+                "E0003",
+                err!(E0003),
+                elem.span(),
+                "the empty tuple type cannot be an array element type",
+            );
+        }
+    }
+}
+
+impl Visitor for Validator<'_> {
+    fn visit_expr(&mut self, src: &str, expr: Spanned<&Expr>) -> CalResult<()> {
+        self.check_span(expr.span(), "expression");
+        self.parents.push(expr.span());
+
+        match &expr.value().kind {
+            ExprKind::BinOp(_, op, _) => self.check_span(op.span(), "operator"),
+            ExprKind::UnOp(op, _) => self.check_span(op.span(), "operator"),
+            ExprKind::Let(_, name, ..) => self.check_ident(*name, "binding"),
+            ExprKind::Assign(name, _) => self.check_ident(*name, "variable"),
+            ExprKind::Case(_, arms) => {
+                if arms.is_empty() {
+                    self.report(
+                        "E0004",
+                        err!(E0004),
+                        expr.span(),
+                        "this `case` expression has no arms",
+                    );
+                }
+                for arm in arms.iter() {
+                    self.check_span(arm.span(), "arm");
+                }
+            }
+            ExprKind::Attributed(attrs, _) => {
+                if attrs.is_empty() {
+                    self.report(
+                        "E0004",
+                        err!(E0004),
+                        expr.span(),
+                        "this attributed expression has no attributes",
+                    );
+                }
+                for attr in attrs.iter() {
+                    self.check_span(attr.span(), "attribute");
+                    self.parents.push(attr.span());
+                    self.check_ident(attr.value().name, "attribute name");
+                    for arg in attr.value().args.iter() {
+                        self.check_ident(*arg, "attribute argument");
+                    }
+                    self.parents.pop();
+                }
+            }
+            ExprKind::Fn(ty_params, name, params, ..) => {
+                if !self.in_sequence {
+                    self.report(
+                        "E0004",
+                        err!(E0004),
+                        expr.span(),
+                        "this function item is not directly in a block or at the top level",
+                    );
+                }
+                for ty_param in ty_params.iter() {
+                    self.check_ident(*ty_param, "type parameter");
+                }
+                self.check_ident(*name, "function name");
+                for param in params.iter() {
+                    self.check_span(param.span(), "parameter");
+                    self.parents.push(param.span());
+                    self.check_ident(param.value().name, "parameter");
+                    self.parents.pop();
+                }
+            }
+            ExprKind::Loop(body) => {
+                if !matches!(body.value().kind, ExprKind::Block(_)) {
+                    self.report(
+                        "E0004",
+                        err!(E0004),
+                        body.span(),
+                        "the body of this `loop` is not a block",
+                    );
+                }
+            }
+            ExprKind::Primary(_)
+            | ExprKind::Block(_)
+            | ExprKind::Tuple(_)
+            | ExprKind::Call(..)
+            | ExprKind::Break(_) => {}
+        }
+        let in_sequence = std::mem::replace(
+            &mut self.in_sequence,
+            matches!(expr.value().kind, ExprKind::Block(_)),
+        );
+        walk_expr(self, src, expr)?;
+        self.in_sequence = in_sequence;
+
+        self.parents.pop();
+        Ok(())
+    }
+
+    fn visit_primary(&mut self, _src: &str, primary: Spanned<&Primary>) -> CalResult<()> {
+        match primary.value_owned() {
+            Primary::Symbol(sym) => {
+                self.check_ident(Spanned::new(primary.span(), *sym), "variable");
+            }
+            Primary::Number(_) | Primary::Bool(_) => self.check_span(primary.span(), "literal"),
+        }
+        Ok(())
+    }
+
+    fn visit_ty(&mut self, src: &str, ty: Spanned<&Ty>) -> CalResult<()> {
+        self.check_span(ty.span(), "type");
+        self.parents.push(ty.span());
+
+        match &ty.value().kind {
+            TyKind::Symbol(sym) | TyKind::Apply(sym, _) => self.check_ident(*sym, "type name"),
+            TyKind::IndefArray(elem) => self.check_array_elem(elem.as_ref().map(|x| &**x)),
+            TyKind::DefArray(elem, len) => {
+                self.check_array_elem(elem.as_ref().map(|x| &**x));
+                self.check_span(len.span(), "array length");
+                match len.value_owned() {
+                    Numeral::Integer {
+                        suffix: None | Some(Suffix::Uint),
+                        ..
+                    } => {}
+                    Numeral::Integer {
+                        suffix: Some(Suffix::Sint),
+                        ..
+                    } => self.report(
+                        "E0002",
+                        err!(E0002),
+                        len.span(),
+                        "array lengths cannot be signed integers",
+                    ),
+                    Numeral::Float { .. } => self.report(
+                        "E0002",
+                        err!(E0002),
+                        len.span(),
+                        "array lengths cannot be floats",
+                    ),
+                }
+            }
+            TyKind::Tuple(_) => {}
+        }
+        walk_ty(self, src, ty)?;
+
+        self.parents.pop();
+        Ok(())
+    }
+
+    fn visit_pat(&mut self, src: &str, pat: Spanned<&Pat>) -> CalResult<()> {
+        self.check_span(pat.span(), "pattern");
+        self.parents.push(pat.span());
+
+        match &pat.value().kind {
+            PatKind::Binding(_, name) => self.check_ident(*name, "binding"),
+            PatKind::Number(num) | PatKind::Neg(num) => self.check_span(num.span(), "literal"),
+            PatKind::Ctor(name, pats) => {
+                self.check_ident(*name, "constructor name");
+                if pats.is_empty() {
+                    self.report(
+                        "E0004",
+                        err!(E0004),
+                        pat.span(),
+                        "this constructor pattern has no fields",
+                    );
+                }
+            }
+            PatKind::Wild | PatKind::Bool(_) | PatKind::Tuple(_) => {}
+        }
+        walk_pat(self, src, pat)?;
+
+        self.parents.pop();
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use calypso_base::span::Span;
+
+    use super::*;
+    use crate::{arena::Arena, expr::Mutability, node::NodeIdGen, sexpr};
+
+    /// Validate the S-expression `src`, returning the primary labels of the
+    /// errors.
+    fn errors(src: &str) -> Vec<String> {
+        let arena = Arena::new();
+        let exprs = sexpr::read_exprs(src, &NodeIdGen::new(), &arena).unwrap();
+        let mut grcx = GlobalReportingCtxt::new();
+        let valid = validate(&mut grcx, 0, &exprs);
+        assert_eq!(valid, grcx.errors().is_empty());
+        grcx.errors().iter().map(|e| format!("{e:?}")).collect()
+    }
+
+    #[test]
+    fn valid_trees() {
+        assert!(errors("(let x (ty (def-arr int 4u)) (+ 1 2) in (block x))").is_empty());
+        assert!(errors("(let x (ty (tuple)) y in x)").is_empty());
+        assert!(errors("(block (fn f (with T) (params (x (ty T))) x) (call f 1))").is_empty());
+    }
+
+    #[test]
+    fn nested_fn_items() {
+        let errs = errors("(let f (fn f (params) 1) in f)");
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].contains("this function item is not directly in a block"));
+    }
+
+    #[test]
+    fn array_lengths() {
+        let errs = errors("(let x (ty (def-arr int 1.5)) y in x)");
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].contains("array lengths cannot be floats"));
+
+        let errs = errors("(let x (ty (def-arr int 4s)) y in x)");
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].contains("array lengths cannot be signed integers"));
+    }
+
+    #[test]
+    fn empty_tuple_elements() {
+        let errs = errors("(let x (ty (indef-arr (tuple))) y in x)");
+        assert_eq!(errs.len(), 1);
+        assert!(errs[0].contains("the empty tuple type cannot be an array element type"));
+    }
+
+    #[test]
+    fn hand_built_trees() {
+        let arena = Arena::new();
+        let ids = NodeIdGen::new();
+        let primary = |lo, hi, sym| {
+            Spanned::new(
+                Span::new(lo, hi),
+                Expr::new(
+                    ids.next_id(),
+                    ExprKind::Primary(Spanned::new(Span::new(lo, hi), Primary::Symbol(sym))),
+                ),
+            )
+        };
+
+        // `let x = y in x`, but with the name spanning `x = y` and the body
+        // outside of the `let`.
+        let value = primary(8, 9, Symbol::intern("y"));
+        let body = primary(20, 21, Symbol::intern("x"));
+        let expr = Spanned::new(
+            Span::new(0, 14),
+            Expr::new(
+                ids.next_id(),
+                ExprKind::Let(
+                    Mutability::Immut,
+                    Spanned::new(Span::new(4, 9), Symbol::intern("x")),
+                    None,
+                    value.map(|x| arena.alloc_expr(x)),
+                    body.map(|x| arena.alloc_expr(x)),
+                ),
+            ),
+        );
+
+        let mut grcx = GlobalReportingCtxt::new();
+        assert!(!validate(&mut grcx, 0, &[expr]));
+        assert_eq!(grcx.errors().len(), 2);
+
+        // Dummy spans are not checked.
+        let expr = Spanned::new(
+            Span::new_dummy(),
+            primary(0, 0, Symbol::intern("x")).value_owned(),
+        );
+        let mut grcx = GlobalReportingCtxt::new();
+        assert!(validate(&mut grcx, 0, &[expr]));
+    }
+}
@@ -3,6 +3,7 @@ use std::ops::Range;
 
 /// The location in a slice in which some object spans.
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq, Ord, PartialOrd)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Span {
     lo: u32,
     hi: u32,
@@ -237,6 +238,7 @@ impl From<Range<u32>> for Span {
 /// assert_eq!(spanned.span(), Span::new(0, 1));
 /// ```
 #[derive(Debug, Copy, Clone, Hash, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Spanned<T>
 where
     T: Debug,
@@ -278,6 +280,15 @@ where
         }
     }
 
+    /// Convert a `&'a mut Spanned<T>` into a `Spanned<&'a mut T>`.
+    #[must_use]
+    pub fn as_mut(&'_ mut self) -> Spanned<&'_ mut T> {
+        Spanned {
+            span: self.span,
+            value: &mut self.value,
+        }
+    }
+
     /// Map the value of a `Spanned<T>`.
     #[must_use]
     pub fn map<U: Debug>(self, f: impl FnOnce(T) -> U) -> Spanned<U> {
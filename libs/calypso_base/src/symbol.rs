@@ -75,6 +75,24 @@ impl Display for Symbol {
     }
 }
 
+/// Symbols are serialized as the string they resolve to, and interned when
+/// deserialized, as their raw indices are only meaningful within a single
+/// process.
+#[cfg(feature = "serde")]
+impl serde::Serialize for Symbol {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(self.as_str())
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for Symbol {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let string = String::deserialize(deserializer)?;
+        Ok(Self::intern(&string))
+    }
+}
+
 /// An identifier, i.e. a combination of a symbol and a span.
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
 pub struct Ident {
@@ -190,6 +208,27 @@ macro_rules! intern_static {
                     return Err(sym);
                 }
             }
+            #[cfg(feature = "serde")]
+            impl ::serde::Serialize for $name {
+                fn serialize<S: ::serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                    ::serde::Serialize::serialize(&$crate::symbol::Symbol::from(*self), serializer)
+                }
+            }
+
+            #[cfg(feature = "serde")]
+            impl<'de> ::serde::Deserialize<'de> for $name {
+                fn deserialize<D: ::serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                    let sym = <$crate::symbol::Symbol as ::serde::Deserialize>::deserialize(deserializer)?;
+                    Self::try_from(sym).map_err(|sym| {
+                        <D::Error as ::serde::de::Error>::custom(format_args!(
+                            "`{}` is not a member of `{}`",
+                            sym,
+                            stringify!($name)
+                        ))
+                    })
+                }
+            }
+
             impl From<$name> for $crate::symbol::Symbol {
                 fn from(elem: $name) -> Self {
                     $(
@@ -239,6 +278,11 @@ intern_static! {kw, "Keywords", Keyword => {
     Do; DO: "do"; "Do (`do`)",
     End; END: "end"; "End (`end`)",
     In; IN: "in"; "In (`in`)",
+    Case; CASE: "case"; "Case (`case`)",
+    Fn; FN: "fn"; "Fn (`fn`)",
+    With; WITH: "with"; "With (`with`)",
+    Loop; LOOP: "loop"; "Loop (`loop`)",
+    Break; BREAK: "break"; "Break (`break`)",
 }}
 
 intern_static! {special, "Special strings", Special => {
@@ -8,14 +8,16 @@ use calypso_error::CalResult;
 use codespan_reporting::files::SimpleFiles;
 use codespan_reporting::{
     diagnostic::Diagnostic as CodespanDiag,
-    term::{self, Config},
+    term::{self, Config, DisplayStyle},
 };
 
-use super::error::DiagnosticError;
+use super::{error::DiagnosticError, lint::Lint};
 
 pub mod builder;
+pub mod suggestion;
 
 pub use builder::{Builder, EnsembleBuilder};
+pub use suggestion::{Applicability, Edit, Suggestion};
 
 pub use codespan_reporting::diagnostic::{LabelStyle, Severity};
 
@@ -23,12 +25,32 @@ pub use codespan_reporting::diagnostic::{LabelStyle, Severity};
 pub type SourceMgr = SimpleFiles<String, String>;
 
 /// A diagnostic.
-#[derive(Debug)]
-pub struct Diagnostic(CodespanDiag<usize>);
+#[derive(Clone, Debug)]
+pub struct Diagnostic(CodespanDiag<usize>, Vec<Suggestion>, Option<&'static Lint>);
 
 impl Diagnostic {
-    /// Render the diagnostic to the provided buffer. This will add a newline
-    /// at the end.
+    /// Get the underlying `codespan-reporting` diagnostic, which contains the
+    /// severity, code, message, labels and notes.
+    #[must_use]
+    pub fn codespan(&self) -> &CodespanDiag<usize> {
+        &self.0
+    }
+
+    /// Get the lint that reported the diagnostic, if any.
+    #[must_use]
+    pub fn lint(&self) -> Option<&'static Lint> {
+        self.2
+    }
+
+    /// Get the edits suggested by the diagnostic.
+    #[must_use]
+    pub fn suggestions(&self) -> &[Suggestion] {
+        &self.1
+    }
+
+    /// Render the diagnostic to the provided buffer. Unless the short display
+    /// style is used, its suggestions are rendered as diffs after it, and
+    /// this will add a newline at the end.
     ///
     /// # Errors
     ///
@@ -44,7 +66,12 @@ impl Diagnostic {
         let config = config.unwrap_or(&def_config);
 
         term::emit(buf, config, sourcemgr, &self.0).map_err(DiagnosticError::from)?;
-        writeln!(buf)?;
+        if !matches!(config.display_style, DisplayStyle::Short) {
+            for suggestion in &self.1 {
+                suggestion.render(buf, sourcemgr, config)?;
+            }
+            writeln!(buf)?;
+        }
 
         Ok(())
     }
@@ -53,7 +80,7 @@ impl Diagnostic {
 /// One or more diagnostics in a specific order, in order to form an "ensemble
 /// diagnostic" which is emitted all at once.
 #[allow(clippy::module_name_repetitions)]
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub enum EnsembleDiagnostic {
     /// One diagnostic
     One(Diagnostic),
@@ -62,15 +89,13 @@ pub enum EnsembleDiagnostic {
 }
 
 impl EnsembleDiagnostic {
-    /// Render the ensemble diagnostic to the provided buffer. This will add a
-    /// newline at the end.
+    /// Render the ensemble diagnostic to the provided buffer. Unless the
+    /// short display style is used, this will add a newline at the end.
     ///
     /// # Errors
     ///
     /// This function will error if rendering the diagnostic or writing to the
     /// buffer failed.
-    // TODO(@ThePuzzlemaker: diag|cli): use compact displaystyle for syntax
-    //   errors, add "force rich error" CLI arg for syntax errors
     pub fn render<'gcx>(
         &self,
         buf: &mut Buffer,
@@ -86,6 +111,64 @@ impl EnsembleDiagnostic {
     }
 }
 
+impl EnsembleDiagnostic {
+    /// Render the ensemble diagnostic with one line per diagnostic, in the
+    /// short display style. Unlike that style, the messages of the primary
+    /// labels and the notes are shown in place of the short message, e.g.
+    /// `file:1:9: error[E0001]: unexpected token; expected one of: ...`, as
+    /// they say what went wrong in more detail.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if rendering the diagnostic or writing to the
+    /// buffer failed.
+    pub fn render_compact(
+        &self,
+        buf: &mut Buffer,
+        sourcemgr: &SourceMgr,
+        config: &Config,
+    ) -> CalResult<()> {
+        let config = Config {
+            display_style: DisplayStyle::Short,
+            ..config.clone()
+        };
+        for diag in self.diagnostics() {
+            let mut diag = diag.0.clone();
+            let details = diag
+                .labels
+                .iter()
+                .filter(|label| label.style == LabelStyle::Primary && !label.message.is_empty())
+                .map(|label| label.message.clone())
+                .chain(diag.notes.iter().cloned())
+                .collect::<Vec<_>>();
+            if !details.is_empty() {
+                diag.message = details.join("; ");
+            }
+            term::emit(buf, &config, sourcemgr, &diag).map_err(DiagnosticError::from)?;
+        }
+        Ok(())
+    }
+
+    /// Returns `true` if the ensemble is a syntax error, i.e. its first
+    /// diagnostic has the code `E0001`.
+    #[must_use]
+    pub fn is_syntax_error(&self) -> bool {
+        self.diagnostics()
+            .first()
+            .and_then(|diag| diag.0.code.as_deref())
+            == Some("E0001")
+    }
+
+    /// Get the diagnostics in the ensemble, in order.
+    #[must_use]
+    pub fn diagnostics(&self) -> &[Diagnostic] {
+        match self {
+            Self::One(diag) => std::slice::from_ref(diag),
+            Self::Many(diags) => diags,
+        }
+    }
+}
+
 impl From<Diagnostic> for EnsembleDiagnostic {
     fn from(diag: Diagnostic) -> Self {
         Self::One(diag)
@@ -4,7 +4,8 @@ use codespan_reporting::diagnostic::{Diagnostic as CodespanDiag, Label, LabelSty
 
 use calypso_base::span::Span;
 
-use super::{Diagnostic, EnsembleDiagnostic};
+use super::{Applicability, Diagnostic, Edit, EnsembleDiagnostic, Suggestion};
+use crate::lint::Lint;
 
 /// A builder for an ensemble diagnostic.
 #[derive(Debug, Default)]
@@ -81,6 +82,8 @@ pub struct Builder {
     diag: CodespanDiag<usize>,
     labels: Vec<Label<usize>>,
     notes: Vec<String>,
+    suggestions: Vec<Suggestion>,
+    lint: Option<&'static Lint>,
 }
 
 impl Builder {
@@ -91,6 +94,8 @@ impl Builder {
             diag: CodespanDiag::new(severity),
             labels: Vec::new(),
             notes: Vec::new(),
+            suggestions: Vec::new(),
+            lint: None,
         }
     }
 
@@ -102,6 +107,13 @@ impl Builder {
         self
     }
 
+    /// Set the lint that the diagnostic was reported by.
+    #[must_use]
+    pub fn lint(mut self, lint: &'static Lint) -> Self {
+        self.lint = Some(lint);
+        self
+    }
+
     /// Set the short message of the diagnostic. This will overwrite whatever
     /// short message was already present, if any.
     #[must_use]
@@ -134,9 +146,47 @@ impl Builder {
         self
     }
 
+    /// Add a suggestion to replace the source at `span` with `replacement`.
+    #[must_use]
+    pub fn suggestion(
+        self,
+        file_id: usize,
+        span: Span,
+        replacement: impl Into<String>,
+        applicability: Applicability,
+        message: impl Into<String>,
+    ) -> Self {
+        let edit = Edit {
+            file_id,
+            span,
+            replacement: replacement.into(),
+        };
+        self.multipart_suggestion(vec![edit], applicability, message)
+    }
+
+    /// Add a suggestion that makes several edits at once.
+    #[must_use]
+    pub fn multipart_suggestion(
+        mut self,
+        edits: Vec<Edit>,
+        applicability: Applicability,
+        message: impl Into<String>,
+    ) -> Self {
+        self.suggestions.push(Suggestion {
+            message: message.into(),
+            edits,
+            applicability,
+        });
+        self
+    }
+
     /// Build the diagnostic.
     #[must_use]
     pub fn build(self) -> Diagnostic {
-        Diagnostic(self.diag.with_labels(self.labels).with_notes(self.notes))
+        Diagnostic(
+            self.diag.with_labels(self.labels).with_notes(self.notes),
+            self.suggestions,
+            self.lint,
+        )
     }
 }
@@ -0,0 +1,169 @@
+//! Suggested edits to the source, attached to diagnostics.
+
+use std::io::prelude::*;
+
+use calypso_base::{
+    span::Span,
+    ui::termcolor::{Buffer, Color, ColorSpec, WriteColor},
+};
+use calypso_error::CalResult;
+use codespan_reporting::{diagnostic::Severity, files::Files, term::Config};
+
+use super::SourceMgr;
+use crate::error::DiagnosticError;
+
+/// How likely a suggestion is to be what the user wants.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Applicability {
+    /// The suggestion is definitely what the user wants, and can be applied
+    /// automatically.
+    MachineApplicable,
+    /// The suggestion may be what the user wants, but it should be checked
+    /// before it is applied.
+    MaybeIncorrect,
+}
+
+/// A replacement of a span of a file.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Edit {
+    pub file_id: usize,
+    pub span: Span,
+    pub replacement: String,
+}
+
+/// A suggested change to the source, made of one or more edits that must be
+/// applied together.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Suggestion {
+    /// A short description of the change, e.g. "remove these parentheses"
+    pub message: String,
+    /// The edits to make, which do not overlap
+    pub edits: Vec<Edit>,
+    pub applicability: Applicability,
+}
+
+impl Suggestion {
+    /// Describe the suggestion as a note. If the suggestion replaces a
+    /// single span with some text, the text is included.
+    #[must_use]
+    pub fn to_note(&self) -> String {
+        match &self.edits[..] {
+            [edit] if !edit.replacement.is_empty() => {
+                format!("help: {}: `{}`", self.message, edit.replacement)
+            }
+            _ => format!("help: {}", self.message),
+        }
+    }
+
+    /// Render the suggestion as a diff of the lines that it changes, with
+    /// the lines before the change marked `-` and those after marked `+`.
+    ///
+    /// # Errors
+    ///
+    /// This function will error if the edits are not within their files, or
+    /// if writing to the buffer failed.
+    pub fn render(
+        &self,
+        buf: &mut Buffer,
+        sourcemgr: &SourceMgr,
+        config: &Config,
+    ) -> CalResult<()> {
+        buf.set_color(config.styles.header(Severity::Help))?;
+        write!(buf, "help")?;
+        buf.set_color(&config.styles.header_message)?;
+        writeln!(buf, ": {}", self.message)?;
+        buf.reset()?;
+
+        let mut file_ids = self
+            .edits
+            .iter()
+            .map(|edit| edit.file_id)
+            .collect::<Vec<_>>();
+        file_ids.sort_unstable();
+        file_ids.dedup();
+        for file_id in file_ids {
+            let mut edits = self
+                .edits
+                .iter()
+                .filter(|edit| edit.file_id == file_id)
+                .collect::<Vec<_>>();
+            edits.sort_by_key(|edit| edit.span);
+            let (Some(first), Some(last)) = (edits.first(), edits.last()) else {
+                continue;
+            };
+            render_diff(
+                buf, sourcemgr, config, file_id, first.span, last.span, &edits,
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Render the diff for the edits to one file, which are sorted and span
+/// from `first` to `last`.
+fn render_diff(
+    buf: &mut Buffer,
+    sourcemgr: &SourceMgr,
+    config: &Config,
+    file_id: usize,
+    first: Span,
+    last: Span,
+    edits: &[&Edit],
+) -> CalResult<()> {
+    let src = sourcemgr.source(file_id).map_err(DiagnosticError::from)?;
+    let line_index = |byte_index| {
+        sourcemgr
+            .line_index(file_id, byte_index)
+            .and_then(|line| {
+                sourcemgr
+                    .line_range(file_id, line)
+                    .map(|range| (line, range))
+            })
+            .map_err(DiagnosticError::from)
+    };
+    let (first_line, first_range) = line_index(first.into_range().start)?;
+    let (_, last_range) = line_index(last.into_range().end)?;
+    let lines = first_range.start..last_range.end;
+
+    let mut changed = String::new();
+    let mut pos = lines.start;
+    for edit in edits {
+        changed.push_str(&src[pos..edit.span.into_range().start]);
+        changed.push_str(&edit.replacement);
+        pos = edit.span.into_range().end;
+    }
+    changed.push_str(&src[pos..lines.end]);
+    let original = &src[lines];
+
+    let location = sourcemgr
+        .location(file_id, first.into_range().start)
+        .map_err(DiagnosticError::from)?;
+    let name = sourcemgr.name(file_id).map_err(DiagnosticError::from)?;
+    let first_number = first_line + 1;
+    let last_number = first_number + original.lines().count().max(changed.lines().count());
+    let width = last_number.saturating_sub(1).to_string().len();
+    let border = config.chars.source_border_left;
+
+    writeln!(
+        buf,
+        "{:width$} {} {name}:{}:{}",
+        "", config.chars.snippet_start, location.line_number, location.column_number
+    )?;
+    buf.set_color(&config.styles.source_border)?;
+    writeln!(buf, "{:width$} {border}", "")?;
+    buf.reset()?;
+    for (sign, text, color) in [('-', original, Color::Red), ('+', &changed, Color::Green)] {
+        for (idx, line) in text.lines().enumerate() {
+            buf.set_color(&config.styles.line_number)?;
+            write!(buf, "{:>width$} ", first_number + idx)?;
+            buf.set_color(ColorSpec::new().set_fg(Some(color)))?;
+            writeln!(buf, "{sign} {line}")?;
+            buf.reset()?;
+        }
+    }
+    buf.set_color(&config.styles.source_border)?;
+    writeln!(buf, "{:width$} {border}", "")?;
+    buf.reset()?;
+    writeln!(buf)?;
+    Ok(())
+}
@@ -8,6 +8,7 @@ pub use codespan_reporting as reporting;
 
 pub mod diagnostic;
 pub mod error;
+pub mod lint;
 pub mod report;
 pub mod types;
 
@@ -17,5 +18,6 @@ pub mod prelude {
     pub use super::diagnostic::{self, EnsembleBuilder, LabelStyle};
     pub use super::err;
     pub use super::error::DiagnosticError;
+    pub use super::lint;
     pub use super::reporting::files::Files;
 }
@@ -0,0 +1,435 @@
+//! Lints: named non-fatal diagnostics whose level can be configured.
+//!
+//! Every lint has a default [`Level`]. This can be overridden for the whole
+//! compilation on the command line (`-A`, `-W`, `-D` and `-F`), and for a
+//! single expression with an attribute such as `@allow(unused_mut)`. Lints
+//! are reported with
+//! [`GlobalReportingCtxt::report_lint`](crate::report::GlobalReportingCtxt::report_lint),
+//! which looks up the level that applies at the span being reported.
+
+use std::fmt::{self, Display};
+
+use calypso_base::span::Span;
+
+/// The level of a lint.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum Level {
+    /// The lint is not reported.
+    Allow,
+    /// The lint is reported as a warning.
+    Warn,
+    /// The lint is reported as an error.
+    Deny,
+    /// The lint is reported as an error, and its level cannot be lowered by
+    /// an attribute.
+    Forbid,
+}
+
+impl Level {
+    /// All levels, from least to most strict.
+    pub const ALL: [Level; 4] = [Level::Allow, Level::Warn, Level::Deny, Level::Forbid];
+
+    /// The name of the level, as used in attributes.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Level::Allow => "allow",
+            Level::Warn => "warn",
+            Level::Deny => "deny",
+            Level::Forbid => "forbid",
+        }
+    }
+
+    /// The command-line flag used to set a lint to this level.
+    #[must_use]
+    pub fn flag(self) -> &'static str {
+        match self {
+            Level::Allow => "-A",
+            Level::Warn => "-W",
+            Level::Deny => "-D",
+            Level::Forbid => "-F",
+        }
+    }
+
+    /// Get a level from its name, if it is valid.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Level> {
+        Level::ALL.into_iter().find(|level| level.name() == name)
+    }
+}
+
+impl Display for Level {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A declared lint.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Lint {
+    /// The name of the lint, e.g. `unused_mut`.
+    pub name: &'static str,
+    /// The level of the lint if it is not overridden.
+    pub default_level: Level,
+    /// A one-line description of what the lint checks.
+    pub description: &'static str,
+    /// Extended information about the lint, shown by `calypso explain`.
+    pub explanation: &'static str,
+}
+
+/// A helper macro to declare lints and generate the list of all lints.
+macro_rules! declare_lints {
+    ($($(#[$meta:meta])* $lint:ident($name:literal, $level:ident): $description:literal, $explanation:literal;)*) => {
+        $(
+            $(#[$meta])*
+            pub static $lint: Lint = Lint {
+                name: $name,
+                default_level: Level::$level,
+                description: $description,
+                explanation: $explanation,
+            };
+        )*
+
+        /// All declared lints, in order of declaration.
+        pub static LINTS: &[&Lint] = &[$(&$lint),*];
+    };
+}
+
+declare_lints! {
+    /// Mutable variables that are never assigned to.
+    UNUSED_MUT("unused_mut", Warn):
+        "detects mutable variables that are never assigned to",
+        "A variable was declared as mutable, but it is never assigned to.
+
+Erroneous code example:
+
+```
+let mut x = 1 in x + 1
+```
+
+Remove the `mut` if the variable does not need to be mutable:
+
+```
+let x = 1 in x + 1
+```
+";
+
+    /// Match arms that are never reached.
+    UNREACHABLE_PATTERNS("unreachable_patterns", Warn):
+        "detects match arms that are never reached",
+        "A `case` arm will never be reached, because every value it matches
+is matched by an earlier arm.
+
+Erroneous code example:
+
+```
+case x do
+    _ -> 1,
+    true -> 2,
+end
+```
+
+Remove the unreachable arm, or move it before the arm that covers it.
+";
+
+    /// Bindings that are never used.
+    UNUSED_VARIABLES("unused_variables", Warn):
+        "detects variables that are never used",
+        "A variable is bound, but it is never used.
+
+Erroneous code example:
+
+```
+let x = 1 in 2
+```
+
+Remove the binding, or prefix its name with an underscore if it is
+intentionally unused:
+
+```
+let _x = 1 in 2
+```
+";
+
+    /// Bindings that shadow another binding in the same scope.
+    SHADOWED_BINDINGS("shadowed_bindings", Warn):
+        "detects `let` bindings that shadow a binding in the same scope",
+        "A `let` binds a name that is already bound by an enclosing `let`
+in the same scope, so the earlier binding can no longer be used.
+
+Erroneous code example:
+
+```
+let x = 1 in let y = x * 2 in let x = 3 in x + y
+```
+
+Give the new binding a different name:
+
+```
+let x = 1 in let y = x * 2 in let x_2 = 3 in x_2 + y
+```
+
+Rebinding a name to a value computed from the earlier binding, e.g.
+`let x = x + 1 in ...`, is not linted.
+";
+
+    /// Code that can never be evaluated.
+    UNREACHABLE_CODE("unreachable_code", Warn):
+        "detects code that can never be evaluated",
+        "An expression in a block comes after an expression that never
+finishes evaluating, such as a `break`, so it can never be evaluated.
+
+Remove the unreachable expressions.
+";
+
+    /// Parentheses that do not change how an expression is parsed.
+    UNUSED_PARENS("unused_parens", Warn):
+        "detects parentheses that are not needed",
+        "An expression is wrapped in parentheses that do not change how it
+is parsed.
+
+Erroneous code example:
+
+```
+let x = (1 + 2) in (x)
+```
+
+Remove the parentheses:
+
+```
+let x = 1 + 2 in x
+```
+";
+
+    /// Matches on constant values.
+    CONSTANT_CONDITIONS("constant_conditions", Warn):
+        "detects `case` expressions and `&&` and `||` operators whose condition is a constant",
+        "The value matched by a `case` expression, or the left operand of
+`&&` or `||`, is a constant, so the same branch is always taken.
+
+Erroneous code example:
+
+```
+case 1 + 1 do 2 -> true, _ -> false end
+```
+
+Replace the expression with the branch that is taken:
+
+```
+true
+```
+";
+
+    /// Lint names that are not recognized.
+    UNKNOWN_LINTS("unknown_lints", Warn):
+        "detects lint attributes that name lints that do not exist",
+        "A lint attribute such as `@allow` names a lint that does not exist.
+
+Erroneous code example:
+
+```
+@allow(unused_muts) let mut x = 1 in x
+```
+
+Check the spelling of the lint name. Lint names may be written with
+either `-` or `_` as a separator.
+";
+}
+
+/// Find a lint by its name. Both `-` and `_` are accepted as separators.
+#[must_use]
+pub fn find(name: &str) -> Option<&'static Lint> {
+    let name = name.replace('-', "_");
+    LINTS.iter().copied().find(|lint| lint.name == name)
+}
+
+/// Where the level of a lint was set.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LevelSource {
+    /// The lint is at its default level.
+    Default,
+    /// The level was set on the command line.
+    CommandLine,
+    /// The level was set by the attribute at the given span.
+    Attribute(usize, Span),
+}
+
+/// A lint level set by an attribute, applying to a span of a file.
+#[derive(Copy, Clone, Debug)]
+struct Scope {
+    file_id: usize,
+    span: Span,
+    lint: &'static str,
+    level: Level,
+    attr: Span,
+}
+
+/// The levels of lints, as set on the command line and by attributes.
+#[derive(Clone, Debug, Default)]
+pub struct LintLevels {
+    command_line: Vec<(&'static str, Level)>,
+    scopes: Vec<Scope>,
+}
+
+impl LintLevels {
+    /// Create a new set of lint levels, with all lints at their default
+    /// levels.
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Set the level of a lint for the whole compilation. If the lint was
+    /// already forbidden on the command line, its level is not lowered.
+    pub fn set_command_line(&mut self, lint: &'static Lint, level: Level) {
+        if let Some((_, old)) = self
+            .command_line
+            .iter_mut()
+            .find(|(name, _)| *name == lint.name)
+        {
+            if *old != Level::Forbid {
+                *old = level;
+            }
+        } else {
+            self.command_line.push((lint.name, level));
+        }
+    }
+
+    /// Set the level of a lint within `span`, by the attribute at `attr`.
+    /// Scopes must be pushed outermost first.
+    ///
+    /// # Errors
+    ///
+    /// If the lint is forbidden at `attr` and `level` would lower it, the
+    /// scope is not added and the source of the forbidding level is
+    /// returned.
+    pub fn push_scope(
+        &mut self,
+        lint: &'static Lint,
+        level: Level,
+        file_id: usize,
+        span: Span,
+        attr: Span,
+    ) -> Result<(), LevelSource> {
+        let (current, source) = self.level(lint, file_id, attr);
+        if current == Level::Forbid && level != Level::Forbid {
+            return Err(source);
+        }
+        self.scopes.push(Scope {
+            file_id,
+            span,
+            lint: lint.name,
+            level,
+            attr,
+        });
+        Ok(())
+    }
+
+    /// Get the level of a lint at a span, and where that level was set.
+    #[must_use]
+    pub fn level(&self, lint: &'static Lint, file_id: usize, span: Span) -> (Level, LevelSource) {
+        let mut level = self
+            .command_line
+            .iter()
+            .find(|(name, _)| *name == lint.name)
+            .map_or((lint.default_level, LevelSource::Default), |&(_, level)| {
+                (level, LevelSource::CommandLine)
+            });
+        // Scopes are pushed outermost first, so the innermost enclosing
+        // scope is applied last.
+        for scope in &self.scopes {
+            if scope.file_id == file_id
+                && scope.lint == lint.name
+                && scope.span.contains(span)
+                && level.0 != Level::Forbid
+            {
+                level = (scope.level, LevelSource::Attribute(file_id, scope.attr));
+            }
+        }
+        level
+    }
+
+    /// Remove all levels set by attributes.
+    pub fn clear_scopes(&mut self) {
+        self.scopes.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn find_accepts_either_separator() {
+        assert_eq!(find("unused_mut"), Some(&UNUSED_MUT));
+        assert_eq!(find("unused-mut"), Some(&UNUSED_MUT));
+        assert_eq!(find("unused_muts"), None);
+    }
+
+    #[test]
+    fn innermost_scope_wins() {
+        let mut levels = LintLevels::new();
+        assert_eq!(
+            levels.level(&UNUSED_MUT, 0, Span::new(5, 6)),
+            (Level::Warn, LevelSource::Default)
+        );
+
+        levels.set_command_line(&UNUSED_MUT, Level::Deny);
+        levels
+            .push_scope(
+                &UNUSED_MUT,
+                Level::Allow,
+                0,
+                Span::new(0, 10),
+                Span::new(0, 2),
+            )
+            .unwrap();
+        levels
+            .push_scope(
+                &UNUSED_MUT,
+                Level::Warn,
+                0,
+                Span::new(4, 8),
+                Span::new(4, 5),
+            )
+            .unwrap();
+
+        assert_eq!(
+            levels.level(&UNUSED_MUT, 0, Span::new(5, 6)),
+            (Level::Warn, LevelSource::Attribute(0, Span::new(4, 5)))
+        );
+        assert_eq!(
+            levels.level(&UNUSED_MUT, 0, Span::new(2, 3)),
+            (Level::Allow, LevelSource::Attribute(0, Span::new(0, 2)))
+        );
+        assert_eq!(
+            levels.level(&UNUSED_MUT, 0, Span::new(12, 13)),
+            (Level::Deny, LevelSource::CommandLine)
+        );
+        assert_eq!(
+            levels.level(&UNUSED_MUT, 1, Span::new(5, 6)),
+            (Level::Deny, LevelSource::CommandLine)
+        );
+    }
+
+    #[test]
+    fn forbid_cannot_be_lowered() {
+        let mut levels = LintLevels::new();
+        levels.set_command_line(&UNUSED_MUT, Level::Forbid);
+        levels.set_command_line(&UNUSED_MUT, Level::Allow);
+        assert_eq!(
+            levels.push_scope(
+                &UNUSED_MUT,
+                Level::Allow,
+                0,
+                Span::new(0, 10),
+                Span::new(0, 2)
+            ),
+            Err(LevelSource::CommandLine)
+        );
+        assert_eq!(
+            levels.level(&UNUSED_MUT, 0, Span::new(5, 6)),
+            (Level::Forbid, LevelSource::CommandLine)
+        );
+    }
+}
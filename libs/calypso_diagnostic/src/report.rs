@@ -1,14 +1,50 @@
 //! The global reporting context for diagnostics.
 
-use crate::diagnostic::EnsembleDiagnostic;
+use calypso_base::span::Span;
+use codespan_reporting::term::Config;
+
+use crate::{
+    diagnostic::{Builder, EnsembleBuilder, EnsembleDiagnostic, LabelStyle, Severity},
+    lint::{Level, LevelSource, Lint, LintLevels},
+};
+
+/// The format that diagnostics are emitted in.
+#[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+pub enum ErrorFormat {
+    /// Rich diagnostics with source snippets, for human consumption.
+    #[default]
+    Human,
+    /// One line per diagnostic, with its location and message.
+    Short,
+    /// One JSON object per line for each diagnostic, followed by a summary
+    /// record. Intended for machine interpretation.
+    Json,
+    /// A single SARIF 2.1.0 log containing every diagnostic, for code
+    /// scanning tools.
+    Sarif,
+}
+
+/// How diagnostics are emitted.
+#[derive(Clone, Debug, Default)]
+pub struct EmitConfig {
+    /// The format that diagnostics are emitted in
+    pub format: ErrorFormat,
+    /// Render syntax errors with source snippets in the human format, rather
+    /// than on a single line.
+    pub rich_errors: bool,
+    /// The configuration used to render diagnostics in the human and short
+    /// formats. Its display style is set from the format.
+    pub term: Config,
+}
 
 /// The global reporting context for diagnostics.
-// TODO(@ThePuzzlemaker: frame|diag):
-//   rewrite nonfatals as a better "lint" system
 pub struct GlobalReportingCtxt {
     errors: Vec<EnsembleDiagnostic>,
     nonfatals: Vec<EnsembleDiagnostic>,
     fatal: Option<EnsembleDiagnostic>,
+    lints: LintLevels,
+    emit: EmitConfig,
+    deferred: Option<Vec<EnsembleDiagnostic>>,
 }
 
 impl Default for GlobalReportingCtxt {
@@ -25,6 +61,9 @@ impl GlobalReportingCtxt {
             errors: Vec::new(),
             nonfatals: Vec::new(),
             fatal: None,
+            lints: LintLevels::new(),
+            emit: EmitConfig::default(),
+            deferred: None,
         }
     }
 
@@ -43,7 +82,8 @@ impl GlobalReportingCtxt {
         self.fatal = None;
     }
 
-    /// Clear the entire reporting context
+    /// Clear the entire reporting context, except for the deferred
+    /// diagnostics.
     pub fn clear(&mut self) {
         self.clear_fatal();
         self.clear_nonfatals();
@@ -60,6 +100,50 @@ impl GlobalReportingCtxt {
         self.nonfatals.push(value);
     }
 
+    /// Report a lint at `span`, using the builder provided. Depending on the
+    /// level of the lint at `span`, this is either ignored, reported as a
+    /// non-fatal warning, or reported as a synchronizable error.
+    pub fn report_lint(
+        &mut self,
+        lint: &'static Lint,
+        file_id: usize,
+        span: Span,
+        f: impl FnOnce(Builder) -> Builder,
+    ) {
+        let (level, source) = self.lints.level(lint, file_id, span);
+        let severity = match level {
+            Level::Allow => return,
+            Level::Warn => Severity::Warning,
+            Level::Deny | Level::Forbid => Severity::Error,
+        };
+        let diag = EnsembleBuilder::new()
+            .add(severity, |b| {
+                let b = f(b.lint(lint));
+                match source {
+                    LevelSource::Default => {
+                        b.note(format!("`@{level}({})` is on by default", lint.name))
+                    }
+                    LevelSource::CommandLine => b.note(format!(
+                        "requested on the command line with `{} {}`",
+                        level.flag(),
+                        lint.name
+                    )),
+                    LevelSource::Attribute(file_id, attr) => b.label(
+                        LabelStyle::Secondary,
+                        Some("the lint level is defined here"),
+                        file_id,
+                        attr,
+                    ),
+                }
+            })
+            .build();
+        if level == Level::Warn {
+            self.nonfatals.push(diag);
+        } else {
+            self.errors.push(diag);
+        }
+    }
+
     /// Report a fatal error. If there is already a fatal error reported, it
     /// will not be replaced.
     pub fn report_fatal(&mut self, value: EnsembleDiagnostic) {
@@ -80,9 +164,53 @@ impl GlobalReportingCtxt {
         self.fatal.as_ref()
     }
 
+    /// Get the levels of lints.
+    #[must_use]
+    pub fn lints(&self) -> &LintLevels {
+        &self.lints
+    }
+
+    /// Get the levels of lints, mutably.
+    pub fn lints_mut(&mut self) -> &mut LintLevels {
+        &mut self.lints
+    }
+
+    /// Get the configuration of how diagnostics are emitted.
+    #[must_use]
+    pub fn emit_config(&self) -> &EmitConfig {
+        &self.emit
+    }
+
+    /// Set the configuration of how diagnostics are emitted.
+    pub fn set_emit_config(&mut self, emit: EmitConfig) {
+        self.emit = emit;
+    }
+
     /// Get the list of synchronizable errors.
     #[must_use]
     pub fn errors(&self) -> &[EnsembleDiagnostic] {
         &self.errors
     }
+
+    /// Defer emitting all of the diagnostics reported so far, for error
+    /// formats that emit a single document once the command has finished
+    /// (e.g. SARIF). The diagnostics stay reported, so they are still
+    /// counted, and are kept when the context is cleared.
+    pub fn defer(&mut self) {
+        let reported = self
+            .nonfatals
+            .iter()
+            .chain(&self.errors)
+            .chain(&self.fatal)
+            .cloned();
+        self.deferred.get_or_insert_with(Vec::new).extend(reported);
+    }
+
+    /// Take the deferred diagnostics, in the order they were deferred. This
+    /// is `None` if [`defer`](Self::defer) was never called, i.e. if no
+    /// diagnostics were emitted, which is different from there being no
+    /// diagnostics to emit.
+    pub fn take_deferred(&mut self) -> Option<Vec<EnsembleDiagnostic>> {
+        self.deferred.take()
+    }
 }
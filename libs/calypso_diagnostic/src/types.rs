@@ -27,6 +27,15 @@ macro_rules! register_diagnostics {
                 $( m.insert(stringify!($ecode_no_msg), None); )*
                 m
             };
+
+            /// A map from error codes to the format strings of their short
+            /// messages.
+            pub static ref SHORT_MESSAGES: HashMap<&'static str, &'static str> = {
+                let mut m = HashMap::new();
+                $( m.insert(stringify!($ecode), $format); )*
+                $( m.insert(stringify!($ecode_no_msg), $format_no_msg); )*
+                m
+            };
         }
     };
 }
@@ -44,10 +53,31 @@ macro_rules! err {
     }};
 }
 
-// // todo(diag): add more extended diagnostic information. see #28.
 register_diagnostics! {
     // These diagnostics have detailed information in messages/<ERROR CODE>.md
-    ;
-    E0000: "Testing diagnostic, please ignore. If this is encountered in regular usage, please file an issue.",
     E0001: "A syntax error was encountered.",
+    E0002: "Array lengths must be unsigned integer literals.",
+    E0003: "The empty tuple type cannot be used here.",
+    E0004: "The syntax tree is malformed. This is likely a bug in the compiler; please file an issue.",
+    E0005: "Cannot find value `{}` in this scope.",
+    E0006: "Identifier `{}` is bound more than once in the same pattern.",
+    E0007: "Cannot assign to immutable variable `{}`.",
+    E0008: "Mismatched types: expected `{}`, found `{}`.",
+    E0009: "Cannot find type `{}` in this scope.",
+    E0010: "Wrong number of type arguments for `{}`: expected {}, found {}.",
+    E0011: "Wrong number of arguments to function: expected {}, found {}.",
+    E0012: "Non-exhaustive patterns: {} not covered.",
+    E0013: "This operation will divide by zero.",
+    E0014: "This arithmetic operation will overflow.",
+    E0015: "Literal out of range for `{}`.",
+    E0016: "Integers cannot be raised to a negative power.",
+    E0017: "Cannot find attribute `{}`.",
+    E0018: "Cannot lower the level of the forbidden lint `{}`.",
+    E0019: "Runtime error: {}.",
+    E0020: "Wrong number of fields for constructor `{}`: expected {}, found {}.",
+    E0021: "`break` outside of a loop.",
+    E0022: "`main` function has the wrong type: found `{}`.",
+    ;
+    // These diagnostics do not have detailed information. New diagnostics
+    // should have it, so this list should stay empty.
 }
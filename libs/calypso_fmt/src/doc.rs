@@ -0,0 +1,204 @@
+//! A Wadler-style pretty-printing engine.
+//!
+//! A [`Doc`] describes a document along with the places where it may be
+//! broken onto multiple lines. [`Doc::group`]s are laid out flat (with each
+//! [`Doc::line`] rendered as a space) if they fit within the line width, and
+//! otherwise have all of their direct line breaks broken.
+//!
+//! The layout algorithm is based on Christian Lindig's "Strictly Pretty",
+//! with [`Doc::BreakParent`] as in Prettier to force enclosing groups to
+//! break.
+
+/// A document.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Doc {
+    /// The empty document
+    Nil,
+    /// Some text, which must not contain newlines
+    Text(String),
+    /// A line break, or the given text if the enclosing group is flat
+    Line(&'static str),
+    /// A line break, which also forces all enclosing groups to break
+    HardLine,
+    /// Forces all enclosing groups to break, without producing any output
+    BreakParent,
+    /// A sequence of documents
+    Concat(Vec<Doc>),
+    /// Indent line breaks within a document by the given amount
+    Nest(usize, Box<Doc>),
+    /// A group, which is laid out flat if it fits
+    Group(Box<Doc>),
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+enum Mode {
+    Flat,
+    Break,
+}
+
+impl Doc {
+    /// Some text, which must not contain newlines.
+    #[must_use]
+    pub fn text(text: impl Into<String>) -> Self {
+        Doc::Text(text.into())
+    }
+
+    /// A line break, or a space if the enclosing group is flat.
+    #[must_use]
+    pub fn line() -> Self {
+        Doc::Line(" ")
+    }
+
+    /// A line break, or nothing if the enclosing group is flat.
+    #[must_use]
+    pub fn softline() -> Self {
+        Doc::Line("")
+    }
+
+    /// A sequence of documents.
+    #[must_use]
+    pub fn concat(docs: impl IntoIterator<Item = Doc>) -> Self {
+        Doc::Concat(docs.into_iter().collect())
+    }
+
+    /// Join documents with a separator.
+    #[must_use]
+    pub fn join(docs: impl IntoIterator<Item = Doc>, sep: &Doc) -> Self {
+        let mut joined = Vec::new();
+        for (idx, doc) in docs.into_iter().enumerate() {
+            if idx != 0 {
+                joined.push(sep.clone());
+            }
+            joined.push(doc);
+        }
+        Doc::Concat(joined)
+    }
+
+    /// Indent line breaks within this document.
+    #[must_use]
+    pub fn nest(self, indent: usize) -> Self {
+        Doc::Nest(indent, Box::new(self))
+    }
+
+    /// Lay this document out flat if it fits.
+    #[must_use]
+    pub fn group(self) -> Self {
+        Doc::Group(Box::new(self))
+    }
+
+    /// Render this document, trying to keep lines within `width` columns.
+    /// Lines never have trailing whitespace.
+    #[must_use]
+    pub fn render(&self, width: usize) -> String {
+        let mut out = String::new();
+        let mut col = 0;
+        let mut stack = vec![(0, Mode::Break, self)];
+
+        while let Some((indent, mode, doc)) = stack.pop() {
+            match doc {
+                Doc::Nil | Doc::BreakParent => {}
+                Doc::Text(text) => {
+                    out.push_str(text);
+                    col += text.chars().count();
+                }
+                Doc::Line(flat) if mode == Mode::Flat => {
+                    out.push_str(flat);
+                    col += flat.chars().count();
+                }
+                Doc::Line(_) | Doc::HardLine => {
+                    out.truncate(out.trim_end_matches(' ').len());
+                    out.push('\n');
+                    out.extend(std::iter::repeat(' ').take(indent));
+                    col = indent;
+                }
+                Doc::Concat(docs) => stack.extend(docs.iter().rev().map(|doc| (indent, mode, doc))),
+                Doc::Nest(nest, doc) => stack.push((indent + nest, mode, doc)),
+                Doc::Group(doc) => {
+                    let mode = if mode == Mode::Flat || fits(width.saturating_sub(col), doc, &stack)
+                    {
+                        Mode::Flat
+                    } else {
+                        Mode::Break
+                    };
+                    stack.push((indent, mode, doc));
+                }
+            }
+        }
+
+        out.truncate(out.trim_end_matches(' ').len());
+        out
+    }
+}
+
+/// Check if `doc` fits flat within the `remaining` width, along with the
+/// rest of the document up to its next line break.
+fn fits(remaining: usize, doc: &Doc, rest: &[(usize, Mode, &Doc)]) -> bool {
+    let mut remaining = remaining;
+    let mut stack = vec![(Mode::Flat, doc)];
+    let mut rest = rest.iter().rev();
+
+    loop {
+        let (mode, doc) = match stack.pop() {
+            Some(next) => next,
+            None => match rest.next() {
+                Some(&(_, mode, doc)) => (mode, doc),
+                None => return true,
+            },
+        };
+
+        let text = match doc {
+            Doc::BreakParent if mode == Mode::Flat => return false,
+            Doc::Nil | Doc::BreakParent => continue,
+            Doc::Text(text) => text.as_str(),
+            Doc::Line(flat) if mode == Mode::Flat => flat,
+            Doc::Line(_) => return true,
+            Doc::HardLine => return mode == Mode::Break,
+            Doc::Concat(docs) => {
+                stack.extend(docs.iter().rev().map(|doc| (mode, doc)));
+                continue;
+            }
+            Doc::Nest(_, doc) | Doc::Group(doc) => {
+                stack.push((mode, doc));
+                continue;
+            }
+        };
+
+        let len = text.chars().count();
+        if len > remaining {
+            return false;
+        }
+        remaining -= len;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn groups_break_when_too_wide() {
+        let doc = Doc::concat([
+            Doc::text("do"),
+            Doc::concat([Doc::line(), Doc::text("a;"), Doc::line(), Doc::text("b")]).nest(4),
+            Doc::line(),
+            Doc::text("end"),
+        ])
+        .group();
+
+        assert_eq!(doc.render(80), "do a; b end");
+        assert_eq!(doc.render(8), "do\n    a;\n    b\nend");
+    }
+
+    #[test]
+    fn break_parent_forces_groups_to_break() {
+        let doc = Doc::concat([
+            Doc::text("a"),
+            Doc::concat([Doc::line(), Doc::text("b // c"), Doc::BreakParent]).group(),
+            Doc::line(),
+            Doc::text("d"),
+        ])
+        .group();
+
+        assert_eq!(doc.render(80), "a\nb // c\nd");
+    }
+}
@@ -0,0 +1,555 @@
+//! Conversion of the AST into a [`Doc`].
+
+use calypso_ast::{
+    expr::{
+        prec::{CALL, LET, UNARY},
+        Arm, Attr, BinOpKind, Expr, ExprKind, Mutability, Param, Primary,
+    },
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+use calypso_base::{
+    span::{Span, Spanned},
+    symbol::Symbol,
+};
+
+use crate::{doc::Doc, Config};
+
+/// A comment in the source. Comments are not part of the AST, so they are
+/// placed back in between the expressions in blocks and at the top level,
+/// before the expression that follows them, or after the expression that
+/// they trail on the same line where a line break can follow.
+pub struct Comment<'src> {
+    pub span: Span,
+    pub text: &'src str,
+}
+
+pub struct Formatter<'src, 'cfg> {
+    src: &'src str,
+    config: &'cfg Config,
+    /// Comments, in source order
+    comments: Vec<Comment<'src>>,
+    /// The index of the next comment that has not been output yet
+    next_comment: usize,
+}
+
+impl<'src, 'cfg> Formatter<'src, 'cfg> {
+    pub fn new(src: &'src str, config: &'cfg Config, comments: Vec<Comment<'src>>) -> Self {
+        Self {
+            src,
+            config,
+            comments,
+            next_comment: 0,
+        }
+    }
+
+    /// Format a whole file.
+    pub fn file(mut self, exprs: &[Spanned<Expr>]) -> Doc {
+        let end = u32::try_from(self.src.len()).expect("source files are at most u32::MAX bytes");
+        let items = self.items(exprs, &Doc::HardLine, end);
+        if items == Doc::Concat(Vec::new()) {
+            items
+        } else {
+            Doc::concat([items, Doc::HardLine])
+        }
+    }
+
+    /// Take the next comment if it starts before `pos`.
+    fn comment_before(&mut self, pos: u32) -> Option<(Span, Doc)> {
+        let comment = self.comments.get(self.next_comment)?;
+        if comment.span.lo() >= pos {
+            return None;
+        }
+        self.next_comment += 1;
+        Some((comment.span, Doc::text(comment.text)))
+    }
+
+    /// Take the next comment if it is on the same line as, and directly
+    /// after, an expression ending at `hi`, and before `next`.
+    fn trailing_comment(&mut self, hi: u32, next: u32) -> Option<(Span, Doc)> {
+        let comment = self.comments.get(self.next_comment)?;
+        let lo = comment.span.lo();
+        if lo < hi || lo >= next || self.src[hi as usize..lo as usize].contains('\n') {
+            return None;
+        }
+        self.next_comment += 1;
+        Some((comment.span, Doc::text(comment.text)))
+    }
+
+    /// Take the comments before `pos`, each followed by a line break.
+    fn leading_comments(&mut self, pos: u32) -> Vec<Doc> {
+        let mut docs = Vec::new();
+        while let Some((_, comment)) = self.comment_before(pos) {
+            docs.extend([comment, Doc::HardLine]);
+        }
+        docs
+    }
+
+    /// Output a blank line if there were any blank lines in the source
+    /// between `prev` and `next`. Comments may have been moved out of an
+    /// expression before `prev`, in which case there is nothing to check.
+    fn blank_line(&self, docs: &mut Vec<Doc>, prev: Option<u32>, next: u32) {
+        if let Some(prev) = prev.filter(|&prev| prev <= next) {
+            let between = &self.src[prev as usize..next as usize];
+            if between.matches('\n').count() >= 2 {
+                docs.push(Doc::HardLine);
+            }
+        }
+    }
+
+    /// Format a list of expressions separated by semicolons, along with the
+    /// comments before `end`.
+    fn items(&mut self, items: &[Spanned<Expr>], sep: &Doc, end: u32) -> Doc {
+        let mut docs = Vec::new();
+        // The end of the last expression or comment that was output.
+        let mut prev = None;
+
+        for (idx, item) in items.iter().enumerate() {
+            let span = item.span();
+            if idx != 0 {
+                docs.push(sep.clone());
+            }
+
+            while let Some((comment_span, comment)) = self.comment_before(span.lo()) {
+                self.blank_line(&mut docs, prev, comment_span.lo());
+                docs.extend([comment, Doc::HardLine]);
+                prev = Some(comment_span.hi());
+            }
+            self.blank_line(&mut docs, prev, span.lo());
+
+            docs.push(self.expr(item.as_ref(), LET));
+            if idx + 1 != items.len() {
+                docs.push(Doc::text(";"));
+            }
+            prev = Some(span.hi());
+
+            let next = items.get(idx + 1).map_or(end, |item| item.span().lo());
+            if let Some((comment_span, comment)) = self.trailing_comment(span.hi(), next) {
+                docs.extend([Doc::text(" "), comment, Doc::BreakParent]);
+                prev = Some(comment_span.hi());
+            }
+        }
+
+        while let Some((comment_span, comment)) = self.comment_before(end) {
+            if !docs.is_empty() {
+                docs.push(Doc::HardLine);
+            }
+            self.blank_line(&mut docs, prev, comment_span.lo());
+            docs.extend([comment, Doc::BreakParent]);
+            prev = Some(comment_span.hi());
+        }
+
+        Doc::Concat(docs)
+    }
+
+    /// Format an expression, parenthesizing it if its precedence is lower
+    /// than `min_prec`. Comments before the expression are output before
+    /// it.
+    fn expr(&mut self, expr: Spanned<&Expr>, min_prec: u8) -> Doc {
+        let indent = self.config.indent;
+        let mut leading = self.leading_comments(expr.span().lo());
+        let doc = match &expr.value().kind {
+            ExprKind::BinOp(left, op, right) => self.binop(
+                left.as_ref().map(|x| &**x),
+                *op.value(),
+                right.as_ref().map(|x| &**x),
+            ),
+            ExprKind::UnOp(op, operand) => {
+                // Nested unary operators are separated, so that e.g. `-(-1)`
+                // is not run together into `--1`.
+                let sep = if matches!(operand.value().kind, ExprKind::UnOp(..)) {
+                    " "
+                } else {
+                    ""
+                };
+                Doc::concat([
+                    Doc::text(format!("{}{sep}", op.value())),
+                    self.expr(operand.as_ref().map(|x| &**x), UNARY),
+                ])
+            }
+            ExprKind::Primary(primary) => match *primary.value() {
+                Primary::Number(_) => Doc::text(&self.src[primary.span().into_range()]),
+                Primary::Bool(b) => Doc::text(b.to_string()),
+                Primary::Symbol(sym) => Doc::text(sym.as_str()),
+            },
+            ExprKind::Block(items) => self.block(items, expr.span().hi()),
+            ExprKind::Let(mutability, name, ty, value, body) => self.let_expr(
+                *mutability,
+                *name.value(),
+                ty.as_ref(),
+                value.as_ref().map(|x| &**x),
+                body.as_ref().map(|x| &**x),
+            ),
+            ExprKind::Assign(name, value) => Doc::concat([
+                Doc::text(format!("{} =", name.value())),
+                Doc::concat([Doc::line(), self.expr(value.as_ref().map(|x| &**x), LET)])
+                    .nest(indent),
+            ])
+            .group(),
+            ExprKind::Tuple(exprs) => {
+                let exprs = exprs
+                    .iter()
+                    .map(|expr| self.expr(expr.as_ref(), LET))
+                    .collect::<Vec<_>>();
+                tuple(exprs)
+            }
+            ExprKind::Case(scrutinee, arms) => {
+                self.case(scrutinee.as_ref().map(|x| &**x), arms, expr.span().hi())
+            }
+            ExprKind::Call(callee, args) => {
+                let callee = self.expr(callee.as_ref().map(|x| &**x), CALL);
+                let args = args
+                    .iter()
+                    .map(|arg| self.expr(arg.as_ref(), LET))
+                    .collect::<Vec<_>>();
+                Doc::concat([callee, call_args(args, indent)])
+            }
+            ExprKind::Fn(ty_params, name, params, ret, body) => self.fn_item(
+                ty_params,
+                *name.value(),
+                params,
+                ret.as_ref(),
+                body.as_ref().map(|x| &**x),
+            ),
+            ExprKind::Attributed(attrs, expr) => Doc::concat([
+                attributes(attrs),
+                Doc::line(),
+                self.expr(expr.as_ref().map(|x| &**x), LET),
+            ])
+            .group(),
+            ExprKind::Loop(body) => Doc::concat([
+                Doc::text("loop "),
+                self.expr(body.as_ref().map(|x| &**x), LET),
+            ]),
+            ExprKind::Break(None) => Doc::text("break"),
+            ExprKind::Break(Some(value)) => Doc::concat([
+                Doc::text("break"),
+                Doc::concat([Doc::line(), self.expr(value.as_ref().map(|x| &**x), LET)])
+                    .nest(indent),
+            ])
+            .group(),
+        };
+
+        let doc = if expr.value().precedence() < min_prec {
+            Doc::concat([Doc::text("("), doc, Doc::text(")")])
+        } else {
+            doc
+        };
+        if leading.is_empty() {
+            doc
+        } else {
+            leading.push(doc);
+            Doc::Concat(leading)
+        }
+    }
+
+    /// Format a `let` expression. If it does not fit on one line, the value
+    /// and the body go on their own lines.
+    fn let_expr(
+        &mut self,
+        mutability: Mutability,
+        name: Symbol,
+        ty: Option<&Spanned<Ty>>,
+        value: Spanned<&Expr>,
+        body: Spanned<&Expr>,
+    ) -> Doc {
+        let indent = self.config.indent;
+        let mut header = vec![Doc::text("let ")];
+        if mutability == Mutability::Mut {
+            header.push(Doc::text("mut "));
+        }
+        header.push(Doc::text(name.as_str()));
+        if let Some(ty) = ty {
+            header.extend([Doc::text(": "), self.ty(ty.as_ref())]);
+        }
+        header.extend([
+            Doc::text(" ="),
+            Doc::concat([
+                Doc::line(),
+                // The value of a `let` cannot itself be a `let`.
+                self.expr(value, LET + 1),
+            ])
+            .nest(indent),
+            Doc::text(" in"),
+        ]);
+        // A comment after the value on the same line is kept after the `in`.
+        let trailing = self.trailing_comment(value.span().hi(), body.span().lo());
+        Doc::concat([
+            Doc::concat(header).group(),
+            trailing_doc(trailing),
+            Doc::line(),
+            self.expr(body, LET),
+        ])
+        .group()
+    }
+
+    /// Format a block ending at `hi`. Comments after the last expression go
+    /// before the `end`.
+    fn block(&mut self, items: &[Spanned<Expr>], hi: u32) -> Doc {
+        let end = self.src[..hi as usize].rfind("end").map_or(hi, |end| {
+            u32::try_from(end).expect("source files are at most u32::MAX bytes")
+        });
+        let body = self.items(items, &Doc::line(), end);
+        if body == Doc::Concat(Vec::new()) {
+            Doc::text("do end")
+        } else {
+            Doc::concat([
+                Doc::text("do"),
+                Doc::concat([Doc::line(), body]).nest(self.config.indent),
+                Doc::line(),
+                Doc::text("end"),
+            ])
+            .group()
+        }
+    }
+
+    /// Format a binary operation. A comment after the left operand on the
+    /// same line is kept there, before the operator.
+    fn binop(&mut self, left: Spanned<&Expr>, op: BinOpKind, right: Spanned<&Expr>) -> Doc {
+        let (left_prec, right_prec) = op.operand_precedences();
+        let left_doc = self.expr(left, left_prec);
+        let trailing = self.trailing_comment(left.span().hi(), right.span().lo());
+        Doc::concat([
+            left_doc,
+            trailing_doc(trailing),
+            Doc::concat([
+                Doc::line(),
+                Doc::text(format!("{op} ")),
+                self.expr(right, right_prec),
+            ])
+            .nest(self.config.indent),
+        ])
+        .group()
+    }
+
+    /// Format a function item. If it does not fit on one line, the body goes
+    /// on the next line.
+    fn fn_item(
+        &mut self,
+        ty_params: &[Spanned<Symbol>],
+        name: Symbol,
+        params: &[Spanned<Param>],
+        ret: Option<&Spanned<Ty>>,
+        body: Spanned<&Expr>,
+    ) -> Doc {
+        let mut header = Vec::new();
+        if !ty_params.is_empty() {
+            let ty_params = ty_params
+                .iter()
+                .map(|param| Doc::text(param.value().as_str()))
+                .collect::<Vec<_>>();
+            header.extend([
+                Doc::text("with("),
+                Doc::join(ty_params, &Doc::text(", ")),
+                Doc::text(") "),
+            ]);
+        }
+        let params = params
+            .iter()
+            .map(|param| self.param(param.value()))
+            .collect::<Vec<_>>();
+        header.extend([
+            Doc::text(format!("fn {name}(")),
+            Doc::join(params, &Doc::text(", ")),
+            Doc::text(")"),
+        ]);
+        if let Some(ret) = ret {
+            header.extend([Doc::text(": "), self.ty(ret.as_ref())]);
+        }
+        header.push(Doc::text(" ->"));
+        Doc::concat([
+            Doc::Concat(header),
+            Doc::concat([Doc::line(), self.expr(body, LET)]).nest(self.config.indent),
+        ])
+        .group()
+    }
+
+    /// Format a `case` expression ending at `hi`. If it does not fit on one
+    /// line, each arm goes on its own line.
+    fn case(&mut self, scrutinee: Spanned<&Expr>, arms: &[Spanned<Arm>], hi: u32) -> Doc {
+        let indent = self.config.indent;
+        // `case break do ... end` would parse the block as the value of the
+        // `break`.
+        let min_prec = if matches!(scrutinee.value().kind, ExprKind::Break(None)) {
+            LET + 1
+        } else {
+            LET
+        };
+        let scrutinee = self.expr(scrutinee, min_prec);
+        let mut docs = Vec::new();
+        for (idx, arm) in arms.iter().enumerate() {
+            docs.push(Doc::line());
+            docs.extend(self.leading_comments(arm.span().lo()));
+            let body = arm.value().body.as_ref();
+            docs.push(
+                Doc::concat([
+                    self.pat(arm.value().pat.as_ref()),
+                    Doc::text(" ->"),
+                    Doc::concat([Doc::line(), self.expr(body, LET)]).nest(indent),
+                ])
+                .group(),
+            );
+            let next = arms.get(idx + 1).map_or(hi, |arm| arm.span().lo());
+            if idx + 1 != arms.len() {
+                docs.push(Doc::text(","));
+            }
+            docs.push(trailing_doc(self.trailing_comment(body.span().hi(), next)));
+        }
+        // Comments after the last arm go before the `end`.
+        while let Some((_, comment)) = self.comment_before(hi) {
+            docs.extend([Doc::HardLine, comment]);
+        }
+        Doc::concat([
+            Doc::text("case "),
+            scrutinee,
+            Doc::text(" do"),
+            Doc::Concat(docs).nest(indent),
+            Doc::line(),
+            Doc::text("end"),
+        ])
+        .group()
+    }
+
+    fn pat(&mut self, pat: Spanned<&Pat>) -> Doc {
+        match &pat.value().kind {
+            PatKind::Wild => Doc::text("_"),
+            PatKind::Binding(Mutability::Immut, name) => Doc::text(name.value().as_str()),
+            PatKind::Binding(Mutability::Mut, name) => Doc::text(format!("mut {}", name.value())),
+            PatKind::Bool(b) => Doc::text(b.to_string()),
+            PatKind::Number(num) => Doc::text(&self.src[num.span().into_range()]),
+            PatKind::Neg(num) => Doc::text(format!("-{}", &self.src[num.span().into_range()])),
+            PatKind::Tuple(pats) => {
+                let pats = pats
+                    .iter()
+                    .map(|pat| self.pat(pat.as_ref()))
+                    .collect::<Vec<_>>();
+                tuple(pats)
+            }
+            PatKind::Ctor(name, pats) => {
+                let pats = pats
+                    .iter()
+                    .map(|pat| self.pat(pat.as_ref()))
+                    .collect::<Vec<_>>();
+                Doc::concat([
+                    Doc::text(format!("{}(", name.value())),
+                    Doc::join(pats, &Doc::text(", ")),
+                    Doc::text(")"),
+                ])
+            }
+        }
+    }
+
+    fn param(&mut self, param: &Param) -> Doc {
+        let mut docs = Vec::new();
+        if let Mutability::Mut = param.mutability {
+            docs.push(Doc::text("mut "));
+        }
+        docs.push(Doc::text(param.name.value().as_str()));
+        if let Some(ty) = &param.ty {
+            docs.extend([Doc::text(": "), self.ty(ty.as_ref())]);
+        }
+        Doc::Concat(docs)
+    }
+
+    fn ty(&mut self, ty: Spanned<&Ty>) -> Doc {
+        match &ty.value().kind {
+            TyKind::Symbol(sym) => Doc::text(sym.value().as_str()),
+            TyKind::Apply(sym, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.ty(arg.as_ref()))
+                    .collect::<Vec<_>>();
+                Doc::concat([
+                    Doc::text(sym.value().as_str()),
+                    Doc::text("["),
+                    Doc::join(args, &Doc::text(", ")),
+                    Doc::text("]"),
+                ])
+            }
+            TyKind::IndefArray(elem) => Doc::concat([
+                Doc::text("["),
+                self.ty(elem.as_ref().map(|x| &**x)),
+                Doc::text("]"),
+            ]),
+            TyKind::DefArray(elem, len) => Doc::concat([
+                Doc::text("["),
+                self.ty(elem.as_ref().map(|x| &**x)),
+                Doc::text("; "),
+                Doc::text(&self.src[len.span().into_range()]),
+                Doc::text("]"),
+            ]),
+            TyKind::Tuple(elems) => {
+                let elems = elems
+                    .iter()
+                    .map(|elem| self.ty(elem.as_ref()))
+                    .collect::<Vec<_>>();
+                Doc::concat([
+                    Doc::text("("),
+                    Doc::join(elems, &Doc::text(", ")),
+                    Doc::text(")"),
+                ])
+            }
+        }
+    }
+}
+
+/// Format a comment that trails an expression on the same line. The line
+/// break after the comment must come from the enclosing group, which this
+/// forces to break.
+fn trailing_doc(comment: Option<(Span, Doc)>) -> Doc {
+    match comment {
+        Some((_, comment)) => Doc::concat([Doc::text(" "), comment, Doc::BreakParent]),
+        None => Doc::Nil,
+    }
+}
+
+/// Format the elements of a tuple expression or pattern. A tuple with one
+/// element needs a trailing comma, so that it is not parsed as parentheses.
+fn tuple(elems: Vec<Doc>) -> Doc {
+    let trailing = if elems.len() == 1 { "," } else { "" };
+    Doc::concat([
+        Doc::text("("),
+        Doc::join(elems, &Doc::text(", ")),
+        Doc::text(format!("{trailing})")),
+    ])
+}
+
+/// Format the arguments of a call. If they do not fit on one line, each
+/// argument goes on its own line.
+fn call_args(args: Vec<Doc>, indent: usize) -> Doc {
+    if args.is_empty() {
+        return Doc::text("()");
+    }
+    Doc::concat([
+        Doc::text("("),
+        Doc::concat([
+            Doc::softline(),
+            Doc::join(args, &Doc::concat([Doc::text(","), Doc::line()])),
+        ])
+        .nest(indent),
+        Doc::softline(),
+        Doc::text(")"),
+    ])
+    .group()
+}
+
+/// Format a list of attributes, separated by spaces.
+fn attributes(attrs: &[Spanned<Attr>]) -> Doc {
+    let attrs = attrs
+        .iter()
+        .map(|attr| {
+            let attr = attr.value();
+            let args = attr
+                .args
+                .iter()
+                .map(|arg| Doc::text(arg.value().as_str()))
+                .collect::<Vec<_>>();
+            Doc::concat([
+                Doc::text(format!("@{}(", attr.name.value())),
+                Doc::join(args, &Doc::text(", ")),
+                Doc::text(")"),
+            ])
+        })
+        .collect::<Vec<_>>();
+    Doc::join(attrs, &Doc::text(" "))
+}
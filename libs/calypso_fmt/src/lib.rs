@@ -0,0 +1,82 @@
+//! Source code formatter for Calypso.
+//!
+//! Formatting is done by converting the AST into a [`Doc`](doc::Doc), which
+//! is then laid out to fit within the configured line width. Comments are
+//! not part of the AST, so they are re-attached from the token stream:
+//! comments are kept on their own lines in between and before expressions,
+//! or after an expression on the same line where a line break can follow
+//! it. Other comments are moved to the next place where they can go.
+#![doc(html_root_url = "https://calypso-lang.github.io/rustdoc/calypso_fmt/index.html")]
+#![warn(clippy::pedantic)]
+
+use std::sync::Arc;
+
+use calypso_ast::{arena::Arena, node::NodeIdGen};
+use calypso_base::span::Span;
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::prelude::*;
+use calypso_parsing::{
+    lexer::{self, Token},
+    parser,
+};
+
+use format::{Comment, Formatter};
+
+pub mod doc;
+mod format;
+
+/// Formatter configuration.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Config {
+    /// The maximum line width
+    pub width: usize,
+    /// The number of spaces to indent by
+    pub indent: usize,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            width: 80,
+            indent: 4,
+        }
+    }
+}
+
+/// Format a source file.
+///
+/// If the file has syntax errors, they are reported to the global reporting
+/// context and `None` is returned.
+///
+/// # Errors
+///
+/// This function will error if parsing errors.
+///
+/// # Panics
+///
+/// This function will panic if `file_id` does not refer to a file in the
+/// source manager.
+pub fn format(gcx: &Arc<GlobalCtxt>, file_id: usize, config: &Config) -> CalResult<Option<String>> {
+    let arena = Arena::new();
+    let Some(exprs) = parser::parse(gcx, file_id, &NodeIdGen::new(), &arena)? else {
+        return Ok(None);
+    };
+
+    let sourcemgr = gcx.sourcemgr.read();
+    let src = sourcemgr.source(file_id).unwrap();
+    let comments = lexer::tokens(src, file_id, Arc::clone(gcx))
+        .filter(|tok| matches!(tok.value().0, Token::Comment(_)))
+        .map(|tok| {
+            // Line comments include their trailing newline.
+            let text = tok.value().1.trim_end();
+            let len = u32::try_from(text.len()).expect("source files are at most u32::MAX bytes");
+            Comment {
+                span: Span::new(tok.span().lo(), tok.span().lo() + len),
+                text,
+            }
+        })
+        .collect();
+
+    let doc = Formatter::new(src, config, comments).file(&exprs);
+    Ok(Some(doc.render(config.width)))
+}
@@ -0,0 +1,259 @@
+use std::sync::Arc;
+
+use calypso_ast::{arena::Arena, node::NodeIdGen, spanless::SpanlessEq};
+use calypso_base::ui::{termcolor::ColorChoice, Emitters};
+use calypso_common::{gcx::GlobalCtxt, parking_lot::RwLock};
+use calypso_diagnostic::{diagnostic::SourceMgr, report::GlobalReportingCtxt};
+use calypso_fmt::Config;
+use calypso_parsing::parser;
+
+fn gcx() -> Arc<GlobalCtxt> {
+    Arc::new(GlobalCtxt {
+        emit: RwLock::new(Emitters::new(ColorChoice::Never, ColorChoice::Never)),
+        grcx: RwLock::new(GlobalReportingCtxt::new()),
+        sourcemgr: RwLock::new(SourceMgr::new()),
+    })
+}
+
+fn format(src: &str, width: usize) -> String {
+    let gcx = gcx();
+    let file_id = gcx
+        .sourcemgr
+        .write()
+        .add("<test>".to_string(), src.to_string());
+    let config = Config {
+        width,
+        ..Config::default()
+    };
+    calypso_fmt::format(&gcx, file_id, &config)
+        .unwrap()
+        .unwrap_or_else(|| panic!("failed to parse {src:?}"))
+}
+
+/// Check that the AST of `src` is unchanged by formatting.
+fn assert_same_ast(src: &str, formatted: &str) {
+    let arena = Arena::new();
+    let before = parser::parse_str(src, &NodeIdGen::new(), &arena);
+    let after = parser::parse_str(formatted, &NodeIdGen::new(), &arena);
+    assert!(
+        SpanlessEq::new(src, formatted).eq_exprs(&before, &after),
+        "formatting {src:?} changed its meaning:\n{formatted}"
+    );
+}
+
+/// Check that `src` formats as `expected` within `width` columns, that the
+/// meaning of the code is preserved, and that formatting is idempotent.
+fn check_width(src: &str, expected: &str, width: usize) {
+    let formatted = format(src, width);
+    assert_eq!(formatted, expected, "formatting {src:?}");
+    assert_same_ast(src, &formatted);
+    assert_eq!(
+        format(&formatted, width),
+        formatted,
+        "formatting is not idempotent for {src:?}"
+    );
+}
+
+fn check(src: &str, expected: &str) {
+    check_width(src, expected, 80);
+}
+
+#[test]
+fn empty() {
+    check("", "");
+    check("\n\n", "");
+}
+
+#[test]
+fn operators() {
+    check("1+2*3", "1 + 2 * 3\n");
+    check("(1+2)*3", "(1 + 2) * 3\n");
+    check("1-(2-3)", "1 - (2 - 3)\n");
+    check("(1-2)-3", "1 - 2 - 3\n");
+    check("2**3**4", "2 ** 3 ** 4\n");
+    check("(2**3)**4", "(2 ** 3) ** 4\n");
+    check("-x**2", "-x ** 2\n");
+    check("!(a&&b)||c", "!(a && b) || c\n");
+    check("0x1F+1_000", "0x1F + 1_000\n");
+    // Nested unary operators are not run together.
+    check("-(-1)", "- -1\n");
+    check("- -1", "- -1\n");
+    check("!(!x)", "! !x\n");
+    check("-(!x)", "- !x\n");
+}
+
+#[test]
+fn let_expressions() {
+    check("let x=1 in x", "let x = 1 in x\n");
+    check("let mut x:[int;4]=y in x", "let mut x: [int; 4] = y in x\n");
+    check(
+        "let x:Result[ [T] ,(uint,)]=y in x",
+        "let x: Result[[T], (uint)] = y in x\n",
+    );
+    check("(let x = 1 in x) + 1", "(let x = 1 in x) + 1\n");
+    check(
+        "let x = (let y = 1 in y) in x",
+        "let x = (let y = 1 in y) in x\n",
+    );
+}
+
+#[test]
+fn assignment() {
+    check("x=y=1", "x = y = 1\n");
+    check("(x=1)*2", "(x = 1) * 2\n");
+    check("let mut x=1 in x=x+1", "let mut x = 1 in x = x + 1\n");
+}
+
+#[test]
+fn tuples_and_case() {
+    check("( )", "()\n");
+    check("(x ,)", "(x,)\n");
+    check("(1,(2,3),)", "(1, (2, 3))\n");
+    check(
+        "case (x,y) do (0,_)->a, (mut n,(true,)) -> n, _->() , end",
+        "case (x, y) do (0, _) -> a, (mut n, (true,)) -> n, _ -> () end\n",
+    );
+    check(
+        "case x do - 1->1, (-0x2s,-1.5)->2, _ -> -3 end",
+        "case x do -1 -> 1, (-0x2s, -1.5) -> 2, _ -> -3 end\n",
+    );
+    check_width(
+        "case x do true -> let y = 1 in y, false -> 2 end",
+        "case x do\n    true -> let y = 1 in y,\n    false -> 2\nend\n",
+        32,
+    );
+}
+
+#[test]
+fn functions() {
+    check("fn  f ( ) ->1", "fn f() -> 1\n");
+    check(
+        "with(T,U,) fn pair(mut x:T,y:U):(T,U)->(x,y)",
+        "with(T, U) fn pair(mut x: T, y: U): (T, U) -> (x, y)\n",
+    );
+    check("f ( 1,g(x), )(y)", "f(1, g(x))(y)\n");
+    check("(f+g)(-x)", "(f + g)(-x)\n");
+    check(
+        "case o do Some( (a,None) )->a, Err(e,)->e end",
+        "case o do Some((a, None)) -> a, Err(e) -> e end\n",
+    );
+    check_width(
+        "fn add(x, y) -> let z = x + y in z",
+        "fn add(x, y) ->\n    let z = x + y in z\n",
+        24,
+    );
+    check_width(
+        "compute(first_argument, second(x, y))",
+        "compute(\n    first_argument,\n    second(x, y)\n)\n",
+        24,
+    );
+    check_width("f()", "f()\n", 2);
+}
+
+#[test]
+fn loops() {
+    check("loop  do break(1) end", "loop do break 1 end\n");
+    check(
+        "do loop do break end; 2 end",
+        "do loop do break end; 2 end\n",
+    );
+    check("case (break) do _ -> 1 end", "case (break) do _ -> 1 end\n");
+    check_width(
+        "loop do break let x = 1 in x end",
+        "loop do\n    break\n        let x = 1 in x\nend\n",
+        22,
+    );
+}
+
+#[test]
+fn attributes() {
+    check(
+        "@allow( unused_mut ,)@deny(a,b) let mut x=1 in x",
+        "@allow(unused_mut) @deny(a, b) let mut x = 1 in x\n",
+    );
+    check("let x = (@allow(a) y) in x", "let x = (@allow(a) y) in x\n");
+    check("(@warn() 1) + 2", "(@warn() 1) + 2\n");
+    check_width(
+        "@allow(unused_mut) let mut x = 1 in x",
+        "@allow(unused_mut)\nlet mut x = 1 in x\n",
+        24,
+    );
+}
+
+#[test]
+fn blocks() {
+    check("do end", "do end\n");
+    check("do a;b end", "do a; b end\n");
+    check_width(
+        "do let x = 1 in x + 1; foo end",
+        "do\n    let x = 1 in x + 1;\n    foo\nend\n",
+        24,
+    );
+    check_width(
+        "do do aaaa; bbbb end; cccc end",
+        "do\n    do aaaa; bbbb end;\n    cccc\nend\n",
+        24,
+    );
+}
+
+#[test]
+fn comments() {
+    check(
+        "// a\nx; // b\n// c\ny\n// d\n",
+        "// a\nx; // b\n// c\ny\n// d\n",
+    );
+    check("do x // a\n // b\n end", "do\n    x // a\n    // b\nend\n");
+}
+
+#[test]
+fn comments_in_expressions() {
+    check("(1 // c\n+ 2)", "1 // c\n    + 2\n");
+    check("let x = (1 // c\n) in x", "let x = 1 in\n// c\nx\n");
+    check(
+        "let x = 1 in // a\n// b\nx // c\n// d\n",
+        "let x = 1 in // a\n// b\nx // c\n// d\n",
+    );
+    check(
+        "let x=1+2 in // trailing\ndo a; b end",
+        "let x = 1 + 2 in // trailing\ndo a; b end\n",
+    );
+    check(
+        "case x do 0 -> a, // a\n// b\n_ -> b // c\n// d\nend",
+        "case x do\n    0 -> a, // a\n    // b\n    _ -> b // c\n    // d\nend\n",
+    );
+    check(
+        "case x do _ -> 1 // c\nend; y",
+        "case x do\n    _ -> 1 // c\nend;\ny\n",
+    );
+}
+
+/// Check that a comment can be put between any two tokens without
+/// formatting crashing, changing the meaning of the code, losing the
+/// comment, or becoming non-idempotent.
+#[test]
+fn comments_anywhere() {
+    let src = "@allow(unused_mut) let mut x: (uint, [bool; 2]) = (1 + -2 * 3, y) in \
+               do x = (4, z); case x do (0, _) -> (), (n, mut b) -> n end; x end; w";
+    for (idx, _) in src.match_indices(' ') {
+        let commented = format!("{}// c\n{}", &src[..idx], &src[idx + 1..]);
+        let formatted = format(&commented, 80);
+        assert_same_ast(&commented, &formatted);
+        assert_eq!(
+            formatted.matches("// c").count(),
+            1,
+            "formatting {commented:?} lost the comment:\n{formatted}"
+        );
+        assert_eq!(
+            format(&formatted, 80),
+            formatted,
+            "formatting is not idempotent for {commented:?}"
+        );
+    }
+}
+
+#[test]
+fn blank_lines() {
+    check("x;\n\n\n\ny;\nz", "x;\n\ny;\nz\n");
+    check("x;\n// a\n\n\ny", "x;\n// a\n\ny\n");
+    check("x;\n// a\ny", "x;\n// a\ny\n");
+}
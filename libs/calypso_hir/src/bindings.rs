@@ -0,0 +1,250 @@
+//! Lints about bindings.
+//!
+//! - [`unused_variables`](lint::UNUSED_VARIABLES): a binding that is never
+//!   used, i.e. never read or assigned to. Bindings whose names start with
+//!   an underscore are not linted, so prefixing the name with `_` is
+//!   suggested. The names of function items are not linted either, as
+//!   they may be called from outside of the program, e.g. `main`.
+//! - [`shadowed_bindings`](lint::SHADOWED_BINDINGS): a `let` that binds a
+//!   name already bound by an enclosing `let` of the same scope. The `let`s
+//!   of a scope are a chain of `let`s, each of which is the body of the
+//!   previous one, e.g. `let x = 1 in let y = 2 in let x = 3 in x`. Other
+//!   expressions, such as blocks and `case` arms, start a new scope.
+//!   Rebinding a name to a value that uses the earlier binding, e.g.
+//!   `let x = x + 1 in ...`, is not linted, as that is usually intentional.
+
+use std::collections::{HashMap, HashSet};
+
+use calypso_ast::node::NodeId;
+use calypso_base::{span::Span, symbol::Symbol};
+use calypso_diagnostic::{
+    diagnostic::{Applicability, Edit},
+    prelude::*,
+    report::GlobalReportingCtxt,
+};
+
+use crate::{
+    expr::{Expr, ExprKind},
+    pat::{Pat, PatKind},
+    resolve::{Binding, BindingKind, Resolutions},
+    visit::{walk_expr, walk_pat, Visitor},
+};
+
+/// Check for unused and shadowed bindings, reporting them with the
+/// [`unused_variables`](lint::UNUSED_VARIABLES) and
+/// [`shadowed_bindings`](lint::SHADOWED_BINDINGS) lints.
+pub fn check_bindings(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Expr],
+    res: &Resolutions,
+) {
+    let mut vars = VarCollector::default();
+    for expr in exprs {
+        vars.visit_expr(expr);
+    }
+
+    let mut uses = HashMap::<NodeId, Vec<Span>>::new();
+    for (use_id, def) in res.uses() {
+        if let Some(&span) = vars.spans.get(&use_id) {
+            uses.entry(def).or_default().push(span);
+        }
+    }
+    for spans in uses.values_mut() {
+        spans.sort();
+    }
+
+    let mut taken = vars.names;
+    taken.extend(res.bindings().map(|(_, binding)| binding.name));
+
+    let mut checker = BindingChecker {
+        grcx,
+        file_id,
+        res,
+        uses,
+        taken,
+    };
+    checker.report_unused();
+    for expr in exprs {
+        checker.visit_expr(expr);
+    }
+}
+
+/// Collects the spans of variable uses, and the names that are used.
+#[derive(Default)]
+struct VarCollector {
+    spans: HashMap<NodeId, Span>,
+    names: HashSet<Symbol>,
+}
+
+impl Visitor for VarCollector {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Var(name) = expr.kind {
+            self.spans.insert(expr.id, expr.span);
+            self.names.insert(name);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// Collects the node IDs of the binding patterns in a pattern.
+#[derive(Default)]
+struct BindingCollector(Vec<NodeId>);
+
+impl Visitor for BindingCollector {
+    fn visit_pat(&mut self, pat: &Pat) {
+        if let PatKind::Binding(..) = pat.kind {
+            self.0.push(pat.id);
+        }
+        walk_pat(self, pat);
+    }
+}
+
+struct BindingChecker<'grcx, 'res> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    res: &'res Resolutions,
+    /// Map from the node IDs of binding patterns to the spans of the uses of
+    /// the binding, in source order
+    uses: HashMap<NodeId, Vec<Span>>,
+    /// All names that are bound or used, which cannot be suggested as new
+    /// names
+    taken: HashSet<Symbol>,
+}
+
+/// The span of the name of a binding, without any `mut`.
+fn name_span(binding: &Binding) -> Span {
+    let len = u32::try_from(binding.name.as_str().len())
+        .expect("source files are at most u32::MAX bytes");
+    binding.span.with_lo(binding.span.hi().saturating_sub(len))
+}
+
+/// Returns `true` if a binding is intentionally unused, or should not be
+/// linted for some other reason.
+fn is_ignored(binding: &Binding) -> bool {
+    binding.name.as_str().starts_with('_') || binding.kind == BindingKind::Fn
+}
+
+impl BindingChecker<'_, '_> {
+    fn report_unused(&mut self) {
+        let mut unused = self
+            .res
+            .bindings()
+            .filter(|(id, binding)| !self.uses.contains_key(id) && !is_ignored(binding))
+            .map(|(_, binding)| *binding)
+            .collect::<Vec<_>>();
+        unused.sort_by_key(|binding| binding.span);
+
+        for binding in unused {
+            self.grcx
+                .report_lint(&lint::UNUSED_VARIABLES, self.file_id, binding.span, |b| {
+                    b.short(format!("unused variable: `{}`", binding.name))
+                        .label(
+                            LabelStyle::Primary,
+                            Some("this variable is never used"),
+                            self.file_id,
+                            binding.span,
+                        )
+                        .suggestion(
+                            self.file_id,
+                            name_span(&binding),
+                            format!("_{}", binding.name),
+                            Applicability::MachineApplicable,
+                            "if this is intentional, prefix it with an underscore",
+                        )
+                });
+        }
+    }
+
+    /// Check a `let`, and the `let`s that are chained after it. `chain`
+    /// contains the bindings made by the enclosing `let`s in the same
+    /// scope.
+    fn check_let(&mut self, expr: &Expr, chain: &mut Vec<NodeId>) {
+        let ExprKind::Let(pat, ty, value, body) = &expr.kind else {
+            self.visit_expr(expr);
+            return;
+        };
+        if let Some(ty) = ty {
+            self.visit_ty(ty);
+        }
+        self.visit_expr(value);
+
+        let mut bound = BindingCollector::default();
+        bound.visit_pat(pat);
+        for &id in &bound.0 {
+            let Some(binding) = self.res.binding(id) else {
+                continue;
+            };
+            let prev = chain.iter().rev().find_map(|&prev| {
+                let prev_binding = self.res.binding(prev)?;
+                (prev_binding.name == binding.name).then_some((prev, prev_binding))
+            });
+            if let Some((prev, prev_binding)) = prev {
+                let used_in_value = self.uses.get(&prev).map_or(false, |uses| {
+                    uses.iter().any(|&span| value.span.contains(span))
+                });
+                if !used_in_value && !is_ignored(binding) {
+                    self.report_shadowed(id, *binding, prev_binding.span);
+                }
+            }
+        }
+
+        let len = chain.len();
+        chain.extend(bound.0);
+        self.check_let(body, chain);
+        chain.truncate(len);
+    }
+
+    fn report_shadowed(&mut self, id: NodeId, binding: Binding, prev_span: Span) {
+        let new_name = (2..=usize::MAX)
+            .map(|n| Symbol::intern(&format!("{}_{n}", binding.name)))
+            .find(|name| !self.taken.contains(name))
+            .expect("there is an unused name");
+        self.taken.insert(new_name);
+
+        let edits = std::iter::once(name_span(&binding))
+            .chain(self.uses.get(&id).into_iter().flatten().copied())
+            .map(|span| Edit {
+                file_id: self.file_id,
+                span,
+                replacement: new_name.to_string(),
+            })
+            .collect();
+
+        let first_bound = format!("`{}` is first bound here", binding.name);
+        self.grcx
+            .report_lint(&lint::SHADOWED_BINDINGS, self.file_id, binding.span, |b| {
+                b.short(format!(
+                    "`{}` shadows a binding in the same scope",
+                    binding.name
+                ))
+                .label(
+                    LabelStyle::Primary,
+                    Some("this binding shadows an earlier one"),
+                    self.file_id,
+                    binding.span,
+                )
+                .label(
+                    LabelStyle::Secondary,
+                    Some(&first_bound),
+                    self.file_id,
+                    prev_span,
+                )
+                .multipart_suggestion(
+                    edits,
+                    Applicability::MachineApplicable,
+                    format!("rename the new binding to `{new_name}`"),
+                )
+            });
+    }
+}
+
+impl Visitor for BindingChecker<'_, '_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Let(..) = expr.kind {
+            self.check_let(expr, &mut Vec::new());
+        } else {
+            walk_expr(self, expr);
+        }
+    }
+}
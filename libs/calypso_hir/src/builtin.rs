@@ -0,0 +1,87 @@
+//! Built-in values.
+//!
+//! Built-ins are in scope everywhere, unless they are shadowed by a binding
+//! of the same name. Currently these are the constructors of the built-in
+//! `Option[T]` and `Result[T, E]` types, and the functions that read the
+//! program arguments passed to `fn main(args: Args)`.
+
+use std::fmt::{self, Display};
+
+use calypso_base::symbol::Symbol;
+
+/// A built-in value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Builtin {
+    /// `Some(value)`, a present `Option`
+    Some,
+    /// `None`, an absent `Option`
+    None,
+    /// `Ok(value)`, a successful `Result`
+    Ok,
+    /// `Err(error)`, a failed `Result`
+    Err,
+    /// `arg_count(args)`, the number of program arguments
+    ArgCount,
+    /// `arg_uint(args, idx)`, the program argument at `idx` parsed as a
+    /// `uint`, or `None` if there is no such argument or it is not a `uint`
+    ArgUint,
+}
+
+impl Builtin {
+    /// All built-ins.
+    pub const ALL: [Self; 6] = [
+        Self::Some,
+        Self::None,
+        Self::Ok,
+        Self::Err,
+        Self::ArgCount,
+        Self::ArgUint,
+    ];
+
+    /// Get the built-in with the given name, if there is one.
+    #[must_use]
+    pub fn from_name(name: Symbol) -> Option<Self> {
+        Self::ALL
+            .into_iter()
+            .find(|builtin| builtin.name() == name.as_str())
+    }
+
+    /// The name of the built-in.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Some => "Some",
+            Self::None => "None",
+            Self::Ok => "Ok",
+            Self::Err => "Err",
+            Self::ArgCount => "arg_count",
+            Self::ArgUint => "arg_uint",
+        }
+    }
+
+    /// The number of fields of the built-in, if it is a constructor, and 0
+    /// otherwise.
+    #[must_use]
+    pub fn fields(self) -> usize {
+        match self {
+            Self::Some | Self::Ok | Self::Err => 1,
+            Self::None | Self::ArgCount | Self::ArgUint => 0,
+        }
+    }
+
+    /// Returns `true` if the built-in is a constructor, which can also be
+    /// used in patterns.
+    #[must_use]
+    pub fn is_ctor(self) -> bool {
+        match self {
+            Self::Some | Self::None | Self::Ok | Self::Err => true,
+            Self::ArgCount | Self::ArgUint => false,
+        }
+    }
+}
+
+impl Display for Builtin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
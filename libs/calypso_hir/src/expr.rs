@@ -0,0 +1,114 @@
+use calypso_ast::{
+    expr::{BinOpKind, Numeral, UnOpKind},
+    node::NodeId,
+};
+use calypso_base::{span::Span, symbol::Symbol};
+
+use crate::{pat::Pat, ty::Ty};
+
+/// An expression.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Expr {
+    /// The ID of the AST node this was lowered from, or a fresh ID if this
+    /// was introduced by desugaring
+    pub id: NodeId,
+    /// The span of the source this was lowered from
+    pub span: Span,
+    /// The kind of expression
+    pub kind: ExprKind,
+}
+
+impl Expr {
+    /// Create a new expression.
+    #[must_use]
+    pub fn new(id: NodeId, span: Span, kind: ExprKind) -> Self {
+        Self { id, span, kind }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ExprKind {
+    /// A literal value
+    Lit(Lit),
+    /// A use of a variable
+    Var(Symbol),
+    /// A built-in operation. This is only ever the callee of a
+    /// [`ExprKind::Call`].
+    Prim(Prim),
+    /// `let <pat> (: <ty>)? = <value> in <body>`
+    Let(Pat, Option<Ty>, Box<Expr>, Box<Expr>),
+    /// `<target> = <value>`, which evaluates to the assigned value. The
+    /// target is currently always a [`ExprKind::Var`].
+    Assign(Box<Expr>, Box<Expr>),
+    /// An anonymous function
+    Lambda(Vec<Param>, Box<Expr>),
+    /// A function call
+    Call(Box<Expr>, Vec<Expr>),
+    /// A tuple. The empty tuple is the unit value.
+    Tuple(Vec<Expr>),
+    /// Pattern matching. The arms are tried in order.
+    Match(Box<Expr>, Vec<Arm>),
+    /// An infinite loop, exited using [`ExprKind::Break`]
+    Loop(Box<Expr>),
+    /// Exit the innermost loop, with an optional value
+    Break(Option<Box<Expr>>),
+    /// A sequence of expressions, evaluating to the value of the last one
+    Block(Vec<Expr>),
+    /// A function item, which evaluates to the unit value. Its name is in
+    /// scope in the whole sequence of expressions it is in, so functions in
+    /// the same sequence can be mutually recursive.
+    Fn(Box<FnDef>),
+}
+
+/// A literal value.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Lit {
+    Bool(bool),
+    /// A number literal, along with its source text. The text is kept rather
+    /// than the value, so that later passes can report invalid or
+    /// out-of-range numbers with the type they are used at.
+    Number(Numeral, Symbol),
+}
+
+/// A built-in operation.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Prim {
+    BinOp(BinOpKind),
+    UnOp(UnOpKind),
+}
+
+/// A function parameter.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Param {
+    pub pat: Pat,
+    pub ty: Option<Ty>,
+}
+
+/// The definition of a function item.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct FnDef {
+    /// The name of the function, as a binding pattern
+    pub name: Pat,
+    /// The type parameters from the `with` clause, which are only in scope
+    /// in the signature and body of the function
+    pub ty_params: Vec<TyParam>,
+    pub params: Vec<Param>,
+    /// The return type, if it was annotated
+    pub ret: Option<Ty>,
+    pub body: Expr,
+}
+
+/// A type parameter of a function item.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TyParam {
+    pub span: Span,
+    pub name: Symbol,
+}
+
+/// An arm of a [`ExprKind::Match`].
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Arm {
+    pub span: Span,
+    pub pat: Pat,
+    pub body: Expr,
+}
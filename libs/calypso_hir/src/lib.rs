@@ -0,0 +1,27 @@
+//! The high-level intermediate representation (HIR) of Calypso.
+//!
+//! The HIR is a small core language that the surface syntax is desugared
+//! into, so that later passes do not need to handle every piece of syntactic
+//! sugar. See [`lower`] for how the AST is lowered into the HIR.
+//!
+//! Unlike the AST, the HIR owns its nodes. Every node keeps the [`NodeId`]
+//! and span of the AST node it was lowered from, so that later passes can
+//! report diagnostics on the original source. Nodes that are introduced by
+//! desugaring are given fresh node IDs, and the span of the syntax they were
+//! desugared from.
+//!
+//! [`NodeId`]: calypso_ast::node::NodeId
+#![doc(html_root_url = "https://calypso-lang.github.io/rustdoc/calypso_hir/index.html")]
+#![warn(clippy::pedantic)]
+
+pub mod bindings;
+pub mod builtin;
+pub mod expr;
+pub mod lower;
+pub mod mutability;
+pub mod pat;
+pub mod pretty;
+pub mod reachability;
+pub mod resolve;
+pub mod ty;
+pub mod visit;
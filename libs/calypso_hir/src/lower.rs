@@ -0,0 +1,275 @@
+//! Lowering of the AST into the HIR.
+//!
+//! Most of the AST maps directly onto the HIR. The exceptions are:
+//!
+//! - Operators are lowered into calls of [`Prim`]s, e.g. `a + b` becomes
+//!   `(call + a b)`.
+//! - The short-circuiting operators `&&` and `||` are lowered into matches,
+//!   e.g. `a && b` becomes `(match a (false false) (_ b))`.
+//! - The name bound by a `let` is lowered into a binding pattern.
+//! - `case` expressions are lowered into matches.
+//! - Attributes are removed, leaving the expression they apply to. Lint
+//!   attributes must be collected from the AST before lowering (see
+//!   [`calypso_ast::attr`]).
+//! - The names of function items and their parameters are lowered into
+//!   binding patterns.
+//! - A pattern that is a bare name of a built-in constructor, e.g. `None`,
+//!   is lowered into a constructor pattern rather than a binding. Other
+//!   bindings can still shadow the constructor outside of patterns.
+
+use calypso_ast::{
+    expr::{self as ast, BinOpKind, Mutability},
+    node::NodeIdGen,
+    pat as ast_pat, ty as ast_ty,
+};
+use calypso_base::{
+    span::{Span, Spanned},
+    symbol::Symbol,
+};
+
+use crate::{
+    builtin::Builtin,
+    expr::{Arm, Expr, ExprKind, FnDef, Lit, Param, Prim, TyParam},
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+
+/// Lower a sequence of expressions, parsed from `src`, into the HIR. `ids`
+/// is used to create node IDs for nodes introduced by desugaring, so it must
+/// be the same generator that was used to create the AST.
+///
+/// The AST is expected to be valid (see [`calypso_ast::validate`]).
+#[must_use]
+pub fn lower(src: &str, ids: &NodeIdGen, exprs: &[Spanned<ast::Expr>]) -> Vec<Expr> {
+    let lcx = LoweringCtxt { src, ids };
+    exprs
+        .iter()
+        .map(|expr| lcx.lower_expr(expr.as_ref()))
+        .collect()
+}
+
+struct LoweringCtxt<'src, 'ids> {
+    src: &'src str,
+    ids: &'ids NodeIdGen,
+}
+
+impl LoweringCtxt<'_, '_> {
+    fn lower_expr(&self, expr: Spanned<&ast::Expr>) -> Expr {
+        let span = expr.span();
+        let kind = match &expr.value().kind {
+            ast::ExprKind::BinOp(left, op, right) => {
+                let left = self.lower_expr(left.as_ref().map(|x| &**x));
+                let right = self.lower_expr(right.as_ref().map(|x| &**x));
+                match *op.value() {
+                    BinOpKind::LogicalAnd => self.lower_short_circuit(op.span(), left, true, right),
+                    BinOpKind::LogicalOr => self.lower_short_circuit(op.span(), left, false, right),
+                    kind => ExprKind::Call(
+                        Box::new(self.fresh_expr(op.span(), ExprKind::Prim(Prim::BinOp(kind)))),
+                        vec![left, right],
+                    ),
+                }
+            }
+            ast::ExprKind::UnOp(op, operand) => ExprKind::Call(
+                Box::new(self.fresh_expr(op.span(), ExprKind::Prim(Prim::UnOp(*op.value())))),
+                vec![self.lower_expr(operand.as_ref().map(|x| &**x))],
+            ),
+            ast::ExprKind::Primary(primary) => match *primary.value() {
+                ast::Primary::Number(numeral) => {
+                    ExprKind::Lit(self.lower_numeral(primary.map(|_| numeral)))
+                }
+                ast::Primary::Bool(b) => ExprKind::Lit(Lit::Bool(b)),
+                ast::Primary::Symbol(sym) => ExprKind::Var(sym),
+            },
+            ast::ExprKind::Block(exprs) => ExprKind::Block(
+                exprs
+                    .iter()
+                    .map(|expr| self.lower_expr(expr.as_ref()))
+                    .collect(),
+            ),
+            ast::ExprKind::Let(mutability, name, ty, value, body) => ExprKind::Let(
+                self.binding(*mutability, *name),
+                ty.as_ref().map(|ty| self.lower_ty(ty.as_ref())),
+                Box::new(self.lower_expr(value.as_ref().map(|x| &**x))),
+                Box::new(self.lower_expr(body.as_ref().map(|x| &**x))),
+            ),
+            ast::ExprKind::Assign(name, value) => ExprKind::Assign(
+                Box::new(self.fresh_expr(name.span(), ExprKind::Var(name.value_owned()))),
+                Box::new(self.lower_expr(value.as_ref().map(|x| &**x))),
+            ),
+            ast::ExprKind::Tuple(exprs) => ExprKind::Tuple(
+                exprs
+                    .iter()
+                    .map(|expr| self.lower_expr(expr.as_ref()))
+                    .collect(),
+            ),
+            ast::ExprKind::Case(scrutinee, arms) => ExprKind::Match(
+                Box::new(self.lower_expr(scrutinee.as_ref().map(|x| &**x))),
+                arms.iter()
+                    .map(|arm| Arm {
+                        span: arm.span(),
+                        pat: self.lower_pat(arm.value().pat.as_ref()),
+                        body: self.lower_expr(arm.value().body.as_ref()),
+                    })
+                    .collect(),
+            ),
+            ast::ExprKind::Attributed(_, expr) => {
+                return self.lower_expr(expr.as_ref().map(|x| &**x));
+            }
+            ast::ExprKind::Call(callee, args) => ExprKind::Call(
+                Box::new(self.lower_expr(callee.as_ref().map(|x| &**x))),
+                args.iter()
+                    .map(|arg| self.lower_expr(arg.as_ref()))
+                    .collect(),
+            ),
+            ast::ExprKind::Fn(ty_params, name, params, ret, body) => {
+                ExprKind::Fn(Box::new(self.lower_fn(
+                    ty_params,
+                    *name,
+                    params,
+                    ret.as_ref(),
+                    body.as_ref().map(|x| &**x),
+                )))
+            }
+            ast::ExprKind::Loop(body) => {
+                ExprKind::Loop(Box::new(self.lower_expr(body.as_ref().map(|x| &**x))))
+            }
+            ast::ExprKind::Break(value) => ExprKind::Break(
+                value
+                    .as_ref()
+                    .map(|value| Box::new(self.lower_expr(value.as_ref().map(|x| &**x)))),
+            ),
+        };
+        Expr::new(expr.value().id, span, kind)
+    }
+
+    fn lower_fn(
+        &self,
+        ty_params: &[Spanned<Symbol>],
+        name: Spanned<Symbol>,
+        params: &[Spanned<ast::Param>],
+        ret: Option<&Spanned<ast_ty::Ty>>,
+        body: Spanned<&ast::Expr>,
+    ) -> FnDef {
+        FnDef {
+            name: self.binding(Mutability::Immut, name),
+            ty_params: ty_params
+                .iter()
+                .map(|ty_param| TyParam {
+                    span: ty_param.span(),
+                    name: ty_param.value_owned(),
+                })
+                .collect(),
+            params: params
+                .iter()
+                .map(|param| Param {
+                    pat: self.binding(param.value().mutability, param.value().name),
+                    ty: param
+                        .value()
+                        .ty
+                        .as_ref()
+                        .map(|ty| self.lower_ty(ty.as_ref())),
+                })
+                .collect(),
+            ret: ret.map(|ret| self.lower_ty(ret.as_ref())),
+            body: self.lower_expr(body),
+        }
+    }
+
+    /// Lower `left && right` or `left || right` into a match on `left`,
+    /// which evaluates to `!value` if `left` is `!value`, and otherwise
+    /// evaluates `right`.
+    ///
+    /// The arm for `right` comes last so that type errors are reported on
+    /// `right`, rather than on the desugared literal.
+    fn lower_short_circuit(&self, op_span: Span, left: Expr, value: bool, right: Expr) -> ExprKind {
+        let right_span = right.span;
+        ExprKind::Match(
+            Box::new(left),
+            vec![
+                Arm {
+                    span: op_span,
+                    pat: Pat::new(self.ids.next_id(), op_span, PatKind::Lit(Lit::Bool(!value))),
+                    body: self.fresh_expr(op_span, ExprKind::Lit(Lit::Bool(!value))),
+                },
+                Arm {
+                    span: right_span,
+                    pat: Pat::new(self.ids.next_id(), op_span, PatKind::Wild),
+                    body: right,
+                },
+            ],
+        )
+    }
+
+    /// Create a binding pattern for a name that is bound outside of a
+    /// pattern, e.g. by a `let`.
+    fn binding(&self, mutability: Mutability, name: Spanned<Symbol>) -> Pat {
+        Pat::new(
+            self.ids.next_id(),
+            name.span(),
+            PatKind::Binding(mutability, name.value_owned()),
+        )
+    }
+
+    fn lower_numeral(&self, numeral: Spanned<ast::Numeral>) -> Lit {
+        let text = &self.src[numeral.span().into_range()];
+        Lit::Number(numeral.value_owned(), Symbol::intern(text))
+    }
+
+    fn lower_pat(&self, pat: Spanned<&ast_pat::Pat>) -> Pat {
+        let kind = match &pat.value().kind {
+            ast_pat::PatKind::Wild => PatKind::Wild,
+            ast_pat::PatKind::Binding(Mutability::Immut, name)
+                if Builtin::from_name(name.value_owned()).map_or(false, Builtin::is_ctor) =>
+            {
+                PatKind::Ctor(name.value_owned(), Vec::new())
+            }
+            ast_pat::PatKind::Binding(mutability, name) => {
+                PatKind::Binding(*mutability, name.value_owned())
+            }
+            ast_pat::PatKind::Bool(b) => PatKind::Lit(Lit::Bool(*b)),
+            ast_pat::PatKind::Number(numeral) => PatKind::Lit(self.lower_numeral(*numeral)),
+            ast_pat::PatKind::Neg(numeral) => PatKind::Neg(self.lower_numeral(*numeral)),
+            ast_pat::PatKind::Tuple(pats) => PatKind::Tuple(
+                pats.iter()
+                    .map(|pat| self.lower_pat(pat.as_ref()))
+                    .collect(),
+            ),
+            ast_pat::PatKind::Ctor(name, pats) => PatKind::Ctor(
+                name.value_owned(),
+                pats.iter()
+                    .map(|pat| self.lower_pat(pat.as_ref()))
+                    .collect(),
+            ),
+        };
+        Pat::new(pat.value().id, pat.span(), kind)
+    }
+
+    fn lower_ty(&self, ty: Spanned<&ast_ty::Ty>) -> Ty {
+        let kind = match &ty.value().kind {
+            ast_ty::TyKind::Symbol(sym) => TyKind::Named(sym.value_owned(), Vec::new()),
+            ast_ty::TyKind::Apply(sym, args) => TyKind::Named(
+                sym.value_owned(),
+                args.iter().map(|arg| self.lower_ty(arg.as_ref())).collect(),
+            ),
+            ast_ty::TyKind::IndefArray(elem) => {
+                TyKind::Array(Box::new(self.lower_ty(elem.as_ref().map(|x| &**x))), None)
+            }
+            ast_ty::TyKind::DefArray(elem, len) => TyKind::Array(
+                Box::new(self.lower_ty(elem.as_ref().map(|x| &**x))),
+                Some(Box::new(self.fresh_expr(
+                    len.span(),
+                    ExprKind::Lit(self.lower_numeral(*len)),
+                ))),
+            ),
+            ast_ty::TyKind::Tuple(tys) => {
+                TyKind::Tuple(tys.iter().map(|ty| self.lower_ty(ty.as_ref())).collect())
+            }
+        };
+        Ty::new(ty.value().id, ty.span(), kind)
+    }
+
+    /// Create an expression introduced by desugaring.
+    fn fresh_expr(&self, span: Span, kind: ExprKind) -> Expr {
+        Expr::new(self.ids.next_id(), span, kind)
+    }
+}
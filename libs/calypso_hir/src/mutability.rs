@@ -0,0 +1,130 @@
+//! Mutability checking.
+//!
+//! Only bindings made with `let mut` can be assigned to. Assigning to an
+//! immutable binding is an error, and a `mut` binding that is never assigned
+//! to is warned about.
+//!
+//! Assignment is currently the only way to modify a variable. Mutating
+//! method calls should be checked here once the language has methods.
+
+use std::collections::HashSet;
+
+use calypso_ast::{expr::Mutability, node::NodeId};
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+
+use crate::{
+    expr::{Expr, ExprKind},
+    resolve::Resolutions,
+    visit::{walk_expr, Visitor},
+};
+
+/// Check that only mutable bindings are assigned to, reporting errors to
+/// `grcx` as synchronizable errors, and unused `mut`s with the
+/// [`unused_mut`](lint::UNUSED_MUT) lint.
+pub fn check_mutability(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Expr],
+    res: &Resolutions,
+) {
+    let mut checker = MutabilityChecker {
+        grcx,
+        file_id,
+        res,
+        assigned: HashSet::new(),
+    };
+    for expr in exprs {
+        checker.visit_expr(expr);
+    }
+    checker.report_unused_mut();
+}
+
+struct MutabilityChecker<'grcx, 'res> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    res: &'res Resolutions,
+    /// The bindings that are assigned to
+    assigned: HashSet<NodeId>,
+}
+
+impl MutabilityChecker<'_, '_> {
+    fn check_assign(&mut self, expr: &Expr, target: &Expr) {
+        // Unresolved names have already been reported.
+        let Some((def, binding)) = self.res.binding_of(target.id) else {
+            return;
+        };
+        self.assigned.insert(def);
+        if binding.mutability == Mutability::Mut {
+            return;
+        }
+
+        let bound_here = format!("`{}` is bound immutably here", binding.name);
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code("E0007")
+                        .short(err!(E0007, binding.name))
+                        .label(
+                            LabelStyle::Primary,
+                            Some("cannot assign to an immutable variable"),
+                            self.file_id,
+                            expr.span,
+                        )
+                        .label(
+                            LabelStyle::Secondary,
+                            Some(&bound_here),
+                            self.file_id,
+                            binding.span,
+                        )
+                })
+                .help(|b| {
+                    b.short(format!(
+                        "consider making this binding mutable: `mut {}`",
+                        binding.name
+                    ))
+                    .label(
+                        LabelStyle::Primary,
+                        None,
+                        self.file_id,
+                        binding.span,
+                    )
+                })
+                .build(),
+        );
+    }
+
+    fn report_unused_mut(&mut self) {
+        let mut unused = self
+            .res
+            .bindings()
+            .filter(|(id, binding)| {
+                binding.mutability == Mutability::Mut && !self.assigned.contains(id)
+            })
+            .map(|(_, binding)| *binding)
+            .collect::<Vec<_>>();
+        unused.sort_by_key(|binding| binding.span);
+
+        for binding in unused {
+            self.grcx
+                .report_lint(&lint::UNUSED_MUT, self.file_id, binding.span, |b| {
+                    b.short("variable does not need to be mutable")
+                        .label(
+                            LabelStyle::Primary,
+                            Some("this variable is never assigned to"),
+                            self.file_id,
+                            binding.span,
+                        )
+                        .note(format!("remove the `mut` from `mut {}`", binding.name))
+                });
+        }
+    }
+}
+
+impl Visitor for MutabilityChecker<'_, '_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Assign(target, _) = &expr.kind {
+            self.check_assign(expr, target);
+        }
+        walk_expr(self, expr);
+    }
+}
@@ -0,0 +1,43 @@
+use calypso_ast::{expr::Mutability, node::NodeId};
+use calypso_base::{span::Span, symbol::Symbol};
+
+use crate::expr::Lit;
+
+/// A pattern.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Pat {
+    /// The ID of the AST node this was lowered from, or a fresh ID if this
+    /// was introduced by desugaring
+    pub id: NodeId,
+    /// The span of the source this was lowered from
+    pub span: Span,
+    /// The kind of pattern
+    pub kind: PatKind,
+}
+
+impl Pat {
+    /// Create a new pattern.
+    #[must_use]
+    pub fn new(id: NodeId, span: Span, kind: PatKind) -> Self {
+        Self { id, span, kind }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum PatKind {
+    /// `_`, which matches anything
+    Wild,
+    /// A binding, which matches anything and binds it to a variable
+    Binding(Mutability, Symbol),
+    /// A literal, which matches values equal to it
+    Lit(Lit),
+    /// A negated number literal, which matches values equal to its negation.
+    /// The literal is always a [`Lit::Number`].
+    Neg(Lit),
+    /// A tuple of patterns
+    Tuple(Vec<Pat>),
+    /// A constructor of a built-in type, e.g. `Some(x)` or `None`, which
+    /// matches values made with that constructor whose fields match the
+    /// field patterns
+    Ctor(Symbol, Vec<Pat>),
+}
@@ -0,0 +1,156 @@
+//! Printing of the HIR as S-expressions, e.g.
+//! `(let (mut x) (ty (array uint 4)) (call + 1 2) in (block x))`.
+
+use std::fmt::{self, Display};
+
+use calypso_ast::expr::Mutability;
+
+use crate::{
+    expr::{Expr, ExprKind, Lit, Param, Prim},
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+
+/// Write `items`, each preceded by a space.
+fn write_all<T: Display>(f: &mut fmt::Formatter<'_>, items: &[T]) -> fmt::Result {
+    items.iter().try_for_each(|item| write!(f, " {item}"))
+}
+
+/// Write a parenthesized list of function parameters.
+fn write_params(f: &mut fmt::Formatter<'_>, params: &[Param]) -> fmt::Result {
+    write!(f, "(")?;
+    for (idx, param) in params.iter().enumerate() {
+        if idx != 0 {
+            write!(f, " ")?;
+        }
+        match &param.ty {
+            Some(ty) => write!(f, "({} (ty {ty}))", param.pat)?,
+            None => write!(f, "{}", param.pat)?,
+        }
+    }
+    write!(f, ")")
+}
+
+impl Display for Expr {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            ExprKind::Lit(lit) => write!(f, "{lit}"),
+            ExprKind::Var(sym) => write!(f, "{sym}"),
+            ExprKind::Prim(prim) => write!(f, "{prim}"),
+            ExprKind::Let(pat, ty, value, body) => {
+                write!(f, "(let {pat} ")?;
+                if let Some(ty) = ty {
+                    write!(f, "(ty {ty}) ")?;
+                }
+                write!(f, "{value} in {body})")
+            }
+            ExprKind::Assign(target, value) => write!(f, "(assign {target} {value})"),
+            ExprKind::Lambda(params, body) => {
+                write!(f, "(lambda ")?;
+                write_params(f, params)?;
+                write!(f, " {body})")
+            }
+            ExprKind::Fn(def) => {
+                write!(f, "(fn {} ", def.name)?;
+                if !def.ty_params.is_empty() {
+                    write!(f, "(with")?;
+                    for ty_param in &def.ty_params {
+                        write!(f, " {}", ty_param.name)?;
+                    }
+                    write!(f, ") ")?;
+                }
+                write_params(f, &def.params)?;
+                if let Some(ret) = &def.ret {
+                    write!(f, " (ty {ret})")?;
+                }
+                write!(f, " {})", def.body)
+            }
+            ExprKind::Call(callee, args) => {
+                write!(f, "(call {callee}")?;
+                write_all(f, args)?;
+                write!(f, ")")
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                write!(f, "(match {scrutinee}")?;
+                for arm in arms {
+                    write!(f, " ({} {})", arm.pat, arm.body)?;
+                }
+                write!(f, ")")
+            }
+            ExprKind::Tuple(exprs) => {
+                write!(f, "(tuple")?;
+                write_all(f, exprs)?;
+                write!(f, ")")
+            }
+            ExprKind::Loop(body) => write!(f, "(loop {body})"),
+            ExprKind::Break(None) => write!(f, "(break)"),
+            ExprKind::Break(Some(value)) => write!(f, "(break {value})"),
+            ExprKind::Block(exprs) => {
+                write!(f, "(block")?;
+                write_all(f, exprs)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl Display for Lit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Lit::Bool(b) => write!(f, "{b}"),
+            Lit::Number(_, text) => write!(f, "{text}"),
+        }
+    }
+}
+
+impl Display for Prim {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Prim::BinOp(op) => write!(f, "{op}"),
+            Prim::UnOp(op) => write!(f, "{op}"),
+        }
+    }
+}
+
+impl Display for Pat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            PatKind::Wild => write!(f, "_"),
+            PatKind::Binding(Mutability::Immut, name) => write!(f, "{name}"),
+            PatKind::Binding(Mutability::Mut, name) => write!(f, "(mut {name})"),
+            PatKind::Lit(lit) => write!(f, "{lit}"),
+            PatKind::Neg(lit) => write!(f, "(- {lit})"),
+            PatKind::Tuple(pats) => {
+                write!(f, "(tuple")?;
+                write_all(f, pats)?;
+                write!(f, ")")
+            }
+            PatKind::Ctor(name, pats) if pats.is_empty() => write!(f, "{name}"),
+            PatKind::Ctor(name, pats) => {
+                write!(f, "(ctor {name}")?;
+                write_all(f, pats)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+impl Display for Ty {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match &self.kind {
+            TyKind::Named(sym, args) if args.is_empty() => write!(f, "{sym}"),
+            TyKind::Named(sym, args) => {
+                write!(f, "(apply {sym}")?;
+                write_all(f, args)?;
+                write!(f, ")")
+            }
+            TyKind::Array(elem, None) => write!(f, "(array {elem})"),
+            TyKind::Array(elem, Some(len)) => write!(f, "(array {elem} {len})"),
+            TyKind::Tuple(tys) => {
+                write!(f, "(tuple")?;
+                write_all(f, tys)?;
+                write!(f, ")")
+            }
+        }
+    }
+}
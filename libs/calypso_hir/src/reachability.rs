@@ -0,0 +1,119 @@
+//! Reachability of code.
+//!
+//! An expression *diverges* if evaluating it never finishes, i.e. it always
+//! `break`s out of a loop or loops forever. The expressions in a block after
+//! an expression that diverges can never be evaluated, and are reported with
+//! the [`unreachable_code`](lint::UNREACHABLE_CODE) lint.
+
+// TODO(lint): `ret` and `throw` also diverge, and should be handled here
+//   once the language has them.
+
+use calypso_diagnostic::{diagnostic::Applicability, prelude::*, report::GlobalReportingCtxt};
+
+use crate::{
+    expr::{Expr, ExprKind},
+    visit::{walk_expr, Visitor},
+};
+
+/// Check for unreachable code in a sequence of expressions.
+pub fn check_reachability(grcx: &mut GlobalReportingCtxt, file_id: usize, exprs: &[Expr]) {
+    ReachabilityChecker { grcx, file_id }.check_block(exprs);
+}
+
+/// Returns `true` if evaluating `expr` never finishes.
+#[must_use]
+pub fn diverges(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Lit(_)
+        | ExprKind::Var(_)
+        | ExprKind::Prim(_)
+        | ExprKind::Lambda(..)
+        | ExprKind::Fn(_) => false,
+        ExprKind::Break(_) => true,
+        ExprKind::Let(_, _, value, body) => diverges(value) || diverges(body),
+        ExprKind::Assign(target, value) => diverges(target) || diverges(value),
+        ExprKind::Call(callee, args) => diverges(callee) || args.iter().any(diverges),
+        ExprKind::Tuple(exprs) | ExprKind::Block(exprs) => exprs.iter().any(diverges),
+        ExprKind::Match(scrutinee, arms) => {
+            diverges(scrutinee) || (!arms.is_empty() && arms.iter().all(|arm| diverges(&arm.body)))
+        }
+        ExprKind::Loop(body) => !breaks(body),
+    }
+}
+
+/// Returns `true` if `expr` may `break` out of the loop that it is in.
+fn breaks(expr: &Expr) -> bool {
+    struct BreakFinder(bool);
+
+    impl Visitor for BreakFinder {
+        fn visit_expr(&mut self, expr: &Expr) {
+            match expr.kind {
+                ExprKind::Break(_) => self.0 = true,
+                // `break`s in nested loops exit those loops, and functions
+                // cannot `break` out of the loop they are defined in.
+                ExprKind::Loop(_) | ExprKind::Lambda(..) | ExprKind::Fn(_) => {}
+                _ => walk_expr(self, expr),
+            }
+        }
+    }
+
+    let mut finder = BreakFinder(false);
+    walk_expr(&mut finder, expr);
+    finder.0
+}
+
+struct ReachabilityChecker<'grcx> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+}
+
+impl ReachabilityChecker<'_> {
+    fn check_block(&mut self, exprs: &[Expr]) {
+        for (idx, expr) in exprs.iter().enumerate() {
+            self.visit_expr(expr);
+            if !diverges(expr) {
+                continue;
+            }
+
+            // Nested unreachable code is not reported separately.
+            if let Some(last) = exprs[idx + 1..].last() {
+                let unreachable = exprs[idx + 1].span.to(last.span);
+                let removed = expr.span.shrink_to_hi().to(last.span);
+                self.grcx
+                    .report_lint(&lint::UNREACHABLE_CODE, self.file_id, unreachable, |b| {
+                        b.short("unreachable expression")
+                            .label(
+                                LabelStyle::Primary,
+                                Some("unreachable expression"),
+                                self.file_id,
+                                unreachable,
+                            )
+                            .label(
+                                LabelStyle::Secondary,
+                                Some("any code following this expression is unreachable"),
+                                self.file_id,
+                                expr.span,
+                            )
+                            .suggestion(
+                                self.file_id,
+                                removed,
+                                "",
+                                Applicability::MachineApplicable,
+                                "remove the unreachable code",
+                            )
+                    });
+            }
+            return;
+        }
+    }
+}
+
+impl Visitor for ReachabilityChecker<'_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Block(exprs) = &expr.kind {
+            self.check_block(exprs);
+        } else {
+            walk_expr(self, expr);
+        }
+    }
+}
@@ -0,0 +1,393 @@
+//! Name resolution.
+//!
+//! The resolver links every use of a variable to the pattern that binds it.
+//! Bindings are lexically scoped:
+//!
+//! - `let <pat> = <value> in <body>` binds `pat` in `body` only, so `value`
+//!   may refer to a previous binding of the same name.
+//! - Function parameters are bound in the body of the function.
+//! - The name of a function item is bound in the whole sequence of
+//!   expressions (i.e. block or top level) that it is in, including the
+//!   bodies of all of the functions in that sequence.
+//! - The pattern of a match arm is bound in the body of that arm.
+//! - Bindings never escape the block they are made in.
+//!
+//! A binding shadows any binding of the same name in an enclosing scope,
+//! which becomes visible again once the scope ends. Names that are not bound
+//! anywhere may refer to [`Builtin`]s. The names in constructor patterns
+//! always refer to built-in constructors.
+
+use std::collections::HashMap;
+
+use calypso_ast::{expr::Mutability, node::NodeId};
+use calypso_base::{span::Span, symbol::Symbol};
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+
+use crate::{
+    builtin::Builtin,
+    expr::{Expr, ExprKind},
+    pat::{Pat, PatKind},
+};
+
+/// A variable binding.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct Binding {
+    pub name: Symbol,
+    pub mutability: Mutability,
+    pub kind: BindingKind,
+    /// The span of the binding pattern
+    pub span: Span,
+}
+
+/// What made a [`Binding`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum BindingKind {
+    /// A pattern, e.g. in a `let`, a function parameter, or a match arm
+    Variable,
+    /// The name of a function item
+    Fn,
+}
+
+/// The results of name resolution.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Resolutions {
+    /// Map from the node IDs of variable uses to the node IDs of the binding
+    /// patterns they refer to
+    uses: HashMap<NodeId, NodeId>,
+    /// Map from the node IDs of binding patterns to the bindings
+    bindings: HashMap<NodeId, Binding>,
+    /// Map from the node IDs of variable uses and constructor patterns to
+    /// the built-ins they refer to
+    builtins: HashMap<NodeId, Builtin>,
+}
+
+impl Resolutions {
+    /// Get the node ID of the binding that the variable use `id` refers to,
+    /// or `None` if it could not be resolved.
+    #[must_use]
+    pub fn resolve(&self, id: NodeId) -> Option<NodeId> {
+        self.uses.get(&id).copied()
+    }
+
+    /// Get the binding made by the binding pattern `id`.
+    #[must_use]
+    pub fn binding(&self, id: NodeId) -> Option<&Binding> {
+        self.bindings.get(&id)
+    }
+
+    /// Get the binding that the variable use `id` refers to.
+    #[must_use]
+    pub fn binding_of(&self, id: NodeId) -> Option<(NodeId, &Binding)> {
+        let def = self.resolve(id)?;
+        Some((def, self.binding(def)?))
+    }
+
+    /// Get the built-in that the variable use or constructor pattern `id`
+    /// refers to.
+    #[must_use]
+    pub fn builtin(&self, id: NodeId) -> Option<Builtin> {
+        self.builtins.get(&id).copied()
+    }
+
+    /// Iterate over all variable uses and the binding patterns they refer
+    /// to, in no particular order.
+    pub fn uses(&self) -> impl Iterator<Item = (NodeId, NodeId)> + '_ {
+        self.uses.iter().map(|(&use_id, &def)| (use_id, def))
+    }
+
+    /// Iterate over all bindings, in no particular order.
+    pub fn bindings(&self) -> impl Iterator<Item = (NodeId, &Binding)> + '_ {
+        self.bindings.iter().map(|(&id, binding)| (id, binding))
+    }
+}
+
+/// Resolve the names in a sequence of expressions, reporting unresolved
+/// names and duplicate bindings to `grcx` as synchronizable errors.
+pub fn resolve(grcx: &mut GlobalReportingCtxt, file_id: usize, exprs: &[Expr]) -> Resolutions {
+    let mut resolver = Resolver {
+        grcx,
+        file_id,
+        scopes: vec![Vec::new()],
+        res: Resolutions::default(),
+    };
+    resolver.resolve_seq(exprs);
+    resolver.res
+}
+
+struct Resolver<'grcx> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    /// The bindings in scope, innermost scope last. Within a scope, later
+    /// bindings shadow earlier ones.
+    scopes: Vec<Vec<(Symbol, NodeId)>>,
+    res: Resolutions,
+}
+
+impl Resolver<'_> {
+    fn resolve_expr(&mut self, expr: &Expr) {
+        match &expr.kind {
+            ExprKind::Lit(_) | ExprKind::Prim(_) => {}
+            ExprKind::Var(name) => self.resolve_var(expr.id, expr.span, *name),
+            ExprKind::Let(pat, _, value, body) => {
+                self.resolve_expr(value);
+                self.with_scope(|this| {
+                    this.bind_pats([pat], BindingKind::Variable);
+                    this.resolve_expr(body);
+                });
+            }
+            ExprKind::Assign(target, value) => {
+                self.resolve_expr(target);
+                self.resolve_expr(value);
+            }
+            ExprKind::Lambda(params, body) => self.with_scope(|this| {
+                this.bind_pats(params.iter().map(|param| &param.pat), BindingKind::Variable);
+                this.resolve_expr(body);
+            }),
+            ExprKind::Fn(def) => self.with_scope(|this| {
+                this.bind_pats(
+                    def.params.iter().map(|param| &param.pat),
+                    BindingKind::Variable,
+                );
+                this.resolve_expr(&def.body);
+            }),
+            ExprKind::Call(callee, args) => {
+                self.resolve_expr(callee);
+                for arg in args {
+                    self.resolve_expr(arg);
+                }
+            }
+            ExprKind::Tuple(exprs) => {
+                for expr in exprs {
+                    self.resolve_expr(expr);
+                }
+            }
+            ExprKind::Match(scrutinee, arms) => {
+                self.resolve_expr(scrutinee);
+                for arm in arms {
+                    self.with_scope(|this| {
+                        this.bind_pats([&arm.pat], BindingKind::Variable);
+                        this.resolve_expr(&arm.body);
+                    });
+                }
+            }
+            ExprKind::Loop(body) => self.resolve_expr(body),
+            ExprKind::Break(value) => {
+                if let Some(value) = value {
+                    self.resolve_expr(value);
+                }
+            }
+            ExprKind::Block(exprs) => self.with_scope(|this| this.resolve_seq(exprs)),
+        }
+    }
+
+    /// Resolve a sequence of expressions in the innermost scope, binding the
+    /// names of the function items in it first.
+    fn resolve_seq(&mut self, exprs: &[Expr]) {
+        let fns = exprs.iter().filter_map(|expr| match &expr.kind {
+            ExprKind::Fn(def) => Some(&def.name),
+            _ => None,
+        });
+        self.bind_pats(fns, BindingKind::Fn);
+        for expr in exprs {
+            self.resolve_expr(expr);
+        }
+    }
+
+    fn with_scope(&mut self, f: impl FnOnce(&mut Self)) {
+        self.scopes.push(Vec::new());
+        f(self);
+        self.scopes.pop();
+    }
+
+    /// Add the bindings in a set of patterns, which are bound at the same
+    /// time (e.g. function parameters), to the innermost scope.
+    fn bind_pats<'pat>(&mut self, pats: impl IntoIterator<Item = &'pat Pat>, kind: BindingKind) {
+        let mut bound = Vec::new();
+        for pat in pats {
+            self.collect_bindings(pat, kind, &mut bound);
+        }
+
+        for (idx, &(name, id)) in bound.iter().enumerate() {
+            if let Some(&(_, prev)) = bound[..idx].iter().find(|(prev, _)| *prev == name) {
+                let span = self.res.bindings[&id].span;
+                let prev_span = self.res.bindings[&prev].span;
+                self.grcx.report_syncd(
+                    EnsembleBuilder::new()
+                        .error(|b| {
+                            b.code("E0006")
+                                .short(err!(E0006, name))
+                                .label(
+                                    LabelStyle::Primary,
+                                    Some("used as a binding more than once"),
+                                    self.file_id,
+                                    span,
+                                )
+                                .label(
+                                    LabelStyle::Secondary,
+                                    Some("first bound here"),
+                                    self.file_id,
+                                    prev_span,
+                                )
+                        })
+                        .build(),
+                );
+            }
+        }
+
+        self.scopes
+            .last_mut()
+            .expect("there is always a scope")
+            .extend(bound);
+    }
+
+    fn collect_bindings(
+        &mut self,
+        pat: &Pat,
+        kind: BindingKind,
+        bound: &mut Vec<(Symbol, NodeId)>,
+    ) {
+        match &pat.kind {
+            PatKind::Wild | PatKind::Lit(_) | PatKind::Neg(_) => {}
+            PatKind::Binding(mutability, name) => {
+                self.res.bindings.insert(
+                    pat.id,
+                    Binding {
+                        name: *name,
+                        mutability: *mutability,
+                        kind,
+                        span: pat.span,
+                    },
+                );
+                bound.push((*name, pat.id));
+            }
+            PatKind::Tuple(pats) => {
+                for pat in pats {
+                    self.collect_bindings(pat, kind, bound);
+                }
+            }
+            PatKind::Ctor(name, pats) => {
+                if let Some(ctor) = Builtin::from_name(*name).filter(|builtin| builtin.is_ctor()) {
+                    self.res.builtins.insert(pat.id, ctor);
+                } else {
+                    let similar = similar_ctor(*name).map(Similar::Builtin);
+                    self.report_unresolved(pat.span, *name, similar);
+                }
+                for pat in pats {
+                    self.collect_bindings(pat, kind, bound);
+                }
+            }
+        }
+    }
+
+    fn resolve_var(&mut self, id: NodeId, span: Span, name: Symbol) {
+        let def = self
+            .scopes
+            .iter()
+            .rev()
+            .find_map(|scope| scope.iter().rev().find(|(bound, _)| *bound == name))
+            .map(|&(_, def)| def);
+        if let Some(def) = def {
+            self.res.uses.insert(id, def);
+            return;
+        }
+        if let Some(builtin) = Builtin::from_name(name) {
+            self.res.builtins.insert(id, builtin);
+            return;
+        }
+
+        let similar = self
+            .similar_binding(name)
+            .map(Similar::Binding)
+            .or_else(|| similar_ctor(name).map(Similar::Builtin));
+        self.report_unresolved(span, name, similar);
+    }
+
+    /// Report that `name` could not be found, along with the binding or
+    /// built-in with the most similar name, if any.
+    fn report_unresolved(&mut self, span: Span, name: Symbol, similar: Option<Similar>) {
+        let mut builder = EnsembleBuilder::new().error(|b| {
+            b.code("E0005").short(err!(E0005, name)).label(
+                LabelStyle::Primary,
+                Some("not found in this scope"),
+                self.file_id,
+                span,
+            )
+        });
+        match similar {
+            Some(Similar::Binding(similar)) => {
+                let binding = self.res.bindings[&similar];
+                builder = builder.help(|b| {
+                    b.short(format!(
+                        "a variable with a similar name exists: `{}`",
+                        binding.name
+                    ))
+                    .label(
+                        LabelStyle::Secondary,
+                        Some("similarly named variable defined here"),
+                        self.file_id,
+                        binding.span,
+                    )
+                });
+            }
+            Some(Similar::Builtin(ctor)) => {
+                builder = builder.help(|b| {
+                    b.short(format!(
+                        "a built-in constructor with a similar name exists: `{ctor}`"
+                    ))
+                    .label(LabelStyle::Primary, None, self.file_id, span)
+                });
+            }
+            None => {}
+        }
+        self.grcx.report_syncd(builder.build());
+    }
+
+    /// Find the binding in scope with the name most similar to `name`, if
+    /// any is similar enough to be a likely typo.
+    fn similar_binding(&self, name: Symbol) -> Option<NodeId> {
+        let name = name.as_str();
+        let max_distance = (name.chars().count() / 3).max(1);
+        self.scopes
+            .iter()
+            .rev()
+            .flat_map(|scope| scope.iter().rev())
+            .filter_map(|&(candidate, def)| {
+                let distance = distance(candidate.as_str(), name);
+                (distance <= max_distance).then_some((distance, def))
+            })
+            // `min_by_key` returns the first minimum, i.e. the innermost.
+            .min_by_key(|&(distance, _)| distance)
+            .map(|(_, def)| def)
+    }
+}
+
+/// Something with a name similar to a name that could not be found.
+#[derive(Clone, Copy)]
+enum Similar {
+    Binding(NodeId),
+    Builtin(Builtin),
+}
+
+/// Find the built-in constructor with the name most similar to `name`, if
+/// any is similar enough to be a likely typo.
+fn similar_ctor(name: Symbol) -> Option<Builtin> {
+    let name = name.as_str();
+    let max_distance = (name.chars().count() / 3).max(1);
+    Builtin::ALL
+        .into_iter()
+        .filter(|builtin| builtin.is_ctor())
+        .map(|ctor| (distance(ctor.name(), name), ctor))
+        .filter(|&(distance, _)| distance <= max_distance)
+        .min_by_key(|&(distance, _)| distance)
+        .map(|(_, ctor)| ctor)
+}
+
+/// The edit distance between two names. Names that only differ in case are
+/// always considered similar.
+fn distance(candidate: &str, name: &str) -> usize {
+    if candidate.eq_ignore_ascii_case(name) {
+        0
+    } else {
+        strsim::damerau_levenshtein(candidate, name)
+    }
+}
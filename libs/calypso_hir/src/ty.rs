@@ -0,0 +1,35 @@
+use calypso_ast::node::NodeId;
+use calypso_base::{span::Span, symbol::Symbol};
+
+use crate::expr::Expr;
+
+/// A type.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Ty {
+    /// The ID of the AST node this was lowered from
+    pub id: NodeId,
+    /// The span of the source this was lowered from
+    pub span: Span,
+    /// The kind of type
+    pub kind: TyKind,
+}
+
+impl Ty {
+    /// Create a new type.
+    #[must_use]
+    pub fn new(id: NodeId, span: Span, kind: TyKind) -> Self {
+        Self { id, span, kind }
+    }
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TyKind {
+    /// A named type, along with its type arguments if it is generic, e.g.
+    /// `uint` or `Option[uint]`
+    Named(Symbol, Vec<Ty>),
+    /// An array type, with its length if it is specified. The length is an
+    /// expression that must be evaluated at compile time.
+    Array(Box<Ty>, Option<Box<Expr>>),
+    /// A tuple type
+    Tuple(Vec<Ty>),
+}
@@ -0,0 +1,121 @@
+//! Traversal of the HIR.
+//!
+//! Each method of [`Visitor`] has a default implementation that recurses
+//! into the children of the node using the corresponding `walk_*` function.
+//! Implementations can override only the nodes they care about, and call
+//! the `walk_*` function themselves in order to keep recursing.
+//!
+//! Unlike the AST visitors, HIR visitors cannot fail. Passes over the HIR
+//! report problems as diagnostics instead.
+
+use crate::{
+    expr::{Expr, ExprKind},
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+
+pub trait Visitor {
+    /// Visit an expression. By default, this calls [`walk_expr`].
+    fn visit_expr(&mut self, expr: &Expr) {
+        walk_expr(self, expr);
+    }
+
+    /// Visit a pattern. By default, this calls [`walk_pat`].
+    fn visit_pat(&mut self, pat: &Pat) {
+        walk_pat(self, pat);
+    }
+
+    /// Visit a type. By default, this calls [`walk_ty`].
+    fn visit_ty(&mut self, ty: &Ty) {
+        walk_ty(self, ty);
+    }
+}
+
+/// Visit all of the children of an expression, in source order.
+pub fn walk_expr<V: Visitor + ?Sized>(visitor: &mut V, expr: &Expr) {
+    match &expr.kind {
+        ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Prim(_) | ExprKind::Break(None) => {}
+        ExprKind::Let(pat, ty, value, body) => {
+            visitor.visit_pat(pat);
+            if let Some(ty) = ty {
+                visitor.visit_ty(ty);
+            }
+            visitor.visit_expr(value);
+            visitor.visit_expr(body);
+        }
+        ExprKind::Assign(target, value) => {
+            visitor.visit_expr(target);
+            visitor.visit_expr(value);
+        }
+        ExprKind::Lambda(params, body) => {
+            for param in params {
+                visitor.visit_pat(&param.pat);
+                if let Some(ty) = &param.ty {
+                    visitor.visit_ty(ty);
+                }
+            }
+            visitor.visit_expr(body);
+        }
+        ExprKind::Call(callee, args) => {
+            visitor.visit_expr(callee);
+            for arg in args {
+                visitor.visit_expr(arg);
+            }
+        }
+        ExprKind::Match(scrutinee, arms) => {
+            visitor.visit_expr(scrutinee);
+            for arm in arms {
+                visitor.visit_pat(&arm.pat);
+                visitor.visit_expr(&arm.body);
+            }
+        }
+        ExprKind::Loop(body) | ExprKind::Break(Some(body)) => visitor.visit_expr(body),
+        ExprKind::Tuple(exprs) | ExprKind::Block(exprs) => {
+            for expr in exprs {
+                visitor.visit_expr(expr);
+            }
+        }
+        ExprKind::Fn(def) => {
+            visitor.visit_pat(&def.name);
+            for param in &def.params {
+                visitor.visit_pat(&param.pat);
+                if let Some(ty) = &param.ty {
+                    visitor.visit_ty(ty);
+                }
+            }
+            if let Some(ret) = &def.ret {
+                visitor.visit_ty(ret);
+            }
+            visitor.visit_expr(&def.body);
+        }
+    }
+}
+
+/// Visit all of the children of a pattern, in source order.
+pub fn walk_pat<V: Visitor + ?Sized>(visitor: &mut V, pat: &Pat) {
+    match &pat.kind {
+        PatKind::Wild | PatKind::Binding(..) | PatKind::Lit(_) | PatKind::Neg(_) => {}
+        PatKind::Tuple(pats) | PatKind::Ctor(_, pats) => {
+            for pat in pats {
+                visitor.visit_pat(pat);
+            }
+        }
+    }
+}
+
+/// Visit all of the children of a type, in source order.
+pub fn walk_ty<V: Visitor + ?Sized>(visitor: &mut V, ty: &Ty) {
+    match &ty.kind {
+        TyKind::Array(elem, len) => {
+            visitor.visit_ty(elem);
+            if let Some(len) = len {
+                visitor.visit_expr(len);
+            }
+        }
+        TyKind::Named(_, tys) | TyKind::Tuple(tys) => {
+            for ty in tys {
+                visitor.visit_ty(ty);
+            }
+        }
+    }
+}
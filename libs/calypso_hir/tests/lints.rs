@@ -0,0 +1,109 @@
+use calypso_ast::parens::check_parens;
+use calypso_diagnostic::{diagnostic::Applicability, report::GlobalReportingCtxt};
+use calypso_hir::{bindings::check_bindings, reachability::check_reachability};
+use calypso_test_support::{render, resolve_str_with, Resolved};
+
+/// Run the lints on `src`, returning the rendered warnings and the source
+/// with all suggestions applied.
+fn lint(src: &str) -> (Vec<String>, String) {
+    let Resolved {
+        mut grcx,
+        exprs,
+        res,
+    } = resolve_str_with(src, GlobalReportingCtxt::new(), |grcx, exprs| {
+        check_parens(grcx, 0, src, exprs);
+    });
+    check_bindings(&mut grcx, 0, &exprs, &res);
+    check_reachability(&mut grcx, 0, &exprs);
+    assert!(grcx.errors().is_empty(), "linting {src:?}");
+    (render(grcx.nonfatals()), apply_suggestions(src, &grcx))
+}
+
+/// Apply all machine-applicable suggestions in `grcx` to `src`.
+fn apply_suggestions(src: &str, grcx: &GlobalReportingCtxt) -> String {
+    let mut edits = grcx
+        .nonfatals()
+        .iter()
+        .flat_map(|ensemble| ensemble.diagnostics())
+        .flat_map(|diag| diag.suggestions())
+        .filter(|suggestion| suggestion.applicability == Applicability::MachineApplicable)
+        .flat_map(|suggestion| suggestion.edits.iter())
+        .collect::<Vec<_>>();
+    edits.sort_by_key(|edit| std::cmp::Reverse(edit.span));
+    let mut src = src.to_string();
+    for edit in edits {
+        src.replace_range(edit.span.into_range(), &edit.replacement);
+    }
+    src
+}
+
+#[test]
+fn unused_variables() {
+    let (warnings, fixed) = lint("let x = 1 in let mut y = 2 in let _z = 3 in 4");
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("unused variable: `x`"));
+    assert!(warnings[1].contains("unused variable: `y`"));
+    assert_eq!(fixed, "let _x = 1 in let mut _y = 2 in let _z = 3 in 4");
+
+    // Assigning to a variable counts as a use.
+    let (warnings, _) = lint("let mut x = 1 in x = 2");
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn shadowed_bindings() {
+    let src = "let x = 1 in let y = x * 2 in let x = 3 in x + y";
+    let (warnings, fixed) = lint(src);
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("`x` shadows a binding in the same scope"));
+    assert_eq!(
+        fixed,
+        "let x = 1 in let y = x * 2 in let x_2 = 3 in x_2 + y"
+    );
+
+    // Rebinding a name to a value computed from it is not linted, nor is
+    // shadowing in a nested scope.
+    let (warnings, _) = lint("let x = 1 in let x = x + 1 in x");
+    assert!(warnings.is_empty());
+    let (warnings, _) = lint("let x = 1 in do let x = 2 in x end + x");
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn unused_parens() {
+    let (warnings, fixed) = lint("let x = (1 + 2) in (x) * (x + 1) - (-x)");
+    assert_eq!(warnings.len(), 3);
+    assert!(warnings[0].contains("unnecessary parentheses"));
+    assert_eq!(fixed, "let x = 1 + 2 in x * (x + 1) - -x");
+
+    // Removing the parentheses must not merge tokens.
+    let (_, fixed) = lint("1-(-1)");
+    assert_eq!(fixed, "1- -1");
+
+    // Parentheses around tuples and those that are needed are not linted.
+    let (warnings, _) = lint("(1,); (1 + 2) * 3; -(1 + 2)");
+    assert!(warnings.is_empty());
+
+    // Each unnecessary pair is reported, including those around a pair that
+    // is needed or that belongs to a tuple.
+    let (warnings, fixed) = lint("((1)); 2 * (( 1 + 2 )); ((1, 2)); -((1)); 1-((-1))");
+    assert_eq!(warnings.len(), 8);
+    assert_eq!(fixed, "1; 2 * (1 + 2); (1, 2); -1; 1- -1");
+}
+
+#[test]
+fn unreachable_code() {
+    let (warnings, fixed) = lint("loop do break; 1; 2 end");
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("unreachable expression"));
+    assert_eq!(fixed, "loop do break end");
+
+    // A loop that breaks finishes, so only the code after the `break` is
+    // unreachable, but a loop that does not break diverges.
+    let (warnings, fixed) = lint("loop do break; 1 end; 2");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(fixed, "loop do break end; 2");
+    let (warnings, fixed) = lint("loop do () end; 2");
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(fixed, "loop do () end");
+}
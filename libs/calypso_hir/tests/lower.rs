@@ -0,0 +1,65 @@
+use calypso_base::span::Span;
+use calypso_hir::expr::ExprKind;
+use calypso_test_support::lower_str;
+
+/// Lower `src` and check that it prints as `expected`.
+fn check(src: &str, expected: &str) {
+    let printed = lower_str(src)
+        .iter()
+        .map(ToString::to_string)
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert_eq!(printed, expected, "lowering {src:?}");
+}
+
+#[test]
+fn operators() {
+    check("1 + 2 * x", "(call + 1 (call * 2 x))");
+    check("-!x", "(call - (call ! x))");
+    check("a && b", "(match a (false false) (_ b))");
+    check("a || b", "(match a (true true) (_ b))");
+}
+
+#[test]
+fn lets_and_blocks() {
+    check("let x = 1 in x", "(let x 1 in x)");
+    check(
+        "let mut x: [(uint, bool); 0x4] = y in do x; end",
+        "(let (mut x) (ty (array (tuple uint bool) 0x4)) y in (block x))",
+    );
+    check("do end; 1.5; true", "(block)\n1.5\ntrue");
+}
+
+#[test]
+fn fn_items_and_ctors() {
+    check(
+        "with(T) fn f(mut x: T, y): T -> g(x)",
+        "(fn f (with T) (((mut x) (ty T)) y) (ty T) (call g x))",
+    );
+    check(
+        "case x do Some(None) -> None, y -> Some(y) end",
+        "(match x ((ctor Some None) None) (y (call Some y)))",
+    );
+    check("case x do -1 -> 1, _ -> 2 end", "(match x ((- 1) 1) (_ 2))");
+}
+
+#[test]
+fn spans_and_ids() {
+    let exprs = lower_str("a && b");
+    let expr = &exprs[0];
+    assert_eq!(expr.span, Span::new(0, 6));
+
+    let ExprKind::Match(scrutinee, arms) = &expr.kind else {
+        panic!("expected a match, got {expr}");
+    };
+    assert_eq!(scrutinee.span, Span::new(0, 1));
+    // The desugared `false` points at the operator.
+    assert_eq!(arms[0].body.span, Span::new(2, 4));
+    assert_eq!(arms[1].body.span, Span::new(5, 6));
+
+    let mut ids = vec![expr.id, scrutinee.id, arms[0].body.id, arms[1].body.id];
+    ids.extend(arms.iter().map(|arm| arm.pat.id));
+    ids.sort();
+    ids.dedup();
+    assert_eq!(ids.len(), 6, "node IDs are not unique");
+}
@@ -0,0 +1,122 @@
+use calypso_ast::attr::register_lint_levels;
+use calypso_diagnostic::{
+    lint::{self, Level},
+    report::GlobalReportingCtxt,
+};
+use calypso_hir::mutability::check_mutability;
+use calypso_test_support::{render, resolve_str_with, Resolved};
+
+/// Check the mutability of `src`, returning the rendered errors and
+/// warnings.
+fn check(src: &str) -> (Vec<String>, Vec<String>) {
+    check_with_levels(src, &[])
+}
+
+/// Check the mutability of `src` with the given command-line lint levels.
+fn check_with_levels(src: &str, levels: &[(&str, Level)]) -> (Vec<String>, Vec<String>) {
+    let mut grcx = GlobalReportingCtxt::new();
+    for &(name, level) in levels {
+        let lint = lint::find(name).unwrap();
+        grcx.lints_mut().set_command_line(lint, level);
+    }
+    let Resolved {
+        mut grcx,
+        exprs,
+        res,
+    } = resolve_str_with(src, grcx, |grcx, exprs| {
+        register_lint_levels(grcx, 0, exprs);
+    });
+    check_mutability(&mut grcx, 0, &exprs, &res);
+    (render(grcx.errors()), render(grcx.nonfatals()))
+}
+
+#[test]
+fn assign_mutable() {
+    let (errors, warnings) = check("let mut x = 1 in do x = x + 1; x end");
+    assert!(errors.is_empty());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn assign_immutable() {
+    let (errors, warnings) = check("let x = 1 in do x = 2; let y = 3 in y = x end");
+    assert!(warnings.is_empty());
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("Cannot assign to immutable variable `x`."));
+    assert!(errors[0].contains("consider making this binding mutable: `mut x`"));
+    assert!(errors[1].contains("Cannot assign to immutable variable `y`."));
+}
+
+#[test]
+fn assign_shadowed() {
+    // The assignment refers to the innermost binding, which is immutable.
+    let (errors, warnings) = check("let mut x = 1 in let x = x in x = 2");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Cannot assign to immutable variable `x`."));
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("variable does not need to be mutable"));
+}
+
+#[test]
+fn unused_mut() {
+    let (errors, warnings) = check("let mut a = 1 in let mut b = 2 in do b = a; a end");
+    assert!(errors.is_empty());
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("remove the `mut` from `mut a`"));
+    assert!(warnings[0].contains("`@warn(unused_mut)` is on by default"));
+}
+
+#[test]
+fn lint_attributes() {
+    let (errors, warnings) =
+        check("@allow(unused_mut) let mut a = 1 in @deny(unused_mut) let mut b = 2 in a + b");
+    assert!(warnings.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("remove the `mut` from `mut b`"));
+    assert!(errors[0].contains("the lint level is defined here"));
+}
+
+#[test]
+fn command_line_levels() {
+    let (errors, warnings) =
+        check_with_levels("let mut a = 1 in a", &[("unused-mut", Level::Deny)]);
+    assert!(warnings.is_empty());
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("requested on the command line with `-D unused_mut`"));
+
+    let (errors, warnings) =
+        check_with_levels("let mut a = 1 in a", &[("unused_mut", Level::Allow)]);
+    assert!(errors.is_empty());
+    assert!(warnings.is_empty());
+}
+
+#[test]
+fn forbidden_lints() {
+    let (errors, warnings) = check("@forbid(unused_mut) @allow(unused_mut) let mut a = 1 in a");
+    assert!(warnings.is_empty());
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("Cannot lower the level of the forbidden lint `unused_mut`."));
+    assert!(errors[1].contains("variable does not need to be mutable"));
+
+    let (errors, _) = check_with_levels("@allow(unused_mut) 1", &[("unused_mut", Level::Forbid)]);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("forbidden on the command line with `-F unused_mut`"));
+}
+
+#[test]
+fn unknown_attributes_and_lints() {
+    let (errors, warnings) = check("@allow(unused_muts) @frob(unused_mut) 1");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Cannot find attribute `frob`."));
+    assert_eq!(warnings.len(), 1);
+    assert!(warnings[0].contains("unknown lint: `unused_muts`"));
+}
+
+#[test]
+fn unresolved_target() {
+    // Unresolved names are only reported by the resolver.
+    let (errors, warnings) = check("x = 1");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Cannot find value `x` in this scope."));
+    assert!(warnings.is_empty());
+}
@@ -0,0 +1,146 @@
+use calypso_hir::{
+    builtin::Builtin,
+    expr::{Expr, ExprKind},
+};
+use calypso_test_support::{render, resolve_str, Resolved};
+
+/// Collect the variable uses in `expr`.
+fn vars<'a>(expr: &'a Expr, out: &mut Vec<&'a Expr>) {
+    match &expr.kind {
+        ExprKind::Var(_) => out.push(expr),
+        ExprKind::Lit(_) | ExprKind::Prim(_) | ExprKind::Break(None) => {}
+        ExprKind::Let(_, _, value, body) | ExprKind::Assign(value, body) => {
+            vars(value, out);
+            vars(body, out);
+        }
+        ExprKind::Lambda(_, body) | ExprKind::Loop(body) | ExprKind::Break(Some(body)) => {
+            vars(body, out);
+        }
+        ExprKind::Fn(def) => vars(&def.body, out),
+        ExprKind::Call(callee, args) => {
+            vars(callee, out);
+            args.iter().for_each(|arg| vars(arg, out));
+        }
+        ExprKind::Match(scrutinee, arms) => {
+            vars(scrutinee, out);
+            arms.iter().for_each(|arm| vars(&arm.body, out));
+        }
+        ExprKind::Tuple(exprs) | ExprKind::Block(exprs) => {
+            exprs.iter().for_each(|expr| vars(expr, out));
+        }
+    }
+}
+
+/// Resolve `src`, returning the text of each variable use along with the
+/// position of the binding it refers to, and the rendered errors.
+fn check(src: &str) -> (Vec<(String, Option<u32>)>, Vec<String>) {
+    let Resolved { grcx, exprs, res } = resolve_str(src);
+
+    let mut uses = Vec::new();
+    exprs.iter().for_each(|expr| vars(expr, &mut uses));
+    let uses = uses
+        .into_iter()
+        .map(|expr| {
+            let def = res
+                .binding_of(expr.id)
+                .map(|(_, binding)| binding.span.lo());
+            (src[expr.span.into_range()].to_string(), def)
+        })
+        .collect();
+    (uses, render(grcx.errors()))
+}
+
+fn uses(resolved: &[(&str, Option<u32>)]) -> Vec<(String, Option<u32>)> {
+    resolved
+        .iter()
+        .map(|&(name, def)| (name.to_string(), def))
+        .collect()
+}
+
+#[test]
+fn shadowing() {
+    // The value of a `let` refers to the previous binding.
+    let (resolved, errors) = check("let a = 0 in let a = a + 5 in a");
+    assert!(errors.is_empty());
+    assert_eq!(resolved, uses(&[("a", Some(4)), ("a", Some(17))]));
+
+    // Rebinding only happens within the scope of the `let`.
+    let (resolved, errors) = check("let a = 0 in do let a = 1 in a; a end");
+    assert!(errors.is_empty());
+    assert_eq!(resolved, uses(&[("a", Some(20)), ("a", Some(4))]));
+}
+
+#[test]
+fn unresolved() {
+    let (resolved, errors) = check("let count = 1 in cuont + x; count");
+    assert_eq!(
+        resolved,
+        uses(&[("cuont", None), ("x", None), ("count", None)])
+    );
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("Cannot find value `cuont` in this scope."));
+    assert!(errors[0].contains("a variable with a similar name exists: `count`"));
+    assert!(errors[1].contains("Cannot find value `x` in this scope."));
+    assert!(!errors[1].contains("similar name"));
+    // `count` is no longer in scope after the `let`.
+    assert!(errors[2].contains("Cannot find value `count` in this scope."));
+}
+
+#[test]
+fn parameters() {
+    let (resolved, errors) = check("fn f(x, y, x) -> y");
+    assert_eq!(resolved, uses(&[("y", Some(8))]));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Identifier `x` is bound more than once in the same pattern."));
+}
+
+#[test]
+fn fn_items() {
+    // Function items are in scope in the whole sequence they are in,
+    // including their own bodies and the bodies of the items before them.
+    let (resolved, errors) = check("f(1); fn f(n) -> g(n); fn g(n) -> f(n)");
+    assert!(errors.is_empty());
+    assert_eq!(
+        resolved,
+        uses(&[
+            ("f", Some(9)),
+            ("g", Some(26)),
+            ("n", Some(11)),
+            ("f", Some(9)),
+            ("n", Some(28))
+        ])
+    );
+
+    let (resolved, errors) = check("do fn f() -> 1 end; f()");
+    assert_eq!(resolved, uses(&[("f", None)]));
+    assert_eq!(errors.len(), 1);
+}
+
+#[test]
+fn builtins() {
+    let Resolved { grcx, exprs, res } =
+        resolve_str("let x = Some in case x(None) do Some(y) -> y, None -> Ok end");
+    assert!(grcx.errors().is_empty());
+
+    let mut uses = Vec::new();
+    exprs.iter().for_each(|expr| vars(expr, &mut uses));
+    let builtins = uses
+        .iter()
+        .map(|expr| res.builtin(expr.id))
+        .collect::<Vec<_>>();
+    assert_eq!(
+        builtins,
+        [
+            Some(Builtin::Some),
+            None,
+            Some(Builtin::None),
+            None,
+            Some(Builtin::Ok)
+        ]
+    );
+
+    let (_, errors) = check("case 1 do Smoe(x) -> x end");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Cannot find value `Smoe` in this scope."));
+    assert!(errors[0].contains("a built-in constructor with a similar name exists: `Some`"));
+}
@@ -0,0 +1,486 @@
+//! Evaluation of the HIR.
+
+use std::{cell::RefCell, mem, rc::Rc};
+
+use calypso_ast::{expr::UnOpKind, node::NodeId};
+use calypso_base::span::Span;
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+use calypso_hir::{
+    builtin::Builtin,
+    expr::{Expr, ExprKind, FnDef, Lit, Prim},
+    pat::{Pat, PatKind},
+    resolve::Resolutions,
+};
+use calypso_typeck::{
+    check::TypeckResults,
+    consteval::{
+        describe_failed_op, eval_binop, eval_lit, eval_neg_lit, eval_unop, is_min_sint_magnitude,
+    },
+    ty::{PrimTy, Type},
+};
+
+use crate::value::{Closure, Env, Value};
+
+/// The maximum number of nested function calls. A call nested any deeper
+/// stops the program with a runtime error. The interpreter recurses on the
+/// native stack, so it must be run on a stack that is large enough for this
+/// many calls; in debug builds, that is much larger than the default.
+pub const MAX_CALL_DEPTH: usize = 1000;
+
+/// An error that stops the program.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RuntimeError {
+    /// The span of the expression that failed
+    pub span: Span,
+    /// A short description of the error, e.g. "division by zero"
+    pub message: String,
+    /// A description of what the expression was doing when it failed
+    pub label: String,
+}
+
+impl RuntimeError {
+    /// Report the error to `grcx` as a synchronizable error.
+    pub fn report(&self, grcx: &mut GlobalReportingCtxt, file_id: usize) {
+        grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code("E0019").short(err!(E0019, self.message)).label(
+                        LabelStyle::Primary,
+                        Some(&self.label),
+                        file_id,
+                        self.span,
+                    )
+                })
+                .build(),
+        );
+    }
+}
+
+/// Evaluate a sequence of type checked expressions in order, returning the
+/// value of the last one. If the program fails, the error is reported to
+/// `grcx` as a synchronizable error and `None` is returned.
+///
+/// The expressions must not have any errors, as they are assumed to be
+/// well-typed.
+pub fn interpret<'hir>(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &'hir [Expr],
+    res: &Resolutions,
+    results: &TypeckResults,
+) -> Option<Value<'hir>> {
+    let mut interp = Interpreter::new(res, results);
+    interp.define_fns(exprs);
+    let mut value = Value::UNIT;
+    for expr in exprs {
+        match interp.eval(expr) {
+            Ok(result) => value = result,
+            Err(err) => {
+                err.report(grcx, file_id);
+                return None;
+            }
+        }
+    }
+    Some(value)
+}
+
+/// Run a program with a `main` function: evaluate its type checked
+/// expressions in order, then call `main` with the program arguments `args`
+/// and return the exit status that it returns. If the program fails, the
+/// error is reported to `grcx` as a synchronizable error and `None` is
+/// returned.
+///
+/// `main` must be one of the expressions, and must have been checked by
+/// [`check_main`](calypso_typeck::entry::check_main).
+pub fn interpret_main<'hir>(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &'hir [Expr],
+    main: &'hir FnDef,
+    args: &[String],
+    res: &Resolutions,
+    results: &TypeckResults,
+) -> Option<u8> {
+    let mut interp = Interpreter::new(res, results);
+    interp.define_fns(exprs);
+    let status = exprs
+        .iter()
+        .try_for_each(|expr| interp.eval(expr).map(drop))
+        .and_then(|()| {
+            let main_fn = interp.env[&main.name.id].borrow().clone();
+            let value = interp.call(&main_fn, vec![Value::Args(args.into())])?;
+            exit_status(&value, main)
+        });
+    match status {
+        Ok(status) => Some(status),
+        Err(err) => {
+            err.report(grcx, file_id);
+            None
+        }
+    }
+}
+
+/// Get the exit status from the value that `main` returned. `()` is status
+/// 0, and an integer is its own status if it is from 0 to 255. A generic
+/// `main` may return anything, e.g. `fn main(args) -> args`, and any other
+/// value is an error.
+fn exit_status(value: &Value, main: &FnDef) -> Result<u8, RuntimeError> {
+    let error = |message: &str, label| RuntimeError {
+        span: main.body.span,
+        message: message.to_string(),
+        label,
+    };
+    let out_of_range = || {
+        error(
+            "exit status out of range",
+            format!("`main` returned `{value}`, but exit statuses are from 0 to 255"),
+        )
+    };
+    match *value {
+        Value::Uint(int) => u8::try_from(int).map_err(|_| out_of_range()),
+        Value::Sint(int) => u8::try_from(int).map_err(|_| out_of_range()),
+        _ if value.is_unit() => Ok(0),
+        _ => Err(error(
+            "invalid exit status",
+            format!("`main` returned `{value}`, but it must return an integer or `()`"),
+        )),
+    }
+}
+
+/// Call the built-in function `builtin` with `args`.
+fn call_builtin(builtin: Builtin, args: Vec<Value<'_>>) -> Value<'_> {
+    if builtin.is_ctor() {
+        return Value::Variant(builtin, args);
+    }
+    match (builtin, args.as_slice()) {
+        (Builtin::ArgCount, [Value::Args(args)]) => {
+            Value::Uint(u64::try_from(args.len()).expect("there are at most u64::MAX arguments"))
+        }
+        (Builtin::ArgUint, [Value::Args(args), Value::Uint(idx)]) => {
+            let arg = usize::try_from(*idx)
+                .ok()
+                .and_then(|idx| args.get(idx))
+                .and_then(|arg| arg.parse().ok());
+            match arg {
+                Some(int) => Value::Variant(Builtin::Some, vec![Value::Uint(int)]),
+                None => Value::Variant(Builtin::None, Vec::new()),
+            }
+        }
+        _ => unreachable!("built-ins are called with arguments of the right types"),
+    }
+}
+
+/// The ways that evaluating an expression can finish early.
+enum Unwind<'hir> {
+    /// A `break` out of the innermost loop, with its value
+    Break(Value<'hir>),
+    Error(RuntimeError),
+}
+
+impl From<RuntimeError> for Unwind<'_> {
+    fn from(err: RuntimeError) -> Self {
+        Self::Error(err)
+    }
+}
+
+/// A tree-walking interpreter for the HIR.
+pub struct Interpreter<'hir, 'a> {
+    res: &'a Resolutions,
+    results: &'a TypeckResults,
+    /// The variables in scope
+    env: Env<'hir>,
+    /// The number of function calls being evaluated
+    depth: usize,
+}
+
+impl<'hir, 'a> Interpreter<'hir, 'a> {
+    /// Create an interpreter for expressions that were resolved into `res`
+    /// and type checked into `results`.
+    #[must_use]
+    pub fn new(res: &'a Resolutions, results: &'a TypeckResults) -> Self {
+        Self {
+            res,
+            results,
+            env: Env::new(),
+            depth: 0,
+        }
+    }
+
+    /// Evaluate an expression.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the program fails, e.g. if it
+    /// divides by zero.
+    pub fn eval(&mut self, expr: &'hir Expr) -> Result<Value<'hir>, RuntimeError> {
+        match self.eval_expr(expr) {
+            Ok(value) => Ok(value),
+            Err(Unwind::Error(err)) => Err(err),
+            Err(Unwind::Break(_)) => unreachable!("`break` is only used inside loops"),
+        }
+    }
+
+    /// Define the function items in a sequence of expressions, before any
+    /// of the expressions are evaluated, so that they can be called from
+    /// anywhere in the sequence (including each other's bodies).
+    pub fn define_fns(&mut self, exprs: &'hir [Expr]) {
+        let defs = exprs
+            .iter()
+            .filter_map(|expr| match &expr.kind {
+                ExprKind::Fn(def) => Some(def),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        for def in &defs {
+            self.env
+                .insert(def.name.id, Rc::new(RefCell::new(Value::UNIT)));
+        }
+        for def in defs {
+            *self.env[&def.name.id].borrow_mut() = Value::Fn(Rc::new(Closure {
+                params: &def.params,
+                body: &def.body,
+                env: self.env.clone(),
+            }));
+        }
+    }
+
+    /// Call a function value with `args`.
+    ///
+    /// # Errors
+    ///
+    /// This function returns an error if the program fails while the
+    /// function is running.
+    ///
+    /// # Panics
+    ///
+    /// This function panics if `callee` is not a function.
+    pub fn call(
+        &mut self,
+        callee: &Value<'hir>,
+        args: Vec<Value<'hir>>,
+    ) -> Result<Value<'hir>, RuntimeError> {
+        let closure = match callee {
+            Value::Fn(closure) => closure,
+            Value::Builtin(builtin) => return Ok(call_builtin(*builtin, args)),
+            _ => unreachable!("only functions are called"),
+        };
+
+        let mut env = closure.env.clone();
+        for (param, arg) in closure.params.iter().zip(args) {
+            let mut bindings = Vec::new();
+            let matched = match_pat(&param.pat, arg, &mut bindings);
+            assert!(matched, "parameter patterns are irrefutable");
+            env.extend(bind(bindings));
+        }
+
+        let env = mem::replace(&mut self.env, env);
+        let value = self.eval(closure.body);
+        self.env = env;
+        value
+    }
+
+    fn eval_expr(&mut self, expr: &'hir Expr) -> Result<Value<'hir>, Unwind<'hir>> {
+        let value = match &expr.kind {
+            ExprKind::Lit(lit) => self.eval_lit(*lit, expr),
+            ExprKind::Var(_) => match self.res.builtin(expr.id) {
+                Some(Builtin::None) => Value::Variant(Builtin::None, Vec::new()),
+                Some(builtin) => Value::Builtin(builtin),
+                None => self.var(expr).borrow().clone(),
+            },
+            ExprKind::Prim(_) => unreachable!("primitives are only called"),
+            ExprKind::Let(pat, _, value, body) => {
+                let value = self.eval_expr(value)?;
+                let mut bindings = Vec::new();
+                let matched = match_pat(pat, value, &mut bindings);
+                assert!(matched, "`let` patterns are irrefutable");
+                self.env.extend(bind(bindings));
+                self.eval_expr(body)?
+            }
+            ExprKind::Assign(target, value) => {
+                let value = self.eval_expr(value)?;
+                *self.var(target).borrow_mut() = value.clone();
+                value
+            }
+            // Function items are defined by `define_fns`.
+            ExprKind::Fn(_) => Value::UNIT,
+            ExprKind::Lambda(params, body) => Value::Fn(Rc::new(Closure {
+                params,
+                body,
+                env: self.env.clone(),
+            })),
+            ExprKind::Call(callee, args) => self.eval_call(expr, callee, args)?,
+            ExprKind::Tuple(exprs) => Value::Tuple(
+                exprs
+                    .iter()
+                    .map(|expr| self.eval_expr(expr))
+                    .collect::<Result<_, _>>()?,
+            ),
+            ExprKind::Match(scrutinee, arms) => {
+                let value = self.eval_expr(scrutinee)?;
+                for arm in arms {
+                    let mut bindings = Vec::new();
+                    if match_pat(&arm.pat, value.clone(), &mut bindings) {
+                        self.env.extend(bind(bindings));
+                        return self.eval_expr(&arm.body);
+                    }
+                }
+                return Err(RuntimeError {
+                    span: scrutinee.span,
+                    message: "no arm matched".to_string(),
+                    label: format!("no arm matched the value `{value}`"),
+                }
+                .into());
+            }
+            ExprKind::Loop(body) => loop {
+                match self.eval_expr(body) {
+                    Ok(_) => {}
+                    Err(Unwind::Break(value)) => break value,
+                    Err(err) => return Err(err),
+                }
+            },
+            ExprKind::Break(value) => {
+                let value = match value {
+                    Some(value) => self.eval_expr(value)?,
+                    None => Value::UNIT,
+                };
+                return Err(Unwind::Break(value));
+            }
+            ExprKind::Block(exprs) => {
+                self.define_fns(exprs);
+                let mut value = Value::UNIT;
+                for expr in exprs {
+                    value = self.eval_expr(expr)?;
+                }
+                value
+            }
+        };
+        Ok(value)
+    }
+
+    /// Get the variable that `expr` refers to.
+    fn var(&self, expr: &Expr) -> Rc<RefCell<Value<'hir>>> {
+        self.res
+            .resolve(expr.id)
+            .and_then(|def| self.env.get(&def))
+            .cloned()
+            .expect("variables are bound before they are used")
+    }
+
+    fn eval_lit(&self, lit: Lit, expr: &Expr) -> Value<'hir> {
+        let ty = self
+            .results
+            .node_type(expr.id)
+            .expect("literals have a type after type checking");
+        let value = eval_lit(lit, ty)
+            .and_then(Result::ok)
+            .expect("literals are in range after constant evaluation");
+        value.into()
+    }
+
+    fn eval_call(
+        &mut self,
+        expr: &Expr,
+        callee: &'hir Expr,
+        args: &'hir [Expr],
+    ) -> Result<Value<'hir>, Unwind<'hir>> {
+        let ExprKind::Prim(prim) = callee.kind else {
+            let callee = self.eval_expr(callee)?;
+            let args = args
+                .iter()
+                .map(|arg| self.eval_expr(arg))
+                .collect::<Result<_, _>>()?;
+            if self.depth == MAX_CALL_DEPTH {
+                return Err(RuntimeError {
+                    span: expr.span,
+                    message: "recursion limit reached".to_string(),
+                    label: format!("this call is nested more than {MAX_CALL_DEPTH} calls deep"),
+                }
+                .into());
+            }
+            self.depth += 1;
+            let value = self.call(&callee, args);
+            self.depth -= 1;
+            return Ok(value?);
+        };
+
+        if let (Prim::UnOp(UnOpKind::Negative), [arg]) = (prim, args) {
+            if is_min_sint_magnitude(arg, self.results) {
+                return Ok(Value::Sint(i64::MIN));
+            }
+        }
+
+        let operands = args
+            .iter()
+            .map(|arg| {
+                let value = self.eval_expr(arg)?;
+                Ok(value.to_const().expect("primitive operands are constants"))
+            })
+            .collect::<Result<Vec<_>, Unwind>>()?;
+        let result = match (prim, operands.as_slice()) {
+            (Prim::BinOp(op), [lhs, rhs]) => eval_binop(op, lhs, rhs),
+            (Prim::UnOp(op), [operand]) => eval_unop(op, operand),
+            _ => unreachable!("primitives are called with the right number of arguments"),
+        };
+        result.map(Value::from).map_err(|err| {
+            RuntimeError {
+                span: expr.span,
+                message: err.to_string(),
+                label: describe_failed_op(prim, &operands, err),
+            }
+            .into()
+        })
+    }
+}
+
+/// Create new variables for the values bound by a pattern.
+fn bind(
+    bindings: Vec<(NodeId, Value<'_>)>,
+) -> impl Iterator<Item = (NodeId, Rc<RefCell<Value<'_>>>)> {
+    bindings
+        .into_iter()
+        .map(|(id, value)| (id, Rc::new(RefCell::new(value))))
+}
+
+/// Check if `pat` matches `value`, adding the values bound by its bindings
+/// to `bindings` if it does.
+fn match_pat<'hir>(
+    pat: &Pat,
+    value: Value<'hir>,
+    bindings: &mut Vec<(NodeId, Value<'hir>)>,
+) -> bool {
+    match (&pat.kind, value) {
+        (PatKind::Wild, _) => true,
+        (PatKind::Binding(..), value) => {
+            bindings.push((pat.id, value));
+            true
+        }
+        (PatKind::Lit(Lit::Bool(lit)), Value::Bool(b)) => *lit == b,
+        (PatKind::Lit(lit @ Lit::Number(..)), value) => {
+            let ty = match value {
+                Value::Uint(_) => PrimTy::Uint,
+                Value::Sint(_) => PrimTy::Sint,
+                _ => PrimTy::Float,
+            };
+            eval_lit(*lit, &Type::Prim(ty)).and_then(Result::ok) == value.to_const()
+        }
+        (PatKind::Neg(lit), value @ (Value::Sint(_) | Value::Float(_))) => {
+            let ty = match value {
+                Value::Sint(_) => PrimTy::Sint,
+                _ => PrimTy::Float,
+            };
+            eval_neg_lit(*lit, &Type::Prim(ty)).and_then(Result::ok) == value.to_const()
+        }
+        (PatKind::Tuple(pats), Value::Tuple(values)) => pats
+            .iter()
+            .zip(values)
+            .all(|(pat, value)| match_pat(pat, value, bindings)),
+        (PatKind::Ctor(name, pats), Value::Variant(ctor, values)) => {
+            ctor.name() == name.as_str()
+                && pats
+                    .iter()
+                    .zip(values)
+                    .all(|(pat, value)| match_pat(pat, value, bindings))
+        }
+        _ => false,
+    }
+}
@@ -0,0 +1,13 @@
+//! A tree-walking interpreter for Calypso.
+//!
+//! The interpreter evaluates the HIR once it has been resolved and type
+//! checked, and is the reference for the language's runtime semantics. The
+//! primitive operations are shared with constant evaluation (see
+//! [`calypso_typeck::consteval`]), so integer arithmetic is always checked:
+//! an overflow, a division by zero or a negative exponent stops the program
+//! with a runtime error, reported as a diagnostic on the failing expression.
+#![doc(html_root_url = "https://calypso-lang.github.io/rustdoc/calypso_interp/index.html")]
+#![warn(clippy::pedantic)]
+
+pub mod eval;
+pub mod value;
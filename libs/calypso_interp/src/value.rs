@@ -0,0 +1,129 @@
+//! Runtime values.
+
+use std::{
+    cell::RefCell,
+    collections::HashMap,
+    fmt::{self, Display},
+    rc::Rc,
+};
+
+use calypso_ast::node::NodeId;
+use calypso_hir::{
+    builtin::Builtin,
+    expr::{Expr, Param},
+};
+use calypso_typeck::consteval::Const;
+
+/// A value computed by the interpreter.
+#[derive(Clone, Debug)]
+pub enum Value<'hir> {
+    Uint(u64),
+    Sint(i64),
+    Float(f64),
+    Bool(bool),
+    /// A tuple. The empty tuple is the unit value.
+    Tuple(Vec<Value<'hir>>),
+    /// A function
+    Fn(Rc<Closure<'hir>>),
+    /// A built-in function, e.g. the constructor `Some` or `arg_count`
+    Builtin(Builtin),
+    /// A value built with a built-in constructor, along with its fields
+    Variant(Builtin, Vec<Value<'hir>>),
+    /// The program arguments passed to `main`
+    Args(Rc<[String]>),
+}
+
+impl Value<'_> {
+    /// The unit value, `()`
+    pub const UNIT: Self = Self::Tuple(Vec::new());
+
+    /// Convert the value to a constant, if it does not contain a function, a
+    /// constructed value or the program arguments.
+    /// Primitive operations are evaluated on constants, so that the
+    /// interpreter and constant evaluation agree.
+    #[must_use]
+    pub fn to_const(&self) -> Option<Const> {
+        let value = match self {
+            Self::Uint(int) => Const::Uint(*int),
+            Self::Sint(int) => Const::Sint(*int),
+            Self::Float(float) => Const::Float(*float),
+            Self::Bool(b) => Const::Bool(*b),
+            Self::Tuple(values) => {
+                Const::Tuple(values.iter().map(Self::to_const).collect::<Option<_>>()?)
+            }
+            Self::Fn(_) | Self::Builtin(_) | Self::Variant(..) | Self::Args(_) => return None,
+        };
+        Some(value)
+    }
+
+    /// Returns `true` if the value is the unit value.
+    #[must_use]
+    pub fn is_unit(&self) -> bool {
+        matches!(self, Self::Tuple(values) if values.is_empty())
+    }
+}
+
+impl From<Const> for Value<'_> {
+    fn from(value: Const) -> Self {
+        match value {
+            Const::Uint(int) => Self::Uint(int),
+            Const::Sint(int) => Self::Sint(int),
+            Const::Float(float) => Self::Float(float),
+            Const::Bool(b) => Self::Bool(b),
+            Const::Tuple(values) => Self::Tuple(values.into_iter().map(Self::from).collect()),
+        }
+    }
+}
+
+impl Display for Value<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Tuple(values) => {
+                write!(f, "(")?;
+                for (idx, value) in values.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{value}")?;
+                }
+                if values.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Self::Fn(_) => write!(f, "<fn>"),
+            Self::Builtin(builtin) => write!(f, "{builtin}"),
+            Self::Variant(ctor, fields) if fields.is_empty() => write!(f, "{ctor}"),
+            Self::Variant(ctor, fields) => {
+                write!(f, "{ctor}(")?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{field}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Args(_) => write!(f, "<args>"),
+            value => write!(
+                f,
+                "{}",
+                value.to_const().expect("primitive values are constants")
+            ),
+        }
+    }
+}
+
+/// The variables in scope, by the node IDs of their binding patterns. Each
+/// variable is shared by every closure that captures it, so assignments
+/// are seen by all of them.
+pub type Env<'hir> = HashMap<NodeId, Rc<RefCell<Value<'hir>>>>;
+
+/// A function, along with the variables that were in scope where it was
+/// created.
+#[derive(Debug)]
+pub struct Closure<'hir> {
+    pub params: &'hir [Param],
+    pub body: &'hir Expr,
+    pub env: Env<'hir>,
+}
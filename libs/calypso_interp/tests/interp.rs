@@ -0,0 +1,179 @@
+use calypso_interp::eval::{interpret, interpret_main};
+use calypso_test_support::{check_str, Checked};
+use calypso_typeck::entry::find_main;
+
+/// Run `src`, returning the value of its last expression, or the rendered
+/// runtime error.
+fn run(src: &str) -> Result<String, String> {
+    let Checked {
+        mut grcx,
+        exprs,
+        res,
+        results,
+    } = check_str(src);
+    assert!(grcx.errors().is_empty(), "type checking {src:?}");
+    match interpret(&mut grcx, 0, &exprs, &res, &results) {
+        Some(value) => Ok(value.to_string()),
+        None => Err(format!("{:?}", grcx.errors()[0])),
+    }
+}
+
+#[test]
+fn arithmetic() {
+    assert_eq!(run("1 + 2 * 3 - 4").unwrap(), "3");
+    assert_eq!(run("3 / 2; 7s % -2; -7s / 2").unwrap(), "-3");
+    assert_eq!(run("2 ** 10 | 1").unwrap(), "1025");
+    assert_eq!(
+        run("(3.0 / 2.0, !5u, 5 ^ 9)").unwrap(),
+        "(1.5, 18446744073709551610, 12)"
+    );
+    assert_eq!(run("-9223372036854775808").unwrap(), "-9223372036854775808");
+}
+
+#[test]
+fn bindings_and_control_flow() {
+    assert_eq!(
+        run("let mut x = 1 in do x = x + 1; let y = x * 10 in y + x end").unwrap(),
+        "22"
+    );
+    assert_eq!(
+        run("let a = 1 in case (a, a > 0) do (0, _) -> 0, (n, true) -> n + 100, _ -> 1 end")
+            .unwrap(),
+        "101"
+    );
+    assert_eq!(
+        run("let mut x = 2s in case (-x, -x * 3, 1.5 - 3.0) do (-2, -6, -1.5) -> 1, _ -> 2 end")
+            .unwrap(),
+        "1"
+    );
+    assert_eq!(
+        run("let mut x = -9223372036854775807 - 1 in case x do -9223372036854775808 -> 1, _ -> 2 end")
+            .unwrap(),
+        "1"
+    );
+    // `&&` and `||` short-circuit, so the division is never evaluated.
+    assert_eq!(
+        run("let mut x = 0 in false && 1 / x == 1 || true").unwrap(),
+        "true"
+    );
+}
+
+#[test]
+fn functions() {
+    assert_eq!(
+        run("fn inc(x) -> x + 1; fn twice(f, x) -> f(f(x)); twice(inc, 1)").unwrap(),
+        "3"
+    );
+    // Function items can call each other, and capture the variables in
+    // scope.
+    assert_eq!(
+        run("let mut calls = 0 in do
+            fn even(n) -> do calls = calls + 1; case n do 0 -> true, _ -> odd(n - 1) end end;
+            fn odd(n) -> case n do 0 -> false, _ -> even(n - 1) end;
+            (even(7), calls)
+        end")
+        .unwrap(),
+        "(false, 4)"
+    );
+    assert_eq!(
+        run("with(T) fn swap(p: (T, T)): (T, T) -> case p do (a, b) -> (b, a) end; swap((1, 2))")
+            .unwrap(),
+        "(2, 1)"
+    );
+}
+
+#[test]
+fn builtins() {
+    assert_eq!(
+        run("(Some(1), None, Ok(true), Err(()))").unwrap(),
+        "(Some(1), None, Ok(true), Err(()))"
+    );
+    assert_eq!(run("Some").unwrap(), "Some");
+    assert_eq!(
+        run(
+            "fn get(o) -> case o do Some(Ok(n)) -> n, Some(Err(_)) -> 1, None -> 0 end;
+            (get(Some(Ok(5))), get(Some(Err(true))), get(None))"
+        )
+        .unwrap(),
+        "(5, 1, 0)"
+    );
+}
+
+#[test]
+fn runtime_errors() {
+    let err = run("let mut x = 0 in 1 / x").unwrap_err();
+    assert!(err.contains("Runtime error: division by zero."));
+    assert!(err.contains("attempt to divide `1` by zero"));
+
+    let err = run("let mut x = 255u in x = x * x * x * x * x * x * x * x * x").unwrap_err();
+    assert!(err.contains("Runtime error: arithmetic overflow."));
+
+    let err = run("let mut x = -1s in 2s ** x").unwrap_err();
+    assert!(err.contains("Runtime error: negative exponent."));
+}
+
+#[test]
+fn loops() {
+    assert_eq!(run("loop do break true end").unwrap(), "true");
+    assert_eq!(
+        run("let mut i = 0 in let mut sum = 0 in loop do \
+             case i == 5 do true -> break sum, false -> () end; \
+             sum = sum + i; i = i + 1 \
+             end")
+        .unwrap(),
+        "10"
+    );
+    // `break` exits the innermost loop, and has the type `()` if it has no
+    // value.
+    assert_eq!(
+        run("let mut n = 0 in do loop do loop do break end; n = n + 1; \
+             case n do 3 -> break, _ -> () end end; n end")
+        .unwrap(),
+        "3"
+    );
+}
+
+#[test]
+fn main_function() {
+    let run_main = |src: &str, args: &[&str]| {
+        let Checked {
+            mut grcx,
+            exprs,
+            res,
+            results,
+        } = check_str(src);
+        assert!(grcx.errors().is_empty(), "type checking {src:?}");
+        let main = find_main(&exprs).expect("the program has a `main` function");
+        let args = args.iter().map(ToString::to_string).collect::<Vec<_>>();
+        interpret_main(&mut grcx, 0, &exprs, main, &args, &res, &results)
+            .ok_or_else(|| format!("{:?}", grcx.errors()[0]))
+    };
+
+    let src = "fn main(args: Args) -> case arg_uint(args, 0) do \
+               Some(n) -> n + arg_count(args), None -> 100 end";
+    assert_eq!(run_main(src, &["7", "x"]).unwrap(), 9);
+    assert_eq!(run_main(src, &["x"]).unwrap(), 100);
+    assert_eq!(run_main(src, &[]).unwrap(), 100);
+    assert_eq!(run_main("fn main(args) -> ()", &[]).unwrap(), 0);
+    // The rest of the program is run before `main` is called.
+    let err = run_main("fn main(args) -> (); let mut x = 0 in 1 / x", &[]).unwrap_err();
+    assert!(err.contains("Runtime error: division by zero."));
+
+    let err = run_main("fn main(args) -> -1s", &[]).unwrap_err();
+    assert!(err.contains("Runtime error: exit status out of range."));
+    assert!(err.contains("`main` returned `-1`, but exit statuses are from 0 to 255"));
+    let err = run_main("fn main(args) -> args", &[]).unwrap_err();
+    assert!(err.contains("Runtime error: invalid exit status."));
+    assert!(err.contains("`main` returned `<args>`, but it must return an integer or `()`"));
+}
+
+#[test]
+fn functions_sharing_mutable_state() {
+    assert_eq!(
+        run("let mut r = None in do fn set(v) -> r = Some(v); \
+             fn get(d) -> case r do Some(x) -> x, None -> d end; \
+             let a = get(1) in do set(5); a + get(1) end end")
+        .unwrap(),
+        "6"
+    );
+}
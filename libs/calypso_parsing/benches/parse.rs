@@ -0,0 +1,267 @@
+//! Measure parsing into the arena-allocated AST, and walking it with a
+//! [`Visitor`], on a large generated input.
+//!
+//! The AST used to own its children through `Box`es and `Vec`s. To keep
+//! comparing the two without maintaining a boxed copy of every node type,
+//! the shape of the parsed tree is rebuilt both ways: as a tree where every
+//! node is boxed, and as a tree allocated in a `typed_arena::Arena` the way
+//! the parser allocates the AST. Building and walking the two trees with the
+//! same code shows the cost of the allocations and the pointer chasing
+//! alone.
+//!
+//! Run with `cargo bench -p calypso_parsing`. When run without `--bench`
+//! (e.g. by `cargo test --all-targets`), a much smaller input is used so that
+//! this only acts as a smoke test.
+
+use std::fmt::Write;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use calypso_ast::{
+    arena::Arena,
+    expr::Expr,
+    node::NodeIdGen,
+    pat::Pat,
+    traverse::{self, Visitor},
+    ty::Ty,
+};
+use calypso_base::{
+    span::{Span, Spanned},
+    ui::{termcolor::ColorChoice, Emitters},
+};
+use calypso_common::{gcx::GlobalCtxt, parking_lot::RwLock};
+use calypso_diagnostic::{diagnostic::SourceMgr, prelude::*, report::GlobalReportingCtxt};
+use calypso_parsing::{
+    lexer::{self, Token},
+    parser::grammar::ExprsParser,
+};
+
+fn generate(n: usize) -> String {
+    let mut src = String::new();
+    for i in 0..n {
+        if i != 0 {
+            src.push_str(";\n");
+        }
+        write!(
+            src,
+            "@allow(unused_mut) let mut x{i}: [(uint, [sint; 4]); 8] = (a{i} + {i} * b) - -c ** 2 ** d in \
+             do x{i}; !flag && x{i} <= 10 || (y | z) ^ 0x{i:x}u; let y = x{i} << 2 in y; \
+             case (x{i}, flag) do (0, _) -> (), (n, true) -> (n,), _ -> (x{i}, 1) end end"
+        )
+        .unwrap();
+    }
+    src
+}
+
+#[derive(Default)]
+struct Counter(usize);
+
+impl Visitor for Counter {
+    fn visit_expr(&mut self, src: &str, expr: Spanned<&Expr>) -> CalResult<()> {
+        self.0 += 1;
+        traverse::walk_expr(self, src, expr)
+    }
+
+    fn visit_ty(&mut self, src: &str, ty: Spanned<&Ty>) -> CalResult<()> {
+        self.0 += 1;
+        traverse::walk_ty(self, src, ty)
+    }
+}
+
+/// Records the shape of a tree in pre-order: the span of each node, and
+/// the number of children that it has.
+#[derive(Default)]
+struct Shape {
+    nodes: Vec<(Span, usize)>,
+    /// The number of children seen so far of each node being walked
+    children: Vec<usize>,
+}
+
+impl Shape {
+    fn node(&mut self, span: Span, walk: impl FnOnce(&mut Self) -> CalResult<()>) -> CalResult<()> {
+        if let Some(parent) = self.children.last_mut() {
+            *parent += 1;
+        }
+        let idx = self.nodes.len();
+        self.nodes.push((span, 0));
+        self.children.push(0);
+        walk(self)?;
+        self.nodes[idx].1 = self.children.pop().unwrap();
+        Ok(())
+    }
+}
+
+impl Visitor for Shape {
+    fn visit_expr(&mut self, src: &str, expr: Spanned<&Expr>) -> CalResult<()> {
+        self.node(expr.span(), |this| traverse::walk_expr(this, src, expr))
+    }
+
+    fn visit_ty(&mut self, src: &str, ty: Spanned<&Ty>) -> CalResult<()> {
+        self.node(ty.span(), |this| traverse::walk_ty(this, src, ty))
+    }
+
+    fn visit_pat(&mut self, src: &str, pat: Spanned<&Pat>) -> CalResult<()> {
+        self.node(pat.span(), |this| traverse::walk_pat(this, src, pat))
+    }
+}
+
+/// A node of a tree where every node is boxed, like the AST used to be.
+struct BoxedNode {
+    span: Span,
+    // Every node is its own allocation, as in the boxed AST.
+    #[allow(clippy::vec_box)]
+    children: Vec<Box<BoxedNode>>,
+}
+
+fn build_boxed<'a>(shape: &mut impl Iterator<Item = &'a (Span, usize)>) -> BoxedNode {
+    let &(span, children) = shape.next().unwrap();
+    BoxedNode {
+        span,
+        children: (0..children)
+            .map(|_| Box::new(build_boxed(shape)))
+            .collect(),
+    }
+}
+
+fn walk_boxed(node: &BoxedNode) -> u64 {
+    u64::from(node.span.lo())
+        + node
+            .children
+            .iter()
+            .map(|child| walk_boxed(child))
+            .sum::<u64>()
+}
+
+/// A node of a tree allocated in an arena, like the AST is now.
+struct ArenaNode<'a> {
+    span: Span,
+    children: &'a [ArenaNode<'a>],
+}
+
+/// Build a node in `arena`, using `scratch` to collect the children of each
+/// node before they are allocated contiguously, as the parser does.
+fn build_arena<'a, 's>(
+    arena: &'a typed_arena::Arena<ArenaNode<'a>>,
+    scratch: &mut Vec<ArenaNode<'a>>,
+    shape: &mut impl Iterator<Item = &'s (Span, usize)>,
+) -> ArenaNode<'a> {
+    let &(span, children) = shape.next().unwrap();
+    let start = scratch.len();
+    for _ in 0..children {
+        let child = build_arena(arena, scratch, shape);
+        scratch.push(child);
+    }
+    ArenaNode {
+        span,
+        children: arena.alloc_extend(scratch.drain(start..)),
+    }
+}
+
+fn walk_arena(node: &ArenaNode) -> u64 {
+    u64::from(node.span.lo()) + node.children.iter().map(walk_arena).sum::<u64>()
+}
+
+fn parse<'ast>(
+    gcx: &Arc<GlobalCtxt>,
+    src: &str,
+    file_id: usize,
+    arena: &'ast Arena<'ast>,
+) -> Vec<Spanned<Expr<'ast>>> {
+    let tokens = lexer::tokens(src, file_id, Arc::clone(gcx)).filter_map(|x| {
+        if matches!(x.value().0, Token::Comment(_)) {
+            None
+        } else {
+            Some((x.span().lo(), x.value_owned().0, x.span().hi()))
+        }
+    });
+    ExprsParser::new()
+        .parse(file_id, &NodeIdGen::new(), arena, tokens)
+        .expect("generated input should parse")
+}
+
+fn time<T>(iters: u32, mut f: impl FnMut() -> T) -> Duration {
+    let start = Instant::now();
+    for _ in 0..iters {
+        std::hint::black_box(f());
+    }
+    start.elapsed() / iters
+}
+
+fn main() {
+    let full = std::env::args().any(|arg| arg == "--bench");
+    let (n, iters) = if full { (20_000, 10) } else { (10, 1) };
+
+    let gcx = Arc::new(GlobalCtxt {
+        emit: RwLock::new(Emitters::new(ColorChoice::Never, ColorChoice::Never)),
+        grcx: RwLock::new(GlobalReportingCtxt::new()),
+        sourcemgr: RwLock::new(SourceMgr::new()),
+    });
+    let src = generate(n);
+    let file_id = gcx
+        .sourcemgr
+        .write()
+        .add("<bench>".to_string(), src.clone());
+
+    let parse_time = time(iters, || parse(&gcx, &src, file_id, &Arena::new()).len());
+
+    let arena = Arena::new();
+    let exprs = parse(&gcx, &src, file_id, &arena);
+
+    let walk_time = time(iters, || {
+        let mut counter = Counter::default();
+        for expr in &exprs {
+            counter.visit_expr(&src, expr.as_ref()).unwrap();
+        }
+        counter.0
+    });
+
+    let mut counter = Counter::default();
+    for expr in &exprs {
+        counter.visit_expr(&src, expr.as_ref()).unwrap();
+    }
+    println!(
+        "input: {} bytes, {} top-level expressions, {} nodes",
+        src.len(),
+        exprs.len(),
+        counter.0
+    );
+    println!("parse: {parse_time:>12?}");
+    println!("walk:  {walk_time:>12?}");
+
+    let mut shape = Shape::default();
+    for expr in &exprs {
+        shape.visit_expr(&src, expr.as_ref()).unwrap();
+    }
+    let roots = exprs.len();
+    let boxed = || {
+        let mut nodes = shape.nodes.iter();
+        (0..roots)
+            .map(|_| build_boxed(&mut nodes))
+            .collect::<Vec<_>>()
+    };
+    let build_boxed_time = time(iters, || boxed().len());
+    let build_arena_time = time(iters, || {
+        let arena = typed_arena::Arena::new();
+        let mut scratch = Vec::new();
+        let mut nodes = shape.nodes.iter();
+        (0..roots)
+            .map(|_| build_arena(&arena, &mut scratch, &mut nodes))
+            .count()
+    });
+
+    let boxed = boxed();
+    let arena = typed_arena::Arena::new();
+    let mut scratch = Vec::new();
+    let mut nodes = shape.nodes.iter();
+    let arena_roots = (0..roots)
+        .map(|_| build_arena(&arena, &mut scratch, &mut nodes))
+        .collect::<Vec<_>>();
+    let walk_boxed_time = time(iters, || boxed.iter().map(walk_boxed).sum::<u64>());
+    let walk_arena_time = time(iters, || arena_roots.iter().map(walk_arena).sum::<u64>());
+
+    println!("shape: {} nodes", shape.nodes.len());
+    println!("build (boxed): {build_boxed_time:>12?}");
+    println!("build (arena): {build_arena_time:>12?}");
+    println!("walk (boxed):  {walk_boxed_time:>12?}");
+    println!("walk (arena):  {walk_arena_time:>12?}");
+}
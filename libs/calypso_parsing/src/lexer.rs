@@ -14,6 +14,7 @@ use calypso_diagnostic::prelude::*;
 pub type Lexeme<'lex> = Spanned<(Token, &'lex str)>;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Logos)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 #[logos(extras = (usize, Arc<GlobalCtxt>))]
 pub enum Token {
     #[token("<<=")]
@@ -167,6 +168,7 @@ pub enum Token {
 }
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub enum IdentLike {
     Ident(Symbol),
     Keyword(Keyword),
@@ -182,6 +184,7 @@ pub fn ident(lex: &mut Lexer<Token>) -> IdentLike {
 }
 
 #[derive(Copy, Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize))]
 pub struct CommentProps {
     pub is_doc: bool,
     pub is_inner: bool,
@@ -1,5 +1,147 @@
 // todo(@ThePuzzlemaker: parse|frame):
 //   add span information to AST and parser
 
+use std::sync::Arc;
+
+use lalrpop_util::ParseError;
+
+use calypso_ast::{arena::Arena, expr::Expr, node::NodeIdGen, validate::validate};
+use calypso_base::{
+    span::{Span, Spanned},
+    ui::{termcolor::ColorChoice, Emitters},
+};
+use calypso_common::{gcx::GlobalCtxt, parking_lot::RwLock};
+use calypso_diagnostic::{diagnostic::SourceMgr, prelude::*, report::GlobalReportingCtxt};
+
+use crate::lexer::{self, Token};
+
 #[rustfmt::skip]
 pub mod grammar;
+
+/// Lex and parse a whole source file into a list of expressions.
+///
+/// The resulting AST is checked using [`validate`]. Syntax errors and
+/// invalid ASTs are reported to the global reporting context, in which case
+/// `None` is returned.
+///
+/// # Errors
+///
+/// This function will error if a parser action errors.
+///
+/// # Panics
+///
+/// This function will panic if `file_id` does not refer to a file in the
+/// source manager.
+pub fn parse<'ast>(
+    gcx: &Arc<GlobalCtxt>,
+    file_id: usize,
+    ids: &NodeIdGen,
+    arena: &'ast Arena<'ast>,
+) -> CalResult<Option<Vec<Spanned<Expr<'ast>>>>> {
+    let sourcemgr = gcx.sourcemgr.read();
+    let source = sourcemgr.source(file_id).unwrap();
+    let tokens = lexer::tokens(source, file_id, Arc::clone(gcx)).filter_map(|x| {
+        if matches!(x.value().0, Token::Comment(_)) {
+            None
+        } else {
+            Some((x.span().lo(), x.value_owned().0, x.span().hi()))
+        }
+    });
+
+    match grammar::ExprsParser::new().parse(file_id, ids, arena, tokens) {
+        Ok(exprs) => {
+            let mut grcx_write = gcx.grcx.write();
+            if validate(&mut grcx_write, file_id, &exprs) && grcx_write.errors().is_empty() {
+                Ok(Some(exprs))
+            } else {
+                Ok(None)
+            }
+        }
+        Err(ParseError::User { error }) => Err(error),
+        Err(err) => {
+            report_parse_error(gcx, file_id, err);
+            Ok(None)
+        }
+    }
+}
+
+/// Parse `src` as the only file in a new global context, so its file ID is
+/// 0. This is mostly useful for tests.
+///
+/// # Panics
+///
+/// This function will panic if `src` does not parse, or if its AST is
+/// invalid.
+pub fn parse_str<'ast>(
+    src: &str,
+    ids: &NodeIdGen,
+    arena: &'ast Arena<'ast>,
+) -> Vec<Spanned<Expr<'ast>>> {
+    let gcx = Arc::new(GlobalCtxt {
+        emit: RwLock::new(Emitters::new(ColorChoice::Never, ColorChoice::Never)),
+        grcx: RwLock::new(GlobalReportingCtxt::new()),
+        sourcemgr: RwLock::new(SourceMgr::new()),
+    });
+    let file_id = gcx
+        .sourcemgr
+        .write()
+        .add("<str>".to_string(), src.to_string());
+    match parse(&gcx, file_id, ids, arena) {
+        Ok(Some(exprs)) => exprs,
+        Ok(None) => {
+            let grcx_read = gcx.grcx.read();
+            let errors = grcx_read.fatal().into_iter().chain(grcx_read.errors());
+            panic!("failed to parse {src:?}: {:?}", errors.collect::<Vec<_>>())
+        }
+        Err(err) => panic!("failed to parse {src:?}: {err:?}"),
+    }
+}
+
+fn report_parse_error(gcx: &GlobalCtxt, file_id: usize, err: ParseError<u32, Token, CalError>) {
+    let mut grcx_write = gcx.grcx.write();
+    // The lexer has already reported these.
+    if grcx_write.fatal().is_some() {
+        return;
+    }
+
+    let (span, message, expected) = match err {
+        ParseError::InvalidToken { location } => {
+            (Span::new_shrunk(location), "invalid token", Vec::new())
+        }
+        ParseError::UnrecognizedEOF { location, expected } => (
+            Span::new_shrunk(location),
+            "unexpected end of file",
+            expected,
+        ),
+        ParseError::UnrecognizedToken {
+            token: (_, Token::Error, _),
+            ..
+        } => return,
+        ParseError::UnrecognizedToken {
+            token: (lo, _, hi),
+            expected,
+        } => (Span::new(lo, hi), "unexpected token", expected),
+        ParseError::ExtraToken { token: (lo, _, hi) } => {
+            (Span::new(lo, hi), "unexpected extra token", Vec::new())
+        }
+        ParseError::User { .. } => unreachable!(),
+    };
+
+    grcx_write.report_syncd(
+        EnsembleBuilder::new()
+            .error(|b| {
+                let b = b.code("E0001").short(err!(E0001)).label(
+                    LabelStyle::Primary,
+                    Some(message),
+                    file_id,
+                    span,
+                );
+                if expected.is_empty() {
+                    b
+                } else {
+                    b.note(format!("expected one of: {}", expected.join(", ")))
+                }
+            })
+            .build(),
+    );
+}
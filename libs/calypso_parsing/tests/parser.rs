@@ -0,0 +1,123 @@
+use calypso_ast::{arena::Arena, node::NodeIdGen, sexpr, spanless::SpanlessEq};
+use calypso_parsing::parser;
+
+/// Parse `src` and check that it is structurally equal to the S-expression
+/// fixture `expected`. Parsing also checks that the AST is valid.
+fn check(src: &str, expected: &str) {
+    let arena = Arena::new();
+    let parsed = parser::parse_str(src, &NodeIdGen::new(), &arena);
+    let expected_exprs = sexpr::read_exprs(expected, &NodeIdGen::new(), &arena).unwrap();
+
+    assert!(
+        SpanlessEq::new(src, expected).eq_exprs(&parsed, &expected_exprs),
+        "{src:?} did not parse as {expected:?}:\n{parsed:#?}"
+    );
+}
+
+#[test]
+fn precedence_and_associativity() {
+    check("1 + 2 * 3", "(+ 1 (* 2 3))");
+    check("1 - 2 - 3", "(- (- 1 2) 3)");
+    check("2 ** 3 ** 4", "(** 2 (** 3 4))");
+    check("-a ** 2", "(** (- a) 2)");
+    check("a || b && c == d << 1", "(|| a (&& b (== c (<< d 1))))");
+    check("(1 + 2) * 3", "(* (+ 1 2) 3)");
+}
+
+#[test]
+fn let_and_blocks() {
+    check(
+        "let mut x: [(uint, sint); 3] = 0x10u in do x; !true end",
+        "(let mut x (ty (def-arr (tuple uint sint) 3)) 0x10u in (block x (! true)))",
+    );
+    check("let y = 1.5 in y; z", "(let y 1.5 in y) z");
+    check(
+        "let z: Result[Option[T], [E; 2]] = z in z",
+        "(let z (ty (apply Result (apply Option T) (def-arr E 2))) z in z)",
+    );
+}
+
+#[test]
+fn assignment() {
+    check("x = y = 1 + 2", "(assign x (assign y (+ 1 2)))");
+    check(
+        "let mut x = 1 in x = let y = 2 in y",
+        "(let mut x 1 in (assign x (let y 2 in y)))",
+    );
+    check("(x = 1) * 2", "(* (assign x 1) 2)");
+}
+
+#[test]
+fn tuples_and_case() {
+    check("()", "(tuple)");
+    check("(x,)", "(tuple x)");
+    check("(1, (2, 3),)", "(tuple 1 (tuple 2 3))");
+    check(
+        "case (x, y) do (0, _) -> a, (mut n, (true,)) -> n, _ -> () end",
+        "(case (tuple x y) ((tuple 0 _) a) ((tuple (mut n) (tuple true)) n) (_ (tuple)))",
+    );
+    check(
+        "case b do true -> let c = 1 in c, false -> case c do ((x)) -> x end, end",
+        "(case b (true (let c 1 in c)) (false (case c (x x))))",
+    );
+    check(
+        "case x do -1 -> 1, (- 0x2s, -1.5) -> 2, _ -> -3 end",
+        "(case x ((- 1) 1) ((tuple (- 0x2s) (- 1.5)) 2) (_ (- 3)))",
+    );
+}
+
+#[test]
+fn attributes() {
+    check(
+        "@allow(unused_mut) @deny(a, b,) let mut x = 1 in x + 1",
+        "(attrs (allow unused_mut) (deny a b) (let mut x 1 in (+ x 1)))",
+    );
+    check("@warn() x = 1", "(attrs (warn) (assign x 1))");
+    check(
+        "let x = 1 in @allow(a) x; (@allow(b) y) * 2",
+        "(let x 1 in (attrs (allow a) x))\n(* (attrs (allow b) y) 2)",
+    );
+}
+
+#[test]
+fn calls() {
+    check("f()", "(call f)");
+    check("f(1, g(x),)(y)", "(call (call f 1 (call g x)) y)");
+    check("-f(x) ** 2", "(** (- (call f x)) 2)");
+    check("(f + g)(x)", "(call (+ f g) x)");
+    check(
+        "case o do Some((a, None)) -> a, Err(e,) -> e end",
+        "(case o ((ctor Some (tuple a None)) a) ((ctor Err e) e))",
+    );
+}
+
+#[test]
+fn fn_items() {
+    check("fn f() -> 1", "(fn f (params) 1)");
+    check(
+        "with(T, U,) fn f(mut x: T, y): (T, U) -> let z = x in (z, y)",
+        "(fn f (with T U) (params (mut x (ty T)) y) (ty (tuple T U)) (let z x in (tuple z y)))",
+    );
+    check(
+        "fn f(x) -> x; do fn g() -> f(1); g() end",
+        "(fn f (params x) x)\n(block (fn g (params) (call f 1)) (call g))",
+    );
+}
+
+#[test]
+fn loops() {
+    check(
+        "loop do break; break x + 1 end",
+        "(loop (block (break) (break (+ x 1))))",
+    );
+    check(
+        "x = break let y = 1 in y",
+        "(assign x (break (let y 1 in y)))",
+    );
+    // In the scrutinee of a `case`, a `do` after `break` starts its value.
+    check("case (break) do _ -> 1 end", "(case (break) (_ 1))");
+    check(
+        "case break do x end do _ -> 1 end",
+        "(case (break (block x)) (_ 1))",
+    );
+}
@@ -0,0 +1,117 @@
+//! Shared fixtures for the tests of Calypso's sub-libraries.
+//!
+//! Most tests start from a source string and run it through some prefix of
+//! the front end: parsing, lowering to HIR, name resolution and type
+//! checking. The functions here run those passes in the same order as
+//! `calypso`'s driver does, so the tests only have to write the part that
+//! they are about. The source is always file 0.
+#![doc(html_root_url = "https://calypso-lang.github.io/rustdoc/calypso_test_support/index.html")]
+#![warn(clippy::pedantic)]
+
+use calypso_ast::{arena::Arena, expr as ast, node::NodeIdGen};
+use calypso_base::span::Spanned;
+use calypso_diagnostic::{diagnostic::EnsembleDiagnostic, report::GlobalReportingCtxt};
+use calypso_hir::{
+    expr::Expr,
+    lower::lower,
+    resolve::{resolve, Resolutions},
+};
+use calypso_parsing::parser;
+use calypso_typeck::check::{typeck, TypeckResults};
+
+/// A program that has been lowered to HIR and resolved.
+pub struct Resolved {
+    /// The diagnostics reported so far.
+    pub grcx: GlobalReportingCtxt,
+    /// The top-level expressions.
+    pub exprs: Vec<Expr>,
+    /// The resolutions of the names in `exprs`.
+    pub res: Resolutions,
+}
+
+/// A program that has been lowered to HIR, resolved and type checked.
+pub struct Checked {
+    /// The diagnostics reported so far.
+    pub grcx: GlobalReportingCtxt,
+    /// The top-level expressions.
+    pub exprs: Vec<Expr>,
+    /// The resolutions of the names in `exprs`.
+    pub res: Resolutions,
+    /// The types of the expressions in `exprs`.
+    pub results: TypeckResults,
+}
+
+/// Parse and lower `src`.
+///
+/// # Panics
+///
+/// This function will panic if `src` does not parse.
+#[must_use]
+pub fn lower_str(src: &str) -> Vec<Expr> {
+    let ids = NodeIdGen::new();
+    let arena = Arena::new();
+    let exprs = parser::parse_str(src, &ids, &arena);
+    lower(src, &ids, &exprs)
+}
+
+/// Parse, lower and resolve `src`.
+///
+/// # Panics
+///
+/// This function will panic if `src` does not parse.
+#[must_use]
+pub fn resolve_str(src: &str) -> Resolved {
+    resolve_str_with(src, GlobalReportingCtxt::new(), |_, _| {})
+}
+
+/// Parse, lower and resolve `src`, reporting to `grcx`. `check_ast` is
+/// called on the AST before it is lowered, for the passes that run on the
+/// AST.
+///
+/// # Panics
+///
+/// This function will panic if `src` does not parse.
+pub fn resolve_str_with(
+    src: &str,
+    mut grcx: GlobalReportingCtxt,
+    check_ast: impl for<'ast> FnOnce(&mut GlobalReportingCtxt, &[Spanned<ast::Expr<'ast>>]),
+) -> Resolved {
+    let ids = NodeIdGen::new();
+    let arena = Arena::new();
+    let exprs = parser::parse_str(src, &ids, &arena);
+    check_ast(&mut grcx, &exprs);
+    let exprs = lower(src, &ids, &exprs);
+    let res = resolve(&mut grcx, 0, &exprs);
+    Resolved { grcx, exprs, res }
+}
+
+/// Parse, lower, resolve and type check `src`.
+///
+/// # Panics
+///
+/// This function will panic if `src` does not parse.
+#[must_use]
+pub fn check_str(src: &str) -> Checked {
+    check_hir(lower_str(src))
+}
+
+/// Resolve and type check `exprs`, for HIR that was not lowered from source.
+#[must_use]
+pub fn check_hir(exprs: Vec<Expr>) -> Checked {
+    let mut grcx = GlobalReportingCtxt::new();
+    let res = resolve(&mut grcx, 0, &exprs);
+    let results = typeck(&mut grcx, 0, &exprs, &res);
+    Checked {
+        grcx,
+        exprs,
+        res,
+        results,
+    }
+}
+
+/// Render each of `diagnostics` with its message, labels and notes, for
+/// matching against in tests.
+#[must_use]
+pub fn render(diagnostics: &[EnsembleDiagnostic]) -> Vec<String> {
+    diagnostics.iter().map(|diag| format!("{diag:?}")).collect()
+}
@@ -0,0 +1,831 @@
+//! Type checking and inference of expressions.
+//!
+//! Types are inferred using unification, in the style of Hindley-Milner.
+//! Every expression and pattern is given a type, which may be an inference
+//! variable if it is not yet known. Whenever two types have to be equal,
+//! they are unified. Any variables that are still unknown once an expression
+//! has been checked are defaulted (see [`InferCtxt::default_vars`]).
+//!
+//! Unsuffixed integer literals have the type `{integer}` (i.e. `uint` or
+//! `sint`), which defaults to `uint`. Operators are typed as follows, where
+//! both operands and the result have the same type:
+//!
+//! | Operators              | Operand types           | Result |
+//! |------------------------|-------------------------|--------|
+//! | `+ - * / % **`         | `uint`, `sint`, `float` | same   |
+//! | `& \| ^`, unary `!`    | `uint`, `sint`, `bool`  | same   |
+//! | `<< >>`                | `uint`, `sint`          | same   |
+//! | unary `-`              | `sint`, `float`         | same   |
+//! | `< > <= >=`            | `uint`, `sint`, `float` | `bool` |
+//! | `== !=`                | any                     | `bool` |
+//!
+//! The function items of a sequence of expressions are checked together,
+//! before the rest of the sequence, as they may refer to each other. A
+//! function whose parameter and return types are all annotated is generic
+//! over the type parameters in its `with` clause, and can be used at
+//! different types everywhere, including in its own body. Other functions
+//! are also generalized over the types that were not inferred, but can only
+//! be used at one type within the functions of their sequence. Within a
+//! function, its type parameters are placeholders that are only equal to
+//! themselves.
+//!
+//! The built-ins have the following types:
+//!
+//! | Built-in    | Type                                |
+//! |-------------|-------------------------------------|
+//! | `Some`      | `with(T) fn(T) -> Option[T]`        |
+//! | `None`      | `with(T) Option[T]`                 |
+//! | `Ok`        | `with(T, E) fn(T) -> Result[T, E]`  |
+//! | `Err`       | `with(T, E) fn(E) -> Result[T, E]`  |
+//! | `arg_count` | `fn(Args) -> uint`                  |
+//! | `arg_uint`  | `fn(Args, uint) -> Option[uint]`    |
+
+use std::{collections::HashMap, mem};
+
+use calypso_ast::{
+    expr::{BinOpKind, Mutability, Numeral, Radix, Suffix, UnOpKind},
+    node::NodeId,
+};
+use calypso_base::{span::Span, symbol::Symbol};
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+use calypso_hir::{
+    expr::{Arm, Expr, ExprKind, FnDef, Lit, Prim},
+    pat::{Pat, PatKind},
+    resolve::Resolutions,
+    ty::{Ty, TyKind},
+};
+
+use crate::{
+    consteval::eval_array_len,
+    infer::InferCtxt,
+    ty::{builtin_scheme, Adt, PrimSet, PrimTy, Scheme, Type},
+};
+
+/// The results of type checking.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct TypeckResults {
+    /// Map from the node IDs of expressions, patterns and types to their
+    /// types
+    types: HashMap<NodeId, Type>,
+    /// Map from the node IDs of generalized bindings to their type schemes
+    schemes: HashMap<NodeId, Scheme>,
+}
+
+impl TypeckResults {
+    /// Get the type of the expression, pattern or type `id`.
+    #[must_use]
+    pub fn node_type(&self, id: NodeId) -> Option<&Type> {
+        self.types.get(&id)
+    }
+
+    /// Get the type scheme of the binding pattern `id`, if it was
+    /// generalized. The type of the pattern itself refers to the parameters
+    /// of the scheme.
+    #[must_use]
+    pub fn scheme(&self, id: NodeId) -> Option<&Scheme> {
+        self.schemes.get(&id)
+    }
+
+    /// Iterate over all nodes and their types, in no particular order.
+    pub fn types(&self) -> impl Iterator<Item = (NodeId, &Type)> + '_ {
+        self.types.iter().map(|(&id, ty)| (id, ty))
+    }
+}
+
+/// Type check a sequence of expressions, reporting type errors to `grcx` as
+/// synchronizable errors.
+pub fn typeck(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Expr],
+    res: &Resolutions,
+) -> TypeckResults {
+    let mut checker = Checker {
+        grcx,
+        file_id,
+        res,
+        infcx: InferCtxt::new(),
+        types: HashMap::new(),
+        schemes: HashMap::new(),
+        loops: Vec::new(),
+        ty_params: Vec::new(),
+    };
+    checker.check_seq(exprs);
+
+    checker.infcx.default_vars();
+    let types = checker
+        .types
+        .iter()
+        .map(|(&id, ty)| (id, checker.infcx.resolve(ty)))
+        .collect();
+    let schemes = checker
+        .schemes
+        .iter()
+        .map(|(&id, scheme)| {
+            let ty = checker.infcx.resolve(&scheme.ty);
+            (id, Scheme { ty, ..*scheme })
+        })
+        .collect();
+    TypeckResults { types, schemes }
+}
+
+/// The reason that a type is expected.
+#[derive(Copy, Clone, Debug)]
+struct Cause {
+    span: Span,
+    label: &'static str,
+}
+
+impl Cause {
+    fn new(span: Span, label: &'static str) -> Self {
+        Self { span, label }
+    }
+}
+
+struct Checker<'grcx, 'res> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    res: &'res Resolutions,
+    infcx: InferCtxt,
+    /// The types of expressions, patterns and types, which may contain
+    /// inference variables
+    types: HashMap<NodeId, Type>,
+    /// The type schemes of generalized bindings
+    schemes: HashMap<NodeId, Scheme>,
+    /// The enclosing loops, innermost last, along with the type and span of
+    /// the first `break` out of each
+    loops: Vec<Option<(Type, Span)>>,
+    /// The type parameters in scope, innermost last, along with their
+    /// placeholders
+    ty_params: Vec<(Symbol, Type)>,
+}
+
+/// The signature of a function item, as it is known before checking its
+/// body.
+struct FnSig {
+    /// The type parameters of the function, along with their placeholders
+    ty_params: Vec<(Symbol, Type)>,
+    params: Vec<Type>,
+    ret: Type,
+    /// Whether the types of all parameters and the return type are
+    /// annotated
+    annotated: bool,
+}
+
+impl FnSig {
+    fn ty(&self) -> Type {
+        Type::Fn(self.params.clone(), Box::new(self.ret.clone()))
+    }
+
+    fn placeholders(&self) -> Vec<Type> {
+        self.ty_params.iter().map(|(_, ty)| ty.clone()).collect()
+    }
+}
+
+impl Checker<'_, '_> {
+    fn check_expr(&mut self, expr: &Expr) -> Type {
+        let ty = match &expr.kind {
+            ExprKind::Lit(lit) => self.lit_ty(*lit),
+            ExprKind::Var(_) => self.var_ty(expr.id),
+            ExprKind::Prim(_) => unreachable!("primitives are only ever called"),
+            ExprKind::Let(pat, ty, value, body) => self.check_let(pat, ty.as_ref(), value, body),
+            ExprKind::Assign(target, value) => {
+                let target_ty = self.check_expr(target);
+                let value_ty = self.check_expr(value);
+                let binding_span = self
+                    .res
+                    .binding_of(target.id)
+                    .map_or(target.span, |(_, binding)| binding.span);
+                self.expect(
+                    &target_ty,
+                    Cause::new(binding_span, "expected due to the type of this variable"),
+                    &value_ty,
+                    value.span,
+                );
+                value_ty
+            }
+            ExprKind::Lambda(params, body) => {
+                let param_tys = params
+                    .iter()
+                    .map(|param| {
+                        let ty = match &param.ty {
+                            Some(ty) => self.lower_ty(ty),
+                            None => self.infcx.new_var(),
+                        };
+                        self.check_pat(&param.pat, &ty, param.pat.span);
+                        ty
+                    })
+                    .collect();
+                let ret = self.check_expr(body);
+                Type::Fn(param_tys, Box::new(ret))
+            }
+            ExprKind::Call(callee, args) => self.check_call(expr, callee, args),
+            ExprKind::Match(scrutinee, arms) => self.check_match(scrutinee, arms),
+            ExprKind::Tuple(exprs) => {
+                Type::Tuple(exprs.iter().map(|expr| self.check_expr(expr)).collect())
+            }
+            ExprKind::Loop(body) => {
+                self.loops.push(None);
+                self.check_expr(body);
+                let first_break = self.loops.pop().expect("the loop was just pushed");
+                // A loop with no `break` never evaluates to anything.
+                first_break.map_or_else(|| self.infcx.new_var(), |(ty, _)| ty)
+            }
+            ExprKind::Break(value) => self.check_break(expr, value.as_deref()),
+            ExprKind::Block(exprs) => self.check_seq(exprs),
+            // Function items are checked with the rest of their sequence,
+            // see `check_seq`.
+            ExprKind::Fn(_) => Type::UNIT,
+        };
+        self.types.insert(expr.id, ty.clone());
+        ty
+    }
+
+    /// Check a sequence of expressions, returning the type of the last one.
+    fn check_seq(&mut self, exprs: &[Expr]) -> Type {
+        let fns = exprs
+            .iter()
+            .filter_map(|expr| match &expr.kind {
+                ExprKind::Fn(def) => Some(&**def),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        if !fns.is_empty() {
+            self.check_fns(&fns);
+        }
+        exprs
+            .iter()
+            .map(|expr| self.check_expr(expr))
+            .last()
+            .unwrap_or(Type::UNIT)
+    }
+
+    /// Check the function items of a sequence of expressions, which may
+    /// refer to each other.
+    fn check_fns(&mut self, fns: &[&FnDef]) {
+        self.infcx.enter_level();
+        let sigs = fns.iter().map(|def| self.fn_sig(def)).collect::<Vec<_>>();
+        for (def, sig) in fns.iter().zip(&sigs) {
+            if sig.annotated {
+                let scheme = self.infcx.generalize_with(&sig.ty(), &sig.placeholders());
+                self.types.insert(def.name.id, scheme.ty.clone());
+                self.schemes.insert(def.name.id, scheme);
+            } else {
+                self.types.insert(def.name.id, sig.ty());
+            }
+        }
+        for (def, sig) in fns.iter().zip(&sigs) {
+            self.check_fn_body(def, sig);
+        }
+        self.infcx.exit_level();
+
+        for (def, sig) in fns.iter().zip(&sigs) {
+            if !sig.annotated {
+                let scheme = self.infcx.generalize_with(&sig.ty(), &sig.placeholders());
+                self.types.insert(def.name.id, scheme.ty.clone());
+                self.schemes.insert(def.name.id, scheme);
+            }
+        }
+    }
+
+    /// Get the signature of a function item from its annotations, using
+    /// new inference variables for the types that are not annotated.
+    fn fn_sig(&mut self, def: &FnDef) -> FnSig {
+        let ty_params = def
+            .ty_params
+            .iter()
+            .map(|ty_param| (ty_param.name, self.infcx.new_placeholder(ty_param.name)))
+            .collect::<Vec<_>>();
+        let outer = self.ty_params.len();
+        self.ty_params.extend(ty_params.iter().cloned());
+
+        let mut annotated = true;
+        let mut lower = |this: &mut Self, ty: Option<&Ty>| {
+            if let Some(ty) = ty {
+                this.lower_ty(ty)
+            } else {
+                annotated = false;
+                this.infcx.new_var()
+            }
+        };
+        let params = def
+            .params
+            .iter()
+            .map(|param| lower(self, param.ty.as_ref()))
+            .collect();
+        let ret = lower(self, def.ret.as_ref());
+
+        self.ty_params.truncate(outer);
+        FnSig {
+            ty_params,
+            params,
+            ret,
+            annotated,
+        }
+    }
+
+    fn check_fn_body(&mut self, def: &FnDef, sig: &FnSig) {
+        let outer = self.ty_params.len();
+        self.ty_params.extend(sig.ty_params.iter().cloned());
+        // A function's body cannot `break` out of an enclosing loop.
+        let loops = mem::take(&mut self.loops);
+
+        for (param, ty) in def.params.iter().zip(&sig.params) {
+            self.check_pat(&param.pat, ty, param.pat.span);
+        }
+        let body_ty = self.check_expr(&def.body);
+        let cause = match &def.ret {
+            Some(ret) => Cause::new(ret.span, "expected due to this return type"),
+            None => Cause::new(def.name.span, "expected due to the uses of this function"),
+        };
+        self.expect(&sig.ret, cause, &body_ty, def.body.span);
+
+        self.loops = loops;
+        self.ty_params.truncate(outer);
+    }
+
+    /// Check `let <pat> (: <ty>)? = <value> in <body>`. If `pat` is an
+    /// immutable binding and `value` is a syntactic value (see
+    /// [`is_value`]), the binding is generalized.
+    fn check_let(&mut self, pat: &Pat, ty: Option<&Ty>, value: &Expr, body: &Expr) -> Type {
+        self.infcx.enter_level();
+        let value_ty = self.check_expr(value);
+        let ty = match ty {
+            Some(ty) => {
+                let ann_ty = self.lower_ty(ty);
+                self.expect(
+                    &ann_ty,
+                    Cause::new(ty.span, "expected due to this type"),
+                    &value_ty,
+                    value.span,
+                );
+                ann_ty
+            }
+            None => value_ty,
+        };
+        self.infcx.exit_level();
+
+        if matches!(pat.kind, PatKind::Binding(Mutability::Immut, _)) && is_value(value) {
+            let scheme = self.infcx.generalize(&ty);
+            self.types.insert(pat.id, scheme.ty.clone());
+            self.schemes.insert(pat.id, scheme);
+        } else {
+            self.infcx.keep_at_current_level(&ty);
+            self.check_pat(pat, &ty, value.span);
+        }
+        self.check_expr(body)
+    }
+
+    fn check_match(&mut self, scrutinee: &Expr, arms: &[Arm]) -> Type {
+        let scrutinee_ty = self.check_expr(scrutinee);
+        let mut first: Option<(Type, Span)> = None;
+        for arm in arms {
+            self.check_pat(&arm.pat, &scrutinee_ty, scrutinee.span);
+            let body_ty = self.check_expr(&arm.body);
+            match &first {
+                Some((first_ty, first_span)) => self.expect(
+                    first_ty,
+                    Cause::new(*first_span, "expected because of this arm"),
+                    &body_ty,
+                    arm.body.span,
+                ),
+                None => first = Some((body_ty, arm.body.span)),
+            }
+        }
+        // A match with no arms never evaluates to anything.
+        first.map_or_else(|| self.infcx.new_var(), |(ty, _)| ty)
+    }
+
+    fn check_break(&mut self, expr: &Expr, value: Option<&Expr>) -> Type {
+        let value_ty = value.map_or(Type::UNIT, |value| self.check_expr(value));
+        match self.loops.last_mut() {
+            Some(Some((first_ty, first_span))) => {
+                let (first_ty, first_span) = (first_ty.clone(), *first_span);
+                self.expect(
+                    &first_ty,
+                    Cause::new(first_span, "expected because of this `break`"),
+                    &value_ty,
+                    expr.span,
+                );
+            }
+            Some(first @ None) => *first = Some((value_ty, expr.span)),
+            None => self.grcx.report_syncd(
+                EnsembleBuilder::new()
+                    .error(|b| {
+                        b.code("E0021").short(err!(E0021)).label(
+                            LabelStyle::Primary,
+                            Some("cannot `break` outside of a loop"),
+                            self.file_id,
+                            expr.span,
+                        )
+                    })
+                    .build(),
+            ),
+        }
+        // `break` never evaluates to anything.
+        self.infcx.new_var()
+    }
+
+    fn lit_ty(&mut self, lit: Lit) -> Type {
+        match lit {
+            Lit::Bool(_) => Type::Prim(PrimTy::Bool),
+            Lit::Number(Numeral::Integer { suffix: None, .. }, _) => {
+                self.infcx.new_constrained_var(PrimSet::INTEGER)
+            }
+            Lit::Number(
+                Numeral::Integer {
+                    suffix: Some(Suffix::Uint),
+                    ..
+                },
+                _,
+            ) => Type::Prim(PrimTy::Uint),
+            Lit::Number(
+                Numeral::Integer {
+                    suffix: Some(Suffix::Sint),
+                    ..
+                },
+                _,
+            ) => Type::Prim(PrimTy::Sint),
+            Lit::Number(Numeral::Float { .. }, _) => Type::Prim(PrimTy::Float),
+        }
+    }
+
+    fn var_ty(&mut self, id: NodeId) -> Type {
+        if let Some(builtin) = self.res.builtin(id) {
+            return self.infcx.instantiate(&builtin_scheme(builtin));
+        }
+        // Unresolved names have already been reported.
+        let Some(def) = self.res.resolve(id) else {
+            return Type::Error;
+        };
+        if let Some(scheme) = self.schemes.get(&def) {
+            return self.infcx.instantiate(scheme);
+        }
+        self.types.get(&def).cloned().unwrap_or(Type::Error)
+    }
+
+    fn check_call(&mut self, expr: &Expr, callee: &Expr, args: &[Expr]) -> Type {
+        if let ExprKind::Prim(prim) = callee.kind {
+            return self.check_prim_call(prim, callee, args);
+        }
+
+        let callee_ty = self.check_expr(callee);
+        let arg_tys = args
+            .iter()
+            .map(|arg| self.check_expr(arg))
+            .collect::<Vec<_>>();
+        match self.infcx.shallow_resolve(&callee_ty) {
+            Type::Fn(params, ret) if params.len() != args.len() => {
+                self.report_arity(
+                    "E0011",
+                    err!(E0011, params.len(), args.len()),
+                    &format!("expected {}", plural(params.len(), "argument")),
+                    expr.span,
+                );
+                *ret
+            }
+            Type::Fn(params, ret) => {
+                for ((param, arg_ty), arg) in params.iter().zip(&arg_tys).zip(args) {
+                    self.expect(
+                        param,
+                        Cause::new(callee.span, "expected due to the type of this function"),
+                        arg_ty,
+                        arg.span,
+                    );
+                }
+                *ret
+            }
+            _ => {
+                let ret = self.infcx.new_var();
+                self.expect(
+                    &Type::Fn(arg_tys, Box::new(ret.clone())),
+                    Cause::new(expr.span, "expected due to this call"),
+                    &callee_ty,
+                    callee.span,
+                );
+                ret
+            }
+        }
+    }
+
+    /// Check a call of a primitive operation. All operands must have the
+    /// same type, which may be constrained to a set of primitive types.
+    fn check_prim_call(&mut self, prim: Prim, callee: &Expr, args: &[Expr]) -> Type {
+        let (operands, returns_bool) = match prim {
+            Prim::BinOp(op) => match op {
+                BinOpKind::Add
+                | BinOpKind::Subtract
+                | BinOpKind::Multiply
+                | BinOpKind::Divide
+                | BinOpKind::Modulo
+                | BinOpKind::Exponent => (Some(PrimSet::NUMBER), false),
+                BinOpKind::BitOr | BinOpKind::BitXor | BinOpKind::BitAnd => {
+                    (Some(PrimSet::BITWISE), false)
+                }
+                BinOpKind::BitShiftLeft | BinOpKind::BitShiftRight => {
+                    (Some(PrimSet::INTEGER), false)
+                }
+                // These are lowered into matches, but are easy enough to
+                // type anyway.
+                BinOpKind::LogicalOr | BinOpKind::LogicalAnd => {
+                    (Some(PrimSet::of(PrimTy::Bool)), false)
+                }
+                BinOpKind::Equal | BinOpKind::NotEqual => (None, true),
+                BinOpKind::Lt | BinOpKind::Gt | BinOpKind::LtEq | BinOpKind::GtEq => {
+                    (Some(PrimSet::NUMBER), true)
+                }
+            },
+            Prim::UnOp(UnOpKind::Negative) => (Some(PrimSet::SIGNED), false),
+            Prim::UnOp(UnOpKind::UnaryNot) => (Some(PrimSet::BITWISE), false),
+        };
+
+        let operand_ty = match operands {
+            Some(set) => self.infcx.new_constrained_var(set),
+            None => self.infcx.new_var(),
+        };
+        for (idx, arg) in args.iter().enumerate() {
+            let arg_ty = self.check_expr(arg);
+            let cause = match idx {
+                0 => Cause::new(callee.span, "expected due to this operator"),
+                _ => Cause::new(args[0].span, "expected because of this operand"),
+            };
+            self.expect(&operand_ty, cause, &arg_ty, arg.span);
+        }
+
+        let ret = if returns_bool {
+            Type::Prim(PrimTy::Bool)
+        } else {
+            operand_ty.clone()
+        };
+        self.types.insert(
+            callee.id,
+            Type::Fn(vec![operand_ty; args.len()], Box::new(ret.clone())),
+        );
+        ret
+    }
+
+    /// Check a pattern that is matched against a value of type
+    /// `scrutinee_ty`, from `scrutinee_span`.
+    fn check_pat(&mut self, pat: &Pat, scrutinee_ty: &Type, scrutinee_span: Span) {
+        let cause = Cause::new(pat.span, "expected due to this pattern");
+        match &pat.kind {
+            PatKind::Wild | PatKind::Binding(..) => {}
+            PatKind::Lit(lit) => {
+                let lit_ty = self.lit_ty(*lit);
+                self.expect(&lit_ty, cause, scrutinee_ty, scrutinee_span);
+            }
+            PatKind::Neg(lit) => {
+                // Like negation, negative patterns only have signed types.
+                let ty = self.infcx.new_constrained_var(PrimSet::SIGNED);
+                self.expect(&ty, cause, scrutinee_ty, scrutinee_span);
+                let lit_ty = self.lit_ty(*lit);
+                let cause = Cause::new(pat.span, "expected due to this pattern");
+                self.expect(&ty, cause, &lit_ty, pat.span);
+            }
+            PatKind::Tuple(pats) => {
+                let elem_tys = pats
+                    .iter()
+                    .map(|_| self.infcx.new_var())
+                    .collect::<Vec<_>>();
+                self.expect(
+                    &Type::Tuple(elem_tys.clone()),
+                    cause,
+                    scrutinee_ty,
+                    scrutinee_span,
+                );
+                for (pat, elem_ty) in pats.iter().zip(&elem_tys) {
+                    self.check_pat(pat, elem_ty, scrutinee_span);
+                }
+            }
+            PatKind::Ctor(_, pats) => self.check_ctor_pat(pat, pats, scrutinee_ty, scrutinee_span),
+        }
+        self.types.insert(pat.id, scrutinee_ty.clone());
+    }
+
+    fn check_ctor_pat(
+        &mut self,
+        pat: &Pat,
+        pats: &[Pat],
+        scrutinee_ty: &Type,
+        scrutinee_span: Span,
+    ) {
+        // Unresolved constructors have already been reported.
+        let Some(ctor) = self.res.builtin(pat.id) else {
+            for pat in pats {
+                self.check_pat(pat, &Type::Error, scrutinee_span);
+            }
+            return;
+        };
+
+        let (field_tys, ty) = match self.infcx.instantiate(&builtin_scheme(ctor)) {
+            Type::Fn(field_tys, ty) => (field_tys, *ty),
+            ty => (Vec::new(), ty),
+        };
+        let cause = Cause::new(pat.span, "expected due to this pattern");
+        self.expect(&ty, cause, scrutinee_ty, scrutinee_span);
+
+        if field_tys.len() == pats.len() {
+            for (pat, field_ty) in pats.iter().zip(&field_tys) {
+                self.check_pat(pat, field_ty, scrutinee_span);
+            }
+        } else {
+            self.report_arity(
+                "E0020",
+                err!(E0020, ctor, field_tys.len(), pats.len()),
+                &format!("expected {}", plural(field_tys.len(), "field")),
+                pat.span,
+            );
+            for pat in pats {
+                self.check_pat(pat, &Type::Error, scrutinee_span);
+            }
+        }
+    }
+
+    /// Get the type that a type annotation refers to.
+    fn lower_ty(&mut self, ty: &Ty) -> Type {
+        let lowered = match &ty.kind {
+            TyKind::Named(name, args) => {
+                let args = args
+                    .iter()
+                    .map(|arg| self.lower_ty(arg))
+                    .collect::<Vec<_>>();
+                let ty_param = self
+                    .ty_params
+                    .iter()
+                    .rev()
+                    .find(|(param, _)| param == name)
+                    .map(|(_, ty)| ty.clone());
+                let (lowered, expected_args) = if let Some(ty_param) = ty_param {
+                    (ty_param, 0)
+                } else if let Some(prim) = PrimTy::from_name(name.as_str()) {
+                    (Type::Prim(prim), 0)
+                } else if let Some(adt) = Adt::from_name(name.as_str()) {
+                    (Type::Adt(adt, args.clone()), adt.params())
+                } else {
+                    self.report_unknown_ty(*name, ty.span);
+                    (Type::Error, args.len())
+                };
+                if args.len() == expected_args {
+                    lowered
+                } else {
+                    self.report_arity(
+                        "E0010",
+                        err!(E0010, name, expected_args, args.len()),
+                        &format!("expected {}", plural(expected_args, "type argument")),
+                        ty.span,
+                    );
+                    // Primitive types and type parameters are still known
+                    // without their arguments.
+                    match lowered {
+                        Type::Adt(..) => Type::Error,
+                        lowered => lowered,
+                    }
+                }
+            }
+            TyKind::Array(elem, len) => {
+                let elem = Box::new(self.lower_ty(elem));
+                match len.as_deref().map(|len| (eval_array_len(len), len.span)) {
+                    None => Type::Array(elem, None),
+                    Some((Ok(len), _)) => Type::Array(elem, Some(len)),
+                    Some((Err(_), span)) => {
+                        self.report_len_too_large(span);
+                        Type::Error
+                    }
+                }
+            }
+            TyKind::Tuple(tys) => Type::Tuple(tys.iter().map(|ty| self.lower_ty(ty)).collect()),
+        };
+        self.types.insert(ty.id, lowered.clone());
+        lowered
+    }
+
+    /// Unify the type `found`, of the source at `found_span`, with the type
+    /// `expected`, reporting a mismatch if they are incompatible.
+    fn expect(&mut self, expected: &Type, cause: Cause, found: &Type, found_span: Span) {
+        if self.infcx.unify(expected, found).is_ok() {
+            return;
+        }
+
+        let expected = self.infcx.display(expected).to_string();
+        let found = self.infcx.display(found).to_string();
+        let label = format!("expected `{expected}`, found `{found}`");
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    let b = b.code("E0008").short(err!(E0008, expected, found)).label(
+                        LabelStyle::Primary,
+                        Some(&label),
+                        self.file_id,
+                        found_span,
+                    );
+                    if cause.span == found_span {
+                        b
+                    } else {
+                        b.label(
+                            LabelStyle::Secondary,
+                            Some(cause.label),
+                            self.file_id,
+                            cause.span,
+                        )
+                    }
+                })
+                .build(),
+        );
+    }
+
+    fn report_arity(&mut self, code: &'static str, short: String, label: &str, span: Span) {
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code(code).short(short).label(
+                        LabelStyle::Primary,
+                        Some(label),
+                        self.file_id,
+                        span,
+                    )
+                })
+                .build(),
+        );
+    }
+
+    fn report_len_too_large(&mut self, span: Span) {
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code("E0015")
+                        .short(err!(E0015, PrimTy::Uint))
+                        .label(
+                            LabelStyle::Primary,
+                            Some("this array length does not fit in `uint`"),
+                            self.file_id,
+                            span,
+                        )
+                        .note(format!("the range of `uint` is `0..={}`", u64::MAX))
+                })
+                .build(),
+        );
+    }
+
+    fn report_unknown_ty(&mut self, name: Symbol, span: Span) {
+        let name_str = name.as_str();
+        let max_distance = (name_str.chars().count() / 3).max(1);
+        let candidates = self
+            .ty_params
+            .iter()
+            .rev()
+            .map(|(param, _)| param.as_str())
+            .chain(PrimTy::ALL.into_iter().map(PrimTy::name))
+            .chain(Adt::ALL.into_iter().map(Adt::name));
+        let similar = candidates
+            .map(|candidate| (strsim::damerau_levenshtein(candidate, name_str), candidate))
+            .filter(|&(distance, _)| distance <= max_distance)
+            .min_by_key(|&(distance, _)| distance);
+
+        let mut builder = EnsembleBuilder::new().error(|b| {
+            b.code("E0009").short(err!(E0009, name)).label(
+                LabelStyle::Primary,
+                Some("not found in this scope"),
+                self.file_id,
+                span,
+            )
+        });
+        if let Some((_, similar)) = similar {
+            builder = builder.help(|b| {
+                b.short(format!("a type with a similar name exists: `{similar}`"))
+                    .label(LabelStyle::Primary, None, self.file_id, span)
+            });
+        }
+        self.grcx.report_syncd(builder.build());
+    }
+}
+
+/// Check if an expression is a syntactic value, i.e. it can be evaluated
+/// without any effects. Only these can be generalized.
+fn is_value(expr: &Expr) -> bool {
+    match &expr.kind {
+        ExprKind::Lit(_) | ExprKind::Var(_) | ExprKind::Lambda(..) => true,
+        ExprKind::Tuple(exprs) => exprs.iter().all(is_value),
+        _ => false,
+    }
+}
+
+/// Format a count of something, e.g. `1 argument` or `2 arguments`.
+fn plural(count: usize, what: &str) -> String {
+    if count == 1 {
+        format!("{count} {what}")
+    } else {
+        format!("{count} {what}s")
+    }
+}
+
+/// Get the value of an integer literal with the source text `text`. Returns
+/// `None` if it is too large.
+pub(crate) fn int_value(radix: Radix, text: Symbol) -> Option<u128> {
+    let text = text.as_str();
+    let digits = match radix {
+        Radix::None => text,
+        _ => &text[2..],
+    };
+    let digits = digits.trim_end_matches(['u', 's']).replace('_', "");
+    u128::from_str_radix(&digits, radix.radix()).ok()
+}
@@ -0,0 +1,157 @@
+//! Detection of constant conditions.
+//!
+//! A match whose scrutinee is a constant always takes the same arm, so it
+//! can be replaced by that arm's body. As `&&` and `||` are desugared to
+//! matches on their left operand, this also covers e.g. `true && x`. These
+//! are reported with the [`constant_conditions`](lint::CONSTANT_CONDITIONS)
+//! lint.
+//!
+//! Scrutinees that refer to variables are not linted, even if the variable
+//! is bound to a constant, as matching on a named constant is usually
+//! intentional.
+
+use calypso_diagnostic::{diagnostic::Applicability, prelude::*, report::GlobalReportingCtxt};
+use calypso_hir::{
+    expr::{Arm, Expr, ExprKind},
+    pat::{Pat, PatKind},
+    visit::{walk_expr, walk_pat, Visitor},
+};
+
+use crate::consteval::{matches_pat, Const, Consts};
+
+/// Check for matches on constants in a sequence of expressions, parsed
+/// from `src`, whose constants have been evaluated into `consts`.
+pub fn check_constant_conditions(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    src: &str,
+    exprs: &[Expr],
+    consts: &Consts,
+) {
+    let mut checker = ConditionChecker {
+        grcx,
+        file_id,
+        src,
+        consts,
+    };
+    for expr in exprs {
+        checker.visit_expr(expr);
+    }
+}
+
+/// Returns `true` if `expr` refers to any variable.
+fn uses_vars(expr: &Expr) -> bool {
+    struct VarFinder(bool);
+
+    impl Visitor for VarFinder {
+        fn visit_expr(&mut self, expr: &Expr) {
+            if let ExprKind::Var(_) = expr.kind {
+                self.0 = true;
+            }
+            walk_expr(self, expr);
+        }
+    }
+
+    let mut finder = VarFinder(false);
+    finder.visit_expr(expr);
+    finder.0
+}
+
+/// Returns `true` if `pat` binds any variables.
+fn has_bindings(pat: &Pat) -> bool {
+    struct BindingFinder(bool);
+
+    impl Visitor for BindingFinder {
+        fn visit_pat(&mut self, pat: &Pat) {
+            if let PatKind::Binding(..) = pat.kind {
+                self.0 = true;
+            }
+            walk_pat(self, pat);
+        }
+    }
+
+    let mut finder = BindingFinder(false);
+    finder.visit_pat(pat);
+    finder.0
+}
+
+struct ConditionChecker<'grcx, 'a> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    src: &'a str,
+    consts: &'a Consts,
+}
+
+impl ConditionChecker<'_, '_> {
+    /// The source text to replace a match with `body`, the body of the arm
+    /// that it always takes.
+    fn replacement(&self, body: &Expr) -> String {
+        match &body.kind {
+            // Literals may have been introduced by desugaring, in which case
+            // their span is not their source text.
+            ExprKind::Lit(_) => self.consts.get(body.id).map_or_else(
+                || self.src[body.span.into_range()].to_string(),
+                Const::to_string,
+            ),
+            ExprKind::Var(_) | ExprKind::Tuple(_) | ExprKind::Block(_) => {
+                self.src[body.span.into_range()].to_string()
+            }
+            // The body may have a lower precedence than the match did.
+            _ => format!("({})", &self.src[body.span.into_range()]),
+        }
+    }
+
+    fn report(&mut self, expr: &Expr, scrutinee: &Expr, value: &Const, arm: Option<&Arm>) {
+        let always = format!("this condition is always `{value}`");
+        let replacement = arm
+            .filter(|arm| !has_bindings(&arm.pat))
+            .map(|arm| self.replacement(&arm.body));
+        self.grcx.report_lint(
+            &lint::CONSTANT_CONDITIONS,
+            self.file_id,
+            scrutinee.span,
+            |b| {
+                let b = b.short("constant condition").label(
+                    LabelStyle::Primary,
+                    Some(&always),
+                    self.file_id,
+                    scrutinee.span,
+                );
+                match replacement {
+                    Some(replacement) => b.suggestion(
+                        self.file_id,
+                        expr.span,
+                        replacement,
+                        Applicability::MachineApplicable,
+                        "replace it with the branch that is always taken",
+                    ),
+                    None => b,
+                }
+            },
+        );
+    }
+}
+
+impl Visitor for ConditionChecker<'_, '_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        let ExprKind::Match(scrutinee, arms) = &expr.kind else {
+            walk_expr(self, expr);
+            return;
+        };
+        let value = (!uses_vars(scrutinee))
+            .then(|| self.consts.get(scrutinee.id))
+            .flatten();
+        if let Some(value) = value {
+            let arm = arms.iter().find(|arm| matches_pat(&arm.pat, value));
+            self.report(expr, scrutinee, value, arm);
+            // The scrutinee is replaced as a whole, so constant conditions
+            // within it are not reported separately.
+            for arm in arms {
+                self.visit_pat(&arm.pat);
+                self.visit_expr(&arm.body);
+            }
+        } else {
+            walk_expr(self, expr);
+        }
+    }
+}
@@ -0,0 +1,657 @@
+//! Constant evaluation.
+//!
+//! [`eval_consts`] computes the values of expressions that can be evaluated
+//! at compile time, and reports operations that would always fail at
+//! runtime (e.g. `1 / 0`) as errors. An expression is constant if it is:
+//!
+//! - a literal,
+//! - a primitive operation, tuple or block whose operands are all constant,
+//! - a match on a constant whose chosen arm is constant, or
+//! - a variable that is bound immutably to a constant.
+//!
+//! The operations themselves ([`eval_binop`] and [`eval_unop`]) implement
+//! the language's semantics, so that anything that computes values agrees
+//! with what is computed here:
+//!
+//! - `uint` and `sint` are 64-bit integers, and all integer arithmetic is
+//!   checked. Overflow, division by zero and raising an integer to a
+//!   negative power are errors. `/` rounds towards zero, and the result of
+//!   `%` has the sign of the left operand. Shifting by the number of bits
+//!   in the type or more, or by a negative amount, overflows.
+//! - `float` is a 64-bit IEEE 754 float, and float operations never fail.
+
+use std::{
+    cmp::Ordering,
+    collections::HashMap,
+    fmt::{self, Display},
+    mem,
+};
+
+use calypso_ast::{
+    expr::{BinOpKind, Mutability, Numeral, UnOpKind},
+    node::NodeId,
+};
+use calypso_base::span::Span;
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+use calypso_hir::{
+    expr::{Expr, ExprKind, Lit, Prim},
+    pat::{Pat, PatKind},
+    resolve::Resolutions,
+};
+
+use crate::{
+    check::{int_value, TypeckResults},
+    ty::{PrimTy, Type},
+};
+
+/// The value of a constant expression.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Const {
+    Uint(u64),
+    Sint(i64),
+    Float(f64),
+    Bool(bool),
+    /// A tuple. The empty tuple is the unit value.
+    Tuple(Vec<Const>),
+}
+
+impl Display for Const {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Uint(int) => write!(f, "{int}"),
+            Self::Sint(int) => write!(f, "{int}"),
+            Self::Float(float) => write!(f, "{float:?}"),
+            Self::Bool(b) => write!(f, "{b}"),
+            Self::Tuple(elems) => {
+                write!(f, "(")?;
+                for (idx, elem) in elems.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{elem}")?;
+                }
+                if elems.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+/// The reason that an operation failed.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum ConstError {
+    /// Division or remainder by zero
+    DivisionByZero,
+    /// An integer operation whose result does not fit in its type, or a
+    /// literal that does not fit in its type
+    Overflow,
+    /// An integer raised to a negative power
+    NegativeExponent,
+}
+
+impl Display for ConstError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::DivisionByZero => write!(f, "division by zero"),
+            Self::Overflow => write!(f, "arithmetic overflow"),
+            Self::NegativeExponent => write!(f, "negative exponent"),
+        }
+    }
+}
+
+/// Evaluate a primitive binary operation.
+///
+/// # Errors
+///
+/// This function returns an error if the operation fails, as described in
+/// the [module documentation](self).
+///
+/// # Panics
+///
+/// This function panics if the operands do not have the types that the
+/// operator requires (see [`crate::check`]).
+pub fn eval_binop(op: BinOpKind, lhs: &Const, rhs: &Const) -> Result<Const, ConstError> {
+    let value = match op {
+        BinOpKind::Equal => Const::Bool(lhs == rhs),
+        BinOpKind::NotEqual => Const::Bool(lhs != rhs),
+        BinOpKind::Lt | BinOpKind::Gt | BinOpKind::LtEq | BinOpKind::GtEq => {
+            Const::Bool(compare(op, lhs, rhs))
+        }
+        _ => match (lhs, rhs) {
+            (Const::Uint(lhs), Const::Uint(rhs)) => Const::Uint(uint_binop(op, *lhs, *rhs)?),
+            (Const::Sint(lhs), Const::Sint(rhs)) => Const::Sint(sint_binop(op, *lhs, *rhs)?),
+            (Const::Float(lhs), Const::Float(rhs)) => Const::Float(float_binop(op, *lhs, *rhs)),
+            (Const::Bool(lhs), Const::Bool(rhs)) => Const::Bool(bool_binop(op, *lhs, *rhs)),
+            _ => unreachable!("the operands of `{op}` have the same primitive type"),
+        },
+    };
+    Ok(value)
+}
+
+/// Evaluate a primitive unary operation.
+///
+/// # Errors
+///
+/// This function returns [`ConstError::Overflow`] if the operand is the
+/// smallest `sint` and is negated.
+///
+/// # Panics
+///
+/// This function panics if the operand does not have a type that the
+/// operator allows (see [`crate::check`]).
+pub fn eval_unop(op: UnOpKind, operand: &Const) -> Result<Const, ConstError> {
+    match (op, operand) {
+        (UnOpKind::Negative, Const::Sint(int)) => int
+            .checked_neg()
+            .map(Const::Sint)
+            .ok_or(ConstError::Overflow),
+        (UnOpKind::Negative, Const::Float(float)) => Ok(Const::Float(-float)),
+        (UnOpKind::UnaryNot, Const::Uint(int)) => Ok(Const::Uint(!int)),
+        (UnOpKind::UnaryNot, Const::Sint(int)) => Ok(Const::Sint(!int)),
+        (UnOpKind::UnaryNot, Const::Bool(b)) => Ok(Const::Bool(!b)),
+        _ => unreachable!("the operand of `{op}` has an allowed type"),
+    }
+}
+
+/// Evaluate a literal of type `ty`. Returns `None` if the type is not one
+/// that the literal can have, which is only the case if type checking
+/// failed.
+///
+/// # Panics
+///
+/// This function panics if a float literal is not a valid float, which the
+/// lexer ensures it is.
+#[must_use]
+pub fn eval_lit(lit: Lit, ty: &Type) -> Option<Result<Const, ConstError>> {
+    let value = match (lit, ty) {
+        (Lit::Bool(b), _) => Ok(Const::Bool(b)),
+        (Lit::Number(Numeral::Integer { radix, .. }, text), Type::Prim(PrimTy::Uint)) => {
+            int_value(radix, text)
+                .and_then(|int| u64::try_from(int).ok())
+                .map(Const::Uint)
+                .ok_or(ConstError::Overflow)
+        }
+        (Lit::Number(Numeral::Integer { radix, .. }, text), Type::Prim(PrimTy::Sint)) => {
+            int_value(radix, text)
+                .and_then(|int| i64::try_from(int).ok())
+                .map(Const::Sint)
+                .ok_or(ConstError::Overflow)
+        }
+        (Lit::Number(Numeral::Float { .. }, text), _) => Ok(Const::Float(
+            text.as_str()
+                .trim_end_matches('f')
+                .replace('_', "")
+                .parse()
+                .expect("float literals are valid floats"),
+        )),
+        _ => return None,
+    };
+    Some(value)
+}
+
+/// Evaluate the negation of a number literal of type `ty`, as in the
+/// pattern `-1`. Returns `None` if the type is not one that the negated
+/// literal can have, which is only the case if type checking failed.
+#[must_use]
+pub fn eval_neg_lit(lit: Lit, ty: &Type) -> Option<Result<Const, ConstError>> {
+    match (lit, ty) {
+        // The magnitude of the smallest `sint` does not fit in a `sint`, so
+        // the literal can't be evaluated before it is negated.
+        (Lit::Number(Numeral::Integer { radix, .. }, text), Type::Prim(PrimTy::Sint)) => Some(
+            int_value(radix, text)
+                .and_then(|int| i128::try_from(int).ok())
+                .and_then(|int| i64::try_from(-int).ok())
+                .map(Const::Sint)
+                .ok_or(ConstError::Overflow),
+        ),
+        (Lit::Number(..), Type::Prim(PrimTy::Float)) => {
+            Some(eval_lit(lit, ty)?.and_then(|value| eval_unop(UnOpKind::Negative, &value)))
+        }
+        _ => None,
+    }
+}
+
+/// Evaluate the length of an array type.
+///
+/// # Errors
+///
+/// This function returns [`ConstError::Overflow`] if the length does not
+/// fit in a `uint`.
+///
+/// # Panics
+///
+/// This function panics if the length is not an unsigned integer literal
+/// (see [`calypso_ast::validate`]).
+pub fn eval_array_len(len: &Expr) -> Result<u64, ConstError> {
+    let ExprKind::Lit(lit) = len.kind else {
+        unreachable!("array lengths are unsigned integer literals")
+    };
+    match eval_lit(lit, &Type::Prim(PrimTy::Uint)) {
+        Some(Ok(Const::Uint(len))) => Ok(len),
+        Some(Err(err)) => Err(err),
+        _ => unreachable!("array lengths are unsigned integer literals"),
+    }
+}
+
+fn compare(op: BinOpKind, lhs: &Const, rhs: &Const) -> bool {
+    let ordering = match (lhs, rhs) {
+        (Const::Uint(lhs), Const::Uint(rhs)) => lhs.partial_cmp(rhs),
+        (Const::Sint(lhs), Const::Sint(rhs)) => lhs.partial_cmp(rhs),
+        (Const::Float(lhs), Const::Float(rhs)) => lhs.partial_cmp(rhs),
+        _ => unreachable!("only numbers can be compared"),
+    };
+    match op {
+        BinOpKind::Lt => ordering == Some(Ordering::Less),
+        BinOpKind::Gt => ordering == Some(Ordering::Greater),
+        BinOpKind::LtEq => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        BinOpKind::GtEq => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        _ => unreachable!("`{op}` is not a comparison operator"),
+    }
+}
+
+fn uint_binop(op: BinOpKind, lhs: u64, rhs: u64) -> Result<u64, ConstError> {
+    let result = match op {
+        BinOpKind::Add => lhs.checked_add(rhs),
+        BinOpKind::Subtract => lhs.checked_sub(rhs),
+        BinOpKind::Multiply => lhs.checked_mul(rhs),
+        BinOpKind::Divide | BinOpKind::Modulo if rhs == 0 => {
+            return Err(ConstError::DivisionByZero)
+        }
+        BinOpKind::Divide => lhs.checked_div(rhs),
+        BinOpKind::Modulo => lhs.checked_rem(rhs),
+        BinOpKind::Exponent => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_pow(rhs)),
+        BinOpKind::BitOr => Some(lhs | rhs),
+        BinOpKind::BitXor => Some(lhs ^ rhs),
+        BinOpKind::BitAnd => Some(lhs & rhs),
+        BinOpKind::BitShiftLeft => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)),
+        BinOpKind::BitShiftRight => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)),
+        _ => unreachable!("`{op}` is not an arithmetic or bitwise operator"),
+    };
+    result.ok_or(ConstError::Overflow)
+}
+
+fn sint_binop(op: BinOpKind, lhs: i64, rhs: i64) -> Result<i64, ConstError> {
+    let result = match op {
+        BinOpKind::Add => lhs.checked_add(rhs),
+        BinOpKind::Subtract => lhs.checked_sub(rhs),
+        BinOpKind::Multiply => lhs.checked_mul(rhs),
+        BinOpKind::Divide | BinOpKind::Modulo if rhs == 0 => {
+            return Err(ConstError::DivisionByZero)
+        }
+        BinOpKind::Divide => lhs.checked_div(rhs),
+        BinOpKind::Modulo => lhs.checked_rem(rhs),
+        BinOpKind::Exponent if rhs < 0 => return Err(ConstError::NegativeExponent),
+        BinOpKind::Exponent => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_pow(rhs)),
+        BinOpKind::BitOr => Some(lhs | rhs),
+        BinOpKind::BitXor => Some(lhs ^ rhs),
+        BinOpKind::BitAnd => Some(lhs & rhs),
+        BinOpKind::BitShiftLeft => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shl(rhs)),
+        BinOpKind::BitShiftRight => u32::try_from(rhs).ok().and_then(|rhs| lhs.checked_shr(rhs)),
+        _ => unreachable!("`{op}` is not an arithmetic or bitwise operator"),
+    };
+    result.ok_or(ConstError::Overflow)
+}
+
+fn float_binop(op: BinOpKind, lhs: f64, rhs: f64) -> f64 {
+    match op {
+        BinOpKind::Add => lhs + rhs,
+        BinOpKind::Subtract => lhs - rhs,
+        BinOpKind::Multiply => lhs * rhs,
+        BinOpKind::Divide => lhs / rhs,
+        BinOpKind::Modulo => lhs % rhs,
+        BinOpKind::Exponent => lhs.powf(rhs),
+        _ => unreachable!("`{op}` is not an arithmetic operator"),
+    }
+}
+
+fn bool_binop(op: BinOpKind, lhs: bool, rhs: bool) -> bool {
+    match op {
+        BinOpKind::BitOr | BinOpKind::LogicalOr => lhs | rhs,
+        BinOpKind::BitXor => lhs ^ rhs,
+        BinOpKind::BitAnd | BinOpKind::LogicalAnd => lhs & rhs,
+        _ => unreachable!("`{op}` is not a logical or bitwise operator"),
+    }
+}
+
+/// The values of constant expressions.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Consts {
+    /// Map from the node IDs of constant expressions to their values
+    values: HashMap<NodeId, Const>,
+}
+
+impl Consts {
+    /// Get the value of the expression `id`, if it is constant.
+    #[must_use]
+    pub fn get(&self, id: NodeId) -> Option<&Const> {
+        self.values.get(&id)
+    }
+}
+
+/// Evaluate the constant expressions in a sequence of type checked
+/// expressions, reporting operations that would always fail to `grcx` as
+/// synchronizable errors. If type checking failed, operations whose
+/// operands have the wrong types are not evaluated.
+pub fn eval_consts(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Expr],
+    res: &Resolutions,
+    results: &TypeckResults,
+) -> Consts {
+    let mut evaluator = Evaluator {
+        grcx,
+        file_id,
+        res,
+        results,
+        values: HashMap::new(),
+        bindings: HashMap::new(),
+    };
+    for expr in exprs {
+        evaluator.eval(expr);
+    }
+    Consts {
+        values: evaluator.values,
+    }
+}
+
+struct Evaluator<'grcx, 'res> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    res: &'res Resolutions,
+    results: &'res TypeckResults,
+    /// The values of constant expressions
+    values: HashMap<NodeId, Const>,
+    /// The values of immutable bindings that are bound to constants
+    bindings: HashMap<NodeId, Const>,
+}
+
+impl Evaluator<'_, '_> {
+    /// Evaluate an expression, returning its value if it is constant. All
+    /// subexpressions are evaluated, even if the expression is not constant,
+    /// so that every failing operation is reported.
+    fn eval(&mut self, expr: &Expr) -> Option<Const> {
+        let value = match &expr.kind {
+            ExprKind::Lit(lit) => self.eval_lit(*lit, expr),
+            ExprKind::Var(_) => self
+                .res
+                .resolve(expr.id)
+                .and_then(|def| self.bindings.get(&def))
+                .cloned(),
+            ExprKind::Prim(_) => None,
+            ExprKind::Let(pat, _, value, body) => {
+                if let Some(value) = self.eval(value) {
+                    self.bind(pat, value);
+                }
+                self.eval(body)
+            }
+            ExprKind::Assign(target, value) => {
+                self.eval(target);
+                self.eval(value);
+                None
+            }
+            ExprKind::Lambda(_, body) | ExprKind::Loop(body) => {
+                self.eval(body);
+                None
+            }
+            ExprKind::Fn(def) => {
+                self.eval(&def.body);
+                None
+            }
+            ExprKind::Call(callee, args) => self.eval_call(expr, callee, args),
+            ExprKind::Match(scrutinee, arms) => {
+                let scrutinee = self.eval(scrutinee);
+                let chosen = scrutinee.as_ref().and_then(|scrutinee| {
+                    arms.iter().position(|arm| matches_pat(&arm.pat, scrutinee))
+                });
+                let mut value = None;
+                for (idx, arm) in arms.iter().enumerate() {
+                    if Some(idx) == chosen {
+                        self.bind(&arm.pat, scrutinee.clone().expect("an arm was chosen"));
+                        value = self.eval(&arm.body);
+                    } else {
+                        self.eval(&arm.body);
+                    }
+                }
+                value
+            }
+            ExprKind::Tuple(exprs) => self.eval_all(exprs).map(Const::Tuple),
+            ExprKind::Break(value) => {
+                if let Some(value) = value {
+                    self.eval(value);
+                }
+                None
+            }
+            ExprKind::Block(exprs) => self
+                .eval_all(exprs)
+                .map(|mut values| values.pop().unwrap_or(Const::Tuple(Vec::new()))),
+        };
+        if let Some(value) = &value {
+            self.values.insert(expr.id, value.clone());
+        }
+        value
+    }
+
+    /// Evaluate all of `exprs`, returning their values if they are all
+    /// constant.
+    fn eval_all(&mut self, exprs: &[Expr]) -> Option<Vec<Const>> {
+        let values = exprs.iter().map(|expr| self.eval(expr)).collect::<Vec<_>>();
+        values.into_iter().collect()
+    }
+
+    fn eval_lit(&mut self, lit: Lit, expr: &Expr) -> Option<Const> {
+        let ty = self.results.node_type(expr.id)?;
+        let value = eval_lit(lit, ty)?;
+        if value.is_err() {
+            self.report_lit_out_of_range(lit, ty, expr.span);
+        }
+        value.ok()
+    }
+
+    fn eval_call(&mut self, expr: &Expr, callee: &Expr, args: &[Expr]) -> Option<Const> {
+        let ExprKind::Prim(prim) = callee.kind else {
+            self.eval(callee);
+            self.eval_all(args);
+            return None;
+        };
+
+        if let (Prim::UnOp(UnOpKind::Negative), [arg]) = (prim, args) {
+            if is_min_sint_magnitude(arg, self.results) {
+                return Some(Const::Sint(i64::MIN));
+            }
+        }
+
+        let operands = self.eval_all(args)?;
+        // Type checking reports operands of the wrong types, but the
+        // operation can't be evaluated.
+        if !operands_allowed(prim, &operands) {
+            return None;
+        }
+        let result = match (prim, operands.as_slice()) {
+            (Prim::BinOp(op), [lhs, rhs]) => eval_binop(op, lhs, rhs),
+            (Prim::UnOp(op), [operand]) => eval_unop(op, operand),
+            _ => unreachable!("the number of operands was checked"),
+        };
+        match result {
+            Ok(value) => Some(value),
+            Err(err) => {
+                self.report_failed_op(prim, &operands, err, expr.span);
+                None
+            }
+        }
+    }
+
+    /// Bind the bindings in `pat`, which matches `value`, to the parts of
+    /// `value` that they match. Mutable bindings are skipped, as they may be
+    /// assigned to.
+    fn bind(&mut self, pat: &Pat, value: Const) {
+        match (&pat.kind, value) {
+            (PatKind::Binding(Mutability::Immut, _), value) => {
+                self.bindings.insert(pat.id, value);
+            }
+            (PatKind::Tuple(pats), Const::Tuple(values)) => {
+                for (pat, value) in pats.iter().zip(values) {
+                    self.bind(pat, value);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn report_lit_out_of_range(&mut self, lit: Lit, ty: &Type, span: Span) {
+        let range = match ty {
+            Type::Prim(PrimTy::Sint) => format!("{}..={}", i64::MIN, i64::MAX),
+            _ => format!("0..={}", u64::MAX),
+        };
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code("E0015")
+                        .short(err!(E0015, ty))
+                        .label(
+                            LabelStyle::Primary,
+                            Some(&format!("the literal `{lit}` does not fit in `{ty}`")),
+                            self.file_id,
+                            span,
+                        )
+                        .note(format!("the range of `{ty}` is `{range}`"))
+                })
+                .build(),
+        );
+    }
+
+    fn report_failed_op(&mut self, prim: Prim, operands: &[Const], err: ConstError, span: Span) {
+        let (code, short) = match err {
+            ConstError::DivisionByZero => ("E0013", err!(E0013)),
+            ConstError::Overflow => ("E0014", err!(E0014)),
+            ConstError::NegativeExponent => ("E0016", err!(E0016)),
+        };
+        let label = describe_failed_op(prim, operands, err);
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code(code).short(short).label(
+                        LabelStyle::Primary,
+                        Some(&label),
+                        self.file_id,
+                        span,
+                    )
+                })
+                .build(),
+        );
+    }
+}
+
+/// Returns `true` if `operands` are the right number of operands for
+/// `prim`, and have types that it allows (see [`crate::check`]).
+fn operands_allowed(prim: Prim, operands: &[Const]) -> bool {
+    match (prim, operands) {
+        (Prim::BinOp(op), [lhs, rhs]) => {
+            let allowed = match op {
+                BinOpKind::Equal | BinOpKind::NotEqual => true,
+                BinOpKind::Add
+                | BinOpKind::Subtract
+                | BinOpKind::Multiply
+                | BinOpKind::Divide
+                | BinOpKind::Modulo
+                | BinOpKind::Exponent
+                | BinOpKind::Lt
+                | BinOpKind::Gt
+                | BinOpKind::LtEq
+                | BinOpKind::GtEq => {
+                    matches!(lhs, Const::Uint(_) | Const::Sint(_) | Const::Float(_))
+                }
+                BinOpKind::BitOr | BinOpKind::BitXor | BinOpKind::BitAnd => {
+                    matches!(lhs, Const::Uint(_) | Const::Sint(_) | Const::Bool(_))
+                }
+                BinOpKind::BitShiftLeft | BinOpKind::BitShiftRight => {
+                    matches!(lhs, Const::Uint(_) | Const::Sint(_))
+                }
+                BinOpKind::LogicalOr | BinOpKind::LogicalAnd => matches!(lhs, Const::Bool(_)),
+            };
+            allowed && mem::discriminant(lhs) == mem::discriminant(rhs)
+        }
+        (Prim::UnOp(UnOpKind::Negative), [operand]) => {
+            matches!(operand, Const::Sint(_) | Const::Float(_))
+        }
+        (Prim::UnOp(UnOpKind::UnaryNot), [operand]) => {
+            matches!(operand, Const::Uint(_) | Const::Sint(_) | Const::Bool(_))
+        }
+        _ => false,
+    }
+}
+
+/// Describe a primitive operation on `operands` that failed with `err`,
+/// e.g. "attempt to divide `1` by zero".
+///
+/// # Panics
+///
+/// This function panics if the number of operands is wrong for `prim`.
+#[must_use]
+pub fn describe_failed_op(prim: Prim, operands: &[Const], err: ConstError) -> String {
+    match (err, prim, operands) {
+        (ConstError::DivisionByZero, Prim::BinOp(BinOpKind::Modulo), [lhs, _]) => {
+            format!("attempt to calculate the remainder of `{lhs}` with a divisor of zero")
+        }
+        (ConstError::DivisionByZero, _, [lhs, _]) => format!("attempt to divide `{lhs}` by zero"),
+        (ConstError::NegativeExponent, _, [lhs, rhs]) => {
+            format!("attempt to raise `{lhs}` to the power of `{rhs}`")
+        }
+        (_, Prim::BinOp(op), [lhs, rhs]) => {
+            format!("attempt to compute `{lhs} {op} {rhs}`, which would overflow")
+        }
+        (_, Prim::UnOp(_), [operand]) => {
+            format!("attempt to negate `{operand}`, which would overflow")
+        }
+        _ => unreachable!("primitives are called with the right number of arguments"),
+    }
+}
+
+/// Returns `true` if `arg`, the operand of a negation, is the literal for
+/// the magnitude of the smallest `sint`. `-9223372036854775808` is the only
+/// way to write the smallest `sint`, but the literal on its own is too
+/// large.
+#[must_use]
+pub fn is_min_sint_magnitude(arg: &Expr, results: &TypeckResults) -> bool {
+    let ExprKind::Lit(Lit::Number(Numeral::Integer { radix, .. }, text)) = arg.kind else {
+        return false;
+    };
+    results.node_type(arg.id) == Some(&Type::Prim(PrimTy::Sint))
+        && int_value(radix, text) == Some(i64::MIN.unsigned_abs().into())
+}
+
+/// Check if `pat` matches the value `value`.
+pub(crate) fn matches_pat(pat: &Pat, value: &Const) -> bool {
+    match (&pat.kind, value) {
+        (PatKind::Wild | PatKind::Binding(..), _) => true,
+        (PatKind::Lit(Lit::Bool(lit)), Const::Bool(b)) => lit == b,
+        (PatKind::Lit(lit @ Lit::Number(..)), value) => {
+            let ty = match value {
+                Const::Uint(_) => PrimTy::Uint,
+                Const::Sint(_) => PrimTy::Sint,
+                _ => PrimTy::Float,
+            };
+            eval_lit(*lit, &Type::Prim(ty))
+                .and_then(Result::ok)
+                .as_ref()
+                == Some(value)
+        }
+        (PatKind::Neg(lit), Const::Sint(_) | Const::Float(_)) => {
+            let ty = match value {
+                Const::Sint(_) => PrimTy::Sint,
+                _ => PrimTy::Float,
+            };
+            eval_neg_lit(*lit, &Type::Prim(ty))
+                .and_then(Result::ok)
+                .as_ref()
+                == Some(value)
+        }
+        (PatKind::Tuple(pats), Const::Tuple(values)) => pats
+            .iter()
+            .zip(values)
+            .all(|(pat, value)| matches_pat(pat, value)),
+        _ => false,
+    }
+}
@@ -0,0 +1,87 @@
+//! The entry point of a program.
+//!
+//! A program may define a top-level function named `main`, which `calypso
+//! run` calls with the program arguments once the rest of the top level has
+//! been evaluated. It must have the type `fn(Args) -> uint`, `fn(Args) ->
+//! sint` or `fn(Args) -> ()`, and the integer that it returns is the exit
+//! status of the program. A program without `main` is run as a script, and
+//! exits with status 0 unless it fails.
+
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+use calypso_hir::{
+    expr::{Expr, ExprKind, FnDef},
+    pat::PatKind,
+};
+
+use crate::{
+    check::TypeckResults,
+    ty::{Adt, PrimTy, Type},
+};
+
+/// Find the top-level `main` function in a sequence of expressions, if
+/// there is one.
+#[must_use]
+pub fn find_main(exprs: &[Expr]) -> Option<&FnDef> {
+    exprs.iter().find_map(|expr| match &expr.kind {
+        ExprKind::Fn(def) => match def.name.kind {
+            PatKind::Binding(_, name) if name.as_str() == "main" => Some(&**def),
+            _ => None,
+        },
+        _ => None,
+    })
+}
+
+/// Check that the `main` function in a sequence of type checked expressions,
+/// if there is one, has the right type.
+pub fn check_main(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Expr],
+    results: &TypeckResults,
+) {
+    let Some(def) = find_main(exprs) else {
+        return;
+    };
+    let Some(scheme) = results.scheme(def.name.id) else {
+        return;
+    };
+    if is_main_ty(&scheme.ty) {
+        return;
+    }
+    grcx.report_syncd(
+        EnsembleBuilder::new()
+            .error(|b| {
+                b.code("E0022")
+                    .short(err!(E0022, scheme))
+                    .label(
+                        LabelStyle::Primary,
+                        Some("`main` must take `Args` and return an integer or `()`"),
+                        file_id,
+                        def.name.span,
+                    )
+                    .note(
+                        "`main` is called with the program arguments, and returns the exit status",
+                    )
+            })
+            .build(),
+    );
+}
+
+/// Returns `true` if a function of type `ty` can be called as `main`. Type
+/// parameters are allowed, as they can be instantiated to `Args` or to an
+/// exit status.
+fn is_main_ty(ty: &Type) -> bool {
+    let Type::Fn(params, ret) = ty else {
+        return matches!(ty, Type::Error);
+    };
+    let takes_args = matches!(
+        params.as_slice(),
+        [Type::Adt(Adt::Args, _) | Type::Param(_) | Type::Error]
+    );
+    let returns_status = **ret == Type::UNIT
+        || matches!(
+            **ret,
+            Type::Prim(PrimTy::Uint | PrimTy::Sint) | Type::Param(_) | Type::Error
+        );
+    takes_args && returns_status
+}
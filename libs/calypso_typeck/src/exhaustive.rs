@@ -0,0 +1,540 @@
+//! Exhaustiveness and reachability checking of matches.
+//!
+//! Both checks are built on the usefulness algorithm from Luc Maranget's
+//! "Warnings for pattern matching". The patterns of a match form a matrix
+//! with one row per arm. A row of patterns is *useful* with respect to a
+//! matrix if some value is matched by the row but by none of the rows of the
+//! matrix. Then:
+//!
+//! - an arm is unreachable if its pattern is not useful with respect to the
+//!   arms before it, and
+//! - a match is exhaustive if `_` is not useful with respect to all of its
+//!   arms.
+//!
+//! Instead of only checking if `_` is useful, the values that it is useful
+//! for are computed, so that missing patterns can be reported with concrete
+//! examples, e.g. ``pattern `(_, 3)` not covered``.
+//!
+//! The names in constructor patterns always refer to built-in constructors,
+//! so they are looked up by name here (see [`calypso_hir::resolve`]).
+//!
+//! Number types have too many values to list, so literal patterns can never
+//! cover all of them. The example of a missing integer is the smallest
+//! non-negative integer that is not matched.
+
+use std::fmt::{self, Display};
+
+use calypso_ast::expr::Numeral;
+use calypso_base::symbol::Symbol;
+use calypso_diagnostic::{prelude::*, report::GlobalReportingCtxt};
+use calypso_hir::{
+    builtin::Builtin,
+    expr::{Arm, Expr, ExprKind, Lit},
+    pat::{Pat, PatKind},
+    visit::{walk_expr, Visitor},
+};
+
+use crate::{
+    check::{int_value, TypeckResults},
+    ty::{ctor_fields, PrimTy, Type},
+};
+
+/// The maximum number of missing patterns that are listed.
+const MAX_WITNESSES: usize = 3;
+
+/// Check that every match is exhaustive and has no unreachable arms,
+/// reporting non-exhaustive matches to `grcx` as synchronizable errors and
+/// unreachable arms with the
+/// [`unreachable_patterns`](lint::UNREACHABLE_PATTERNS) lint.
+///
+/// Matches whose patterns do not have the type of their scrutinee are
+/// skipped, as type checking has already reported them.
+pub fn check_exhaustiveness(
+    grcx: &mut GlobalReportingCtxt,
+    file_id: usize,
+    exprs: &[Expr],
+    results: &TypeckResults,
+) {
+    let mut checker = ExhaustivenessChecker {
+        grcx,
+        file_id,
+        results,
+    };
+    for expr in exprs {
+        checker.visit_expr(expr);
+    }
+}
+
+struct ExhaustivenessChecker<'grcx, 'results> {
+    grcx: &'grcx mut GlobalReportingCtxt,
+    file_id: usize,
+    results: &'results TypeckResults,
+}
+
+impl ExhaustivenessChecker<'_, '_> {
+    fn check_match(&mut self, expr: &Expr, scrutinee: &Expr, arms: &[Arm]) {
+        let Some(ty) = self.results.node_type(scrutinee.id) else {
+            return;
+        };
+        if ty.contains_error() || !arms.iter().all(|arm| fits(&arm.pat, ty)) {
+            return;
+        }
+
+        let tys = [ty.clone()];
+        let mut rows = Vec::with_capacity(arms.len());
+        for arm in arms {
+            let row = vec![deconstruct(&arm.pat)];
+            if !is_useful(&rows, &row, &tys) {
+                self.report_unreachable(expr, arm);
+            }
+            rows.push(row);
+        }
+
+        let witnesses = missing(&rows, &tys);
+        if !witnesses.is_empty() {
+            self.report_non_exhaustive(scrutinee, ty, &witnesses);
+        }
+    }
+
+    fn report_unreachable(&mut self, expr: &Expr, arm: &Arm) {
+        self.grcx.report_lint(
+            &lint::UNREACHABLE_PATTERNS,
+            self.file_id,
+            arm.pat.span,
+            |b| {
+                b.short("unreachable pattern")
+                    .label(
+                        LabelStyle::Primary,
+                        Some("this arm is never reached"),
+                        self.file_id,
+                        arm.pat.span,
+                    )
+                    .label(
+                        LabelStyle::Secondary,
+                        Some("in this `case` expression"),
+                        self.file_id,
+                        expr.span,
+                    )
+                    .note("every value matched by this arm is matched by an earlier arm")
+            },
+        );
+    }
+
+    fn report_non_exhaustive(&mut self, scrutinee: &Expr, ty: &Type, witnesses: &[Vec<DPat>]) {
+        let mut patterns = witnesses
+            .iter()
+            .take(MAX_WITNESSES)
+            .map(|witness| format!("`{}`", witness[0]))
+            .collect::<Vec<_>>();
+        let patterns = if witnesses.len() > MAX_WITNESSES {
+            format!("patterns {} and more", patterns.join(", "))
+        } else if patterns.len() == 1 {
+            format!("pattern {}", patterns[0])
+        } else {
+            let last = patterns.pop().expect("there are multiple patterns");
+            format!("patterns {} and {last}", patterns.join(", "))
+        };
+        let help = if witnesses.len() == 1 {
+            format!("add an arm for `{}`, or an arm with `_`", witnesses[0][0])
+        } else {
+            "add arms for the missing patterns, or an arm with `_`".to_string()
+        };
+
+        self.grcx.report_syncd(
+            EnsembleBuilder::new()
+                .error(|b| {
+                    b.code("E0012")
+                        .short(err!(E0012, patterns))
+                        .label(
+                            LabelStyle::Primary,
+                            Some(&format!("{patterns} not covered")),
+                            self.file_id,
+                            scrutinee.span,
+                        )
+                        .note(format!("the matched value has type `{ty}`"))
+                        .note(help)
+                })
+                .build(),
+        );
+    }
+}
+
+impl Visitor for ExhaustivenessChecker<'_, '_> {
+    fn visit_expr(&mut self, expr: &Expr) {
+        if let ExprKind::Match(scrutinee, arms) = &expr.kind {
+            self.check_match(expr, scrutinee, arms);
+        }
+        walk_expr(self, expr);
+    }
+}
+
+/// A constructor of values that patterns can match on.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum Ctor {
+    Bool(bool),
+    Number(NumKey),
+    /// A tuple with the given number of elements
+    Tuple(usize),
+    /// A built-in constructor
+    Variant(Builtin),
+}
+
+impl Ctor {
+    /// Get the number of fields of the constructor.
+    fn arity(&self) -> usize {
+        match self {
+            Self::Bool(_) | Self::Number(_) => 0,
+            Self::Tuple(len) => *len,
+            Self::Variant(ctor) => ctor.fields(),
+        }
+    }
+
+    /// Get the types of the fields of the constructor, when constructing a
+    /// value of type `ty`.
+    fn field_tys(&self, ty: &Type) -> Vec<Type> {
+        match (self, ty) {
+            (Self::Tuple(_), Type::Tuple(tys)) => tys.clone(),
+            (Self::Variant(ctor), Type::Adt(_, args)) => ctor_fields(*ctor, args),
+            _ => Vec::new(),
+        }
+    }
+}
+
+/// The value of a number literal, used to compare number patterns.
+#[derive(Clone, Debug, PartialEq, Eq)]
+enum NumKey {
+    Int(i128),
+    /// The bits of a float
+    Float(u64),
+    /// The source text of a literal whose value could not be determined,
+    /// e.g. an integer that is too large
+    Text(Symbol),
+}
+
+impl NumKey {
+    /// Get the key of a number literal, which is negated if `negative`.
+    fn new(numeral: Numeral, text: Symbol, negative: bool) -> Self {
+        let key = match numeral {
+            Numeral::Integer { radix, .. } => int_value(radix, text)
+                .and_then(|int| i128::try_from(int).ok())
+                .map(|int| Self::Int(if negative { -int } else { int })),
+            Numeral::Float { .. } => text
+                .as_str()
+                .trim_end_matches('f')
+                .replace('_', "")
+                .parse::<f64>()
+                .ok()
+                .map(|float| Self::Float(if negative { -float } else { float }.to_bits())),
+        };
+        key.unwrap_or_else(|| {
+            if negative {
+                Self::Text(Symbol::intern(&format!("-{text}")))
+            } else {
+                Self::Text(text)
+            }
+        })
+    }
+}
+
+/// A deconstructed pattern, which is either `_` or a constructor applied to
+/// patterns for its fields. Bindings are equivalent to `_`.
+///
+/// Missing values are also represented as deconstructed patterns.
+#[derive(Clone, Debug)]
+enum DPat {
+    Wild,
+    Ctor(Ctor, Vec<DPat>),
+}
+
+impl DPat {
+    fn wilds(count: usize) -> Vec<Self> {
+        vec![Self::Wild; count]
+    }
+
+    fn ctor(&self) -> Option<&Ctor> {
+        match self {
+            Self::Wild => None,
+            Self::Ctor(ctor, _) => Some(ctor),
+        }
+    }
+}
+
+impl Display for DPat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Wild => write!(f, "_"),
+            Self::Ctor(Ctor::Bool(b), _) => write!(f, "{b}"),
+            Self::Ctor(Ctor::Number(NumKey::Int(int)), _) => write!(f, "{int}"),
+            Self::Ctor(Ctor::Number(NumKey::Float(bits)), _) => {
+                write!(f, "{:?}", f64::from_bits(*bits))
+            }
+            Self::Ctor(Ctor::Number(NumKey::Text(text)), _) => write!(f, "{text}"),
+            Self::Ctor(Ctor::Variant(ctor), fields) if fields.is_empty() => write!(f, "{ctor}"),
+            Self::Ctor(Ctor::Variant(ctor), fields) => {
+                write!(f, "{ctor}(")?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{field}")?;
+                }
+                write!(f, ")")
+            }
+            Self::Ctor(Ctor::Tuple(_), fields) => {
+                write!(f, "(")?;
+                for (idx, field) in fields.iter().enumerate() {
+                    if idx != 0 {
+                        write!(f, ", ")?;
+                    }
+                    write!(f, "{field}")?;
+                }
+                if fields.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+        }
+    }
+}
+
+fn deconstruct(pat: &Pat) -> DPat {
+    match &pat.kind {
+        PatKind::Wild | PatKind::Binding(..) => DPat::Wild,
+        PatKind::Lit(Lit::Bool(b)) => DPat::Ctor(Ctor::Bool(*b), Vec::new()),
+        PatKind::Lit(Lit::Number(numeral, text)) => DPat::Ctor(
+            Ctor::Number(NumKey::new(*numeral, *text, false)),
+            Vec::new(),
+        ),
+        PatKind::Neg(Lit::Number(numeral, text)) => {
+            DPat::Ctor(Ctor::Number(NumKey::new(*numeral, *text, true)), Vec::new())
+        }
+        PatKind::Neg(Lit::Bool(_)) => unreachable!("only numbers are negated in patterns"),
+        PatKind::Tuple(pats) => DPat::Ctor(
+            Ctor::Tuple(pats.len()),
+            pats.iter().map(deconstruct).collect(),
+        ),
+        PatKind::Ctor(name, pats) => match Builtin::from_name(*name) {
+            Some(ctor) => DPat::Ctor(Ctor::Variant(ctor), pats.iter().map(deconstruct).collect()),
+            // Matches with unresolved constructors are skipped, see `fits`.
+            None => DPat::Wild,
+        },
+    }
+}
+
+/// Check if a pattern can match values of type `ty`.
+fn fits(pat: &Pat, ty: &Type) -> bool {
+    match (&pat.kind, ty) {
+        (PatKind::Wild | PatKind::Binding(..), _)
+        | (PatKind::Lit(Lit::Bool(_)), Type::Prim(PrimTy::Bool))
+        | (
+            PatKind::Lit(Lit::Number(Numeral::Integer { .. }, _)),
+            Type::Prim(PrimTy::Uint | PrimTy::Sint),
+        )
+        | (
+            PatKind::Lit(Lit::Number(Numeral::Float { .. }, _))
+            | PatKind::Neg(Lit::Number(Numeral::Float { .. }, _)),
+            Type::Prim(PrimTy::Float),
+        )
+        | (PatKind::Neg(Lit::Number(Numeral::Integer { .. }, _)), Type::Prim(PrimTy::Sint)) => true,
+        (PatKind::Tuple(pats), Type::Tuple(tys)) => {
+            pats.len() == tys.len() && pats.iter().zip(tys).all(|(pat, ty)| fits(pat, ty))
+        }
+        (PatKind::Ctor(name, pats), Type::Adt(adt, args)) => Builtin::from_name(*name)
+            .filter(|ctor| adt.ctors().map_or(false, |ctors| ctors.contains(ctor)))
+            .map_or(false, |ctor| {
+                let field_tys = ctor_fields(ctor, args);
+                pats.len() == field_tys.len()
+                    && pats.iter().zip(&field_tys).all(|(pat, ty)| fits(pat, ty))
+            }),
+        _ => false,
+    }
+}
+
+/// Get all of the constructors of values of type `ty`, if there are finitely
+/// many of them.
+fn all_ctors(ty: &Type) -> Option<Vec<Ctor>> {
+    match ty {
+        Type::Prim(PrimTy::Bool) => Some(vec![Ctor::Bool(false), Ctor::Bool(true)]),
+        Type::Tuple(tys) => Some(vec![Ctor::Tuple(tys.len())]),
+        Type::Adt(adt, _) => Some(adt.ctors()?.iter().copied().map(Ctor::Variant).collect()),
+        _ => None,
+    }
+}
+
+/// Check if the constructors in the first column of `matrix` include every
+/// constructor of values of type `ty`.
+fn is_complete(matrix: &[Vec<DPat>], ty: &Type) -> bool {
+    all_ctors(ty).is_some() && missing_ctors(matrix, ty).is_none()
+}
+
+/// Get the constructors in the first column of `matrix`, without duplicates.
+fn head_ctors(matrix: &[Vec<DPat>]) -> Vec<&Ctor> {
+    let mut ctors = Vec::new();
+    for ctor in matrix.iter().filter_map(|row| row[0].ctor()) {
+        if !ctors.contains(&ctor) {
+            ctors.push(ctor);
+        }
+    }
+    ctors
+}
+
+/// Get the constructors of values of type `ty` that are missing from the
+/// first column of `matrix`, or `None` if none are missing.
+fn missing_ctors(matrix: &[Vec<DPat>], ty: &Type) -> Option<Vec<Ctor>> {
+    let present = head_ctors(matrix);
+    let missing = all_ctors(ty)?
+        .into_iter()
+        .filter(|ctor| !present.contains(&ctor))
+        .collect::<Vec<_>>();
+    (!missing.is_empty()).then_some(missing)
+}
+
+/// Specialize a row by `ctor`: if the row starts with `ctor` or `_`, replace
+/// it with the patterns for its fields. Otherwise, the row cannot match
+/// values built with `ctor`.
+fn specialize(row: &[DPat], ctor: &Ctor) -> Option<Vec<DPat>> {
+    let mut specialized = match &row[0] {
+        DPat::Wild => DPat::wilds(ctor.arity()),
+        DPat::Ctor(head, fields) if head == ctor => fields.clone(),
+        DPat::Ctor(..) => return None,
+    };
+    specialized.extend_from_slice(&row[1..]);
+    Some(specialized)
+}
+
+fn specialize_matrix(matrix: &[Vec<DPat>], ctor: &Ctor) -> Vec<Vec<DPat>> {
+    matrix
+        .iter()
+        .filter_map(|row| specialize(row, ctor))
+        .collect()
+}
+
+/// Get the rows that start with `_`, without their first column. These are
+/// the rows that match values built with constructors that are not in the
+/// first column.
+fn default_matrix(matrix: &[Vec<DPat>]) -> Vec<Vec<DPat>> {
+    matrix
+        .iter()
+        .filter(|row| matches!(row[0], DPat::Wild))
+        .map(|row| row[1..].to_vec())
+        .collect()
+}
+
+/// Get the types of the columns after specializing by `ctor`.
+fn specialized_tys(tys: &[Type], ctor: &Ctor) -> Vec<Type> {
+    let mut specialized = ctor.field_tys(&tys[0]);
+    specialized.extend_from_slice(&tys[1..]);
+    specialized
+}
+
+/// Check if `row`, whose columns have the types `tys`, matches any value
+/// that none of the rows of `matrix` do.
+fn is_useful(matrix: &[Vec<DPat>], row: &[DPat], tys: &[Type]) -> bool {
+    if row.is_empty() {
+        return matrix.is_empty();
+    }
+
+    match &row[0] {
+        DPat::Ctor(ctor, _) => {
+            let row = specialize(row, ctor).expect("a row always specializes by its own head");
+            is_useful(
+                &specialize_matrix(matrix, ctor),
+                &row,
+                &specialized_tys(tys, ctor),
+            )
+        }
+        DPat::Wild => {
+            if is_complete(matrix, &tys[0]) {
+                all_ctors(&tys[0]).into_iter().flatten().any(|ctor| {
+                    let row = specialize(row, &ctor).expect("`_` specializes by any constructor");
+                    is_useful(
+                        &specialize_matrix(matrix, &ctor),
+                        &row,
+                        &specialized_tys(tys, &ctor),
+                    )
+                })
+            } else {
+                is_useful(&default_matrix(matrix), &row[1..], &tys[1..])
+            }
+        }
+    }
+}
+
+/// Get examples of rows of values, with the types `tys`, that no row of
+/// `matrix` matches. At most one more than [`MAX_WITNESSES`] are returned, so
+/// that it is known whether there are more than can be listed.
+///
+/// Values built with each constructor in the first column are looked for
+/// separately, so that every missing value is covered by some example.
+fn missing(matrix: &[Vec<DPat>], tys: &[Type]) -> Vec<Vec<DPat>> {
+    if tys.is_empty() {
+        return if matrix.is_empty() {
+            vec![Vec::new()]
+        } else {
+            Vec::new()
+        };
+    }
+
+    let ty = &tys[0];
+    let present = head_ctors(matrix);
+    let all = all_ctors(ty);
+    // The examples for the other columns of values that are not built with
+    // any constructor in the first column
+    let rest = if present.is_empty() || all.is_none() || missing_ctors(matrix, ty).is_some() {
+        missing(&default_matrix(matrix), &tys[1..])
+    } else {
+        Vec::new()
+    };
+    let with_head = |head: DPat| {
+        rest.iter()
+            .map(|witness| {
+                let mut row = vec![head.clone()];
+                row.extend_from_slice(witness);
+                row
+            })
+            .collect::<Vec<_>>()
+    };
+
+    let mut witnesses = Vec::new();
+    if present.is_empty() {
+        witnesses.extend(with_head(DPat::Wild));
+    } else {
+        let ctors = all
+            .clone()
+            .unwrap_or_else(|| present.iter().map(|&ctor| ctor.clone()).collect());
+        for ctor in ctors {
+            let arity = ctor.arity();
+            if !present.contains(&&ctor) {
+                witnesses.extend(with_head(DPat::Ctor(ctor, DPat::wilds(arity))));
+                continue;
+            }
+
+            let specialized = specialize_matrix(matrix, &ctor);
+            for mut witness in missing(&specialized, &specialized_tys(tys, &ctor)) {
+                let rest = witness.split_off(arity);
+                let mut row = vec![DPat::Ctor(ctor.clone(), witness)];
+                row.extend(rest);
+                witnesses.push(row);
+            }
+        }
+        if all.is_none() {
+            witnesses.extend(with_head(other_number(&present, ty)));
+        }
+    }
+    witnesses.truncate(MAX_WITNESSES + 1);
+    witnesses
+}
+
+/// Get an example of a value of the number type `ty` that is not any of the
+/// constructors in `present`.
+fn other_number(present: &[&Ctor], ty: &Type) -> DPat {
+    match ty {
+        Type::Prim(PrimTy::Uint | PrimTy::Sint) => {
+            let int = (0..=i128::MAX)
+                .find(|int| !present.contains(&&Ctor::Number(NumKey::Int(*int))))
+                .expect("finitely many integers are present");
+            DPat::Ctor(Ctor::Number(NumKey::Int(int)), Vec::new())
+        }
+        _ => DPat::Wild,
+    }
+}
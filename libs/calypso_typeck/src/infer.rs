@@ -0,0 +1,414 @@
+//! Type inference variables, unification and generalization.
+//!
+//! Generalization uses levels: every inference variable records the level
+//! at which it was created, and the level is increased while checking the
+//! value of a `let`. Variables created at a deeper level than the current
+//! one, which do not escape into an enclosing scope, can be generalized.
+//! When a variable is bound, the levels of the variables in its type are
+//! lowered to its own, since they are now reachable from wherever it is.
+//!
+//! Placeholders (the type parameters of a function, within that function)
+//! also record the level they were created at. A variable can only be bound
+//! to a type containing a placeholder if the variable was created at the
+//! same level or deeper, so that placeholders cannot escape the function
+//! that they belong to.
+
+use std::{
+    collections::HashMap,
+    fmt::{self, Display},
+};
+
+use calypso_base::symbol::Symbol;
+
+use crate::ty::{PrimSet, Scheme, TyVid, Type};
+
+/// The value of an inference variable.
+#[derive(Clone, Debug)]
+enum VarValue {
+    /// The variable is not yet known, but may be constrained to a set of
+    /// primitive types
+    Unbound {
+        constraint: Option<PrimSet>,
+        level: u32,
+    },
+    /// The variable is known to be this type
+    Bound(Type),
+}
+
+/// The error returned when two types cannot be unified.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct TypeError;
+
+/// A table of inference variables.
+#[derive(Clone, Debug, Default)]
+pub struct InferCtxt {
+    vars: Vec<VarValue>,
+    /// The levels that placeholders were created at
+    placeholders: Vec<u32>,
+    /// The current level
+    level: u32,
+}
+
+impl InferCtxt {
+    #[must_use]
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Enter a level, e.g. when checking the value of a `let`.
+    pub fn enter_level(&mut self) {
+        self.level += 1;
+    }
+
+    /// Exit the current level.
+    pub fn exit_level(&mut self) {
+        self.level -= 1;
+    }
+
+    /// Create a new unconstrained inference variable.
+    pub fn new_var(&mut self) -> Type {
+        self.new_var_with(None)
+    }
+
+    /// Create a new inference variable that is constrained to a set of
+    /// primitive types.
+    pub fn new_constrained_var(&mut self, set: PrimSet) -> Type {
+        self.new_var_with(Some(set))
+    }
+
+    fn new_var_with(&mut self, constraint: Option<PrimSet>) -> Type {
+        let vid = TyVid(u32::try_from(self.vars.len()).expect("too many inference variables"));
+        self.vars.push(VarValue::Unbound {
+            constraint,
+            level: self.level,
+        });
+        Type::Var(vid)
+    }
+
+    /// Create a new placeholder for the type parameter `name`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`u32::MAX`] placeholders.
+    pub fn new_placeholder(&mut self, name: Symbol) -> Type {
+        let idx = u32::try_from(self.placeholders.len()).expect("too many placeholders");
+        self.placeholders.push(self.level);
+        Type::Placeholder(name, idx)
+    }
+
+    fn value(&self, vid: TyVid) -> &VarValue {
+        &self.vars[vid.0 as usize]
+    }
+
+    fn bind(&mut self, vid: TyVid, ty: Type) {
+        self.vars[vid.0 as usize] = VarValue::Bound(ty);
+    }
+
+    /// Replace `ty` with the type it is bound to, if it is a bound inference
+    /// variable. The resulting type is only resolved at its outermost level.
+    #[must_use]
+    pub fn shallow_resolve(&self, ty: &Type) -> Type {
+        match ty {
+            Type::Var(vid) => match self.value(*vid) {
+                VarValue::Bound(ty) => self.shallow_resolve(ty),
+                VarValue::Unbound { .. } => ty.clone(),
+            },
+            _ => ty.clone(),
+        }
+    }
+
+    /// Replace all bound inference variables within `ty` with the types they
+    /// are bound to.
+    #[must_use]
+    pub fn resolve(&self, ty: &Type) -> Type {
+        self.map_vars(ty, &mut |_| None)
+    }
+
+    /// Resolve `ty`, additionally replacing unbound inference variables using
+    /// `f`, if it returns `Some`.
+    fn map_vars(&self, ty: &Type, f: &mut impl FnMut(TyVid) -> Option<Type>) -> Type {
+        match self.shallow_resolve(ty) {
+            Type::Tuple(tys) => Type::Tuple(tys.iter().map(|ty| self.map_vars(ty, f)).collect()),
+            Type::Array(elem, len) => Type::Array(Box::new(self.map_vars(&elem, f)), len),
+            Type::Adt(adt, args) => {
+                Type::Adt(adt, args.iter().map(|ty| self.map_vars(ty, f)).collect())
+            }
+            Type::Fn(params, ret) => Type::Fn(
+                params.iter().map(|ty| self.map_vars(ty, f)).collect(),
+                Box::new(self.map_vars(&ret, f)),
+            ),
+            Type::Var(vid) => f(vid).unwrap_or(Type::Var(vid)),
+            ty => ty,
+        }
+    }
+
+    /// Make two types equal, binding inference variables as needed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the types are incompatible. Some inference
+    /// variables may already have been bound when this happens.
+    pub fn unify(&mut self, a: &Type, b: &Type) -> Result<(), TypeError> {
+        let (a, b) = (self.shallow_resolve(a), self.shallow_resolve(b));
+        match (&a, &b) {
+            (Type::Error, _) | (_, Type::Error) => Ok(()),
+            (Type::Var(a), Type::Var(b)) if a == b => Ok(()),
+            (&Type::Var(a), &Type::Var(b)) => self.unify_vars(a, b),
+            (&Type::Var(vid), ty) | (ty, &Type::Var(vid)) => self.unify_var_ty(vid, ty),
+            (Type::Prim(a), Type::Prim(b)) if a == b => Ok(()),
+            (Type::Param(a), Type::Param(b)) if a == b => Ok(()),
+            (Type::Placeholder(_, a), Type::Placeholder(_, b)) if a == b => Ok(()),
+            (Type::Tuple(a), Type::Tuple(b)) if a.len() == b.len() => self.unify_all(a, b),
+            (Type::Adt(a, a_args), Type::Adt(b, b_args)) if a == b => {
+                self.unify_all(a_args, b_args)
+            }
+            (Type::Array(a, a_len), Type::Array(b, b_len)) if a_len == b_len => self.unify(a, b),
+            (Type::Fn(a_params, a_ret), Type::Fn(b_params, b_ret))
+                if a_params.len() == b_params.len() =>
+            {
+                self.unify_all(a_params, b_params)?;
+                self.unify(a_ret, b_ret)
+            }
+            _ => Err(TypeError),
+        }
+    }
+
+    fn unify_all(&mut self, a: &[Type], b: &[Type]) -> Result<(), TypeError> {
+        a.iter().zip(b).try_for_each(|(a, b)| self.unify(a, b))
+    }
+
+    fn unify_vars(&mut self, a: TyVid, b: TyVid) -> Result<(), TypeError> {
+        let (
+            &VarValue::Unbound {
+                constraint: a_set,
+                level: a_level,
+            },
+            &VarValue::Unbound {
+                constraint: b_set,
+                level: b_level,
+            },
+        ) = (self.value(a), self.value(b))
+        else {
+            unreachable!("variables are resolved before unifying them")
+        };
+        let constraint = match (a_set, b_set) {
+            (None, set) | (set, None) => set,
+            (Some(a_set), Some(b_set)) => {
+                let set = a_set.intersection(b_set);
+                if set.is_empty() {
+                    return Err(TypeError);
+                }
+                if let Some(prim) = set.single() {
+                    self.bind(a, Type::Prim(prim));
+                    self.bind(b, Type::Prim(prim));
+                    return Ok(());
+                }
+                Some(set)
+            }
+        };
+        self.vars[b.0 as usize] = VarValue::Unbound {
+            constraint,
+            level: a_level.min(b_level),
+        };
+        self.bind(a, Type::Var(b));
+        Ok(())
+    }
+
+    fn unify_var_ty(&mut self, vid: TyVid, ty: &Type) -> Result<(), TypeError> {
+        let &VarValue::Unbound { constraint, level } = self.value(vid) else {
+            unreachable!("variables are resolved before unifying them")
+        };
+        match (constraint, ty) {
+            (Some(set), &Type::Prim(prim)) if !set.contains(prim) => Err(TypeError),
+            (Some(_), Type::Prim(_)) | (None, _)
+                if !self.occurs(vid, ty) && !self.escapes(ty, level) =>
+            {
+                self.lower_levels(ty, level);
+                self.bind(vid, ty.clone());
+                Ok(())
+            }
+            _ => Err(TypeError),
+        }
+    }
+
+    /// Check if the inference variable `vid` occurs in `ty`.
+    fn occurs(&self, vid: TyVid, ty: &Type) -> bool {
+        self.resolve(ty).contains_var(vid)
+    }
+
+    /// Check if a placeholder created at a deeper level than `level` occurs
+    /// in `ty`.
+    fn escapes(&self, ty: &Type, level: u32) -> bool {
+        fn walk(infcx: &InferCtxt, ty: &Type, level: u32) -> bool {
+            match ty {
+                Type::Placeholder(_, idx) => infcx.placeholders[*idx as usize] > level,
+                Type::Tuple(tys) | Type::Adt(_, tys) => tys.iter().any(|ty| walk(infcx, ty, level)),
+                Type::Array(elem, _) => walk(infcx, elem, level),
+                Type::Fn(params, ret) => {
+                    params.iter().any(|ty| walk(infcx, ty, level)) || walk(infcx, ret, level)
+                }
+                Type::Prim(_) | Type::Param(_) | Type::Var(_) | Type::Error => false,
+            }
+        }
+        walk(self, &self.resolve(ty), level)
+    }
+
+    /// Lower the levels of the unbound inference variables in `ty` to the
+    /// current level, so that they are never generalized by an enclosing
+    /// level. This is needed when a value checked at a deeper level is not
+    /// generalized, e.g. when it is bound mutably.
+    pub fn keep_at_current_level(&mut self, ty: &Type) {
+        self.lower_levels(ty, self.level);
+    }
+
+    /// Lower the levels of the unbound inference variables in `ty` to at most
+    /// `level`.
+    fn lower_levels(&mut self, ty: &Type, level: u32) {
+        let mut vids = Vec::new();
+        self.map_vars(ty, &mut |vid| {
+            vids.push(vid);
+            None
+        });
+        for vid in vids {
+            if let VarValue::Unbound {
+                level: var_level, ..
+            } = &mut self.vars[vid.0 as usize]
+            {
+                *var_level = (*var_level).min(level);
+            }
+        }
+    }
+
+    /// Generalize `ty` over the unconstrained inference variables within it
+    /// that were created at a deeper level than the current one. The
+    /// variables are bound to the corresponding type parameters.
+    ///
+    /// Variables that are constrained to a set of primitive types are never
+    /// generalized, and are defaulted instead.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`u32::MAX`] type parameters.
+    pub fn generalize(&mut self, ty: &Type) -> Scheme {
+        self.generalize_with(ty, &[])
+    }
+
+    /// Generalize `ty` like [`generalize`](Self::generalize), and also over
+    /// the given placeholders, which become the first type parameters, in
+    /// order.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are more than [`u32::MAX`] type parameters.
+    pub fn generalize_with(&mut self, ty: &Type, placeholders: &[Type]) -> Scheme {
+        let ty = replace_placeholders(&self.resolve(ty), placeholders);
+        let first = placeholders.len();
+        let mut params = HashMap::new();
+        let ty = self.map_vars(&ty, &mut |vid| match self.vars[vid.0 as usize] {
+            VarValue::Unbound {
+                constraint: None,
+                level,
+            } if level > self.level => {
+                let next = u32::try_from(first + params.len()).expect("too many type parameters");
+                Some(Type::Param(*params.entry(vid).or_insert(next)))
+            }
+            _ => None,
+        });
+        for (&vid, &idx) in &params {
+            self.bind(vid, Type::Param(idx));
+        }
+        Scheme {
+            params: u32::try_from(first + params.len()).expect("too many type parameters"),
+            ty,
+        }
+    }
+
+    /// Replace the type parameters of a scheme with new inference variables.
+    pub fn instantiate(&mut self, scheme: &Scheme) -> Type {
+        let vars = (0..scheme.params)
+            .map(|_| self.new_var())
+            .collect::<Vec<_>>();
+        substitute(&scheme.ty, &vars)
+    }
+
+    /// Bind every unbound inference variable to its default type. Variables
+    /// constrained to a set of primitive types default to the most preferred
+    /// type in the set (see [`PrimSet::default_ty`]), and unconstrained
+    /// variables default to the unit type.
+    pub fn default_vars(&mut self) {
+        for value in &mut self.vars {
+            if let VarValue::Unbound { constraint, .. } = value {
+                let ty = constraint
+                    .and_then(PrimSet::default_ty)
+                    .map_or(Type::UNIT, Type::Prim);
+                *value = VarValue::Bound(ty);
+            }
+        }
+    }
+
+    /// Display a type, including the constraints of any unbound inference
+    /// variables within it, e.g. `[{integer}]`.
+    #[must_use]
+    pub fn display<'a>(&'a self, ty: &'a Type) -> impl Display + 'a {
+        DisplayType { infcx: self, ty }
+    }
+}
+
+/// Replace the type parameters in `ty` with `args`.
+pub(crate) fn substitute(ty: &Type, args: &[Type]) -> Type {
+    map_leaves(ty, &mut |ty| match ty {
+        Type::Param(idx) => Some(args[*idx as usize].clone()),
+        _ => None,
+    })
+}
+
+/// Replace the placeholders in `ty` with the type parameters corresponding
+/// to their position in `placeholders`.
+fn replace_placeholders(ty: &Type, placeholders: &[Type]) -> Type {
+    map_leaves(ty, &mut |ty| {
+        let idx = placeholders
+            .iter()
+            .position(|placeholder| placeholder == ty)?;
+        Some(Type::Param(
+            u32::try_from(idx).expect("too many type parameters"),
+        ))
+    })
+}
+
+/// Rebuild `ty`, replacing the types that have no children using `f`, if it
+/// returns `Some`. Inference variables are not resolved.
+fn map_leaves(ty: &Type, f: &mut impl FnMut(&Type) -> Option<Type>) -> Type {
+    match ty {
+        Type::Tuple(tys) => Type::Tuple(tys.iter().map(|ty| map_leaves(ty, f)).collect()),
+        Type::Array(elem, len) => Type::Array(Box::new(map_leaves(elem, f)), *len),
+        Type::Fn(params, ret) => Type::Fn(
+            params.iter().map(|ty| map_leaves(ty, f)).collect(),
+            Box::new(map_leaves(ret, f)),
+        ),
+        Type::Adt(adt, args) => Type::Adt(*adt, args.iter().map(|ty| map_leaves(ty, f)).collect()),
+        Type::Prim(_) | Type::Param(_) | Type::Placeholder(..) | Type::Var(_) | Type::Error => {
+            f(ty).unwrap_or_else(|| ty.clone())
+        }
+    }
+}
+
+struct DisplayType<'a> {
+    infcx: &'a InferCtxt,
+    ty: &'a Type,
+}
+
+impl Display for DisplayType<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.ty
+            .write_with(f, &|f, vid| match self.infcx.value(vid) {
+                VarValue::Bound(ty) => write!(f, "{}", self.infcx.display(ty)),
+                VarValue::Unbound {
+                    constraint: Some(set),
+                    ..
+                } => write!(f, "{set}"),
+                VarValue::Unbound {
+                    constraint: None, ..
+                } => write!(f, "_"),
+            })
+    }
+}
@@ -0,0 +1,17 @@
+//! Type checking and inference for Calypso.
+//!
+//! Type checking happens on the HIR, after name resolution. See [`check`]
+//! for how types are inferred. Once types are known, [`exhaustive`] checks
+//! matches and [`consteval`] evaluates constant expressions, which
+//! [`conditions`] uses to find matches on constants. [`entry`] checks the
+//! `main` function of a program.
+#![doc(html_root_url = "https://calypso-lang.github.io/rustdoc/calypso_typeck/index.html")]
+#![warn(clippy::pedantic)]
+
+pub mod check;
+pub mod conditions;
+pub mod consteval;
+pub mod entry;
+pub mod exhaustive;
+pub mod infer;
+pub mod ty;
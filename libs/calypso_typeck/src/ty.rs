@@ -0,0 +1,385 @@
+//! The representation of types.
+
+use std::fmt::{self, Display};
+
+use calypso_base::symbol::Symbol;
+use calypso_hir::builtin::Builtin;
+
+use crate::infer::substitute;
+
+/// A primitive type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum PrimTy {
+    /// `uint`
+    Uint,
+    /// `sint`
+    Sint,
+    /// `float`
+    Float,
+    /// `bool`
+    Bool,
+}
+
+impl PrimTy {
+    /// All of the primitive types, in the order that they are preferred when
+    /// defaulting a [`PrimSet`].
+    pub const ALL: [Self; 4] = [Self::Uint, Self::Sint, Self::Float, Self::Bool];
+
+    /// Get the primitive type with the given name, if any.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|prim| prim.name() == name)
+    }
+
+    /// Get the name of the primitive type.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Uint => "uint",
+            Self::Sint => "sint",
+            Self::Float => "float",
+            Self::Bool => "bool",
+        }
+    }
+
+    fn bit(self) -> u8 {
+        1 << self as u8
+    }
+}
+
+impl Display for PrimTy {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// A set of primitive types. Inference variables can be constrained to a set
+/// of primitive types, e.g. the type of an unsuffixed integer literal is
+/// constrained to [`PrimSet::INTEGER`].
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub struct PrimSet(u8);
+
+impl PrimSet {
+    /// `uint` or `sint`
+    pub const INTEGER: Self = Self(1 << PrimTy::Uint as u8 | 1 << PrimTy::Sint as u8);
+    /// `uint`, `sint` or `float`
+    pub const NUMBER: Self = Self(Self::INTEGER.0 | 1 << PrimTy::Float as u8);
+    /// `sint` or `float`
+    pub const SIGNED: Self = Self(1 << PrimTy::Sint as u8 | 1 << PrimTy::Float as u8);
+    /// `uint`, `sint` or `bool`
+    pub const BITWISE: Self = Self(Self::INTEGER.0 | 1 << PrimTy::Bool as u8);
+
+    /// Get the set containing only `prim`.
+    #[must_use]
+    pub fn of(prim: PrimTy) -> Self {
+        Self(prim.bit())
+    }
+
+    #[must_use]
+    pub fn contains(self, prim: PrimTy) -> bool {
+        self.0 & prim.bit() != 0
+    }
+
+    #[must_use]
+    pub fn intersection(self, other: Self) -> Self {
+        Self(self.0 & other.0)
+    }
+
+    #[must_use]
+    pub fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    /// Iterate over the types in the set, in order of preference.
+    pub fn iter(self) -> impl Iterator<Item = PrimTy> {
+        PrimTy::ALL
+            .into_iter()
+            .filter(move |&prim| self.contains(prim))
+    }
+
+    /// Get the only type in the set, if it has exactly one.
+    #[must_use]
+    pub fn single(self) -> Option<PrimTy> {
+        let mut iter = self.iter();
+        let prim = iter.next()?;
+        iter.next().is_none().then_some(prim)
+    }
+
+    /// Get the type that a variable constrained to this set defaults to.
+    #[must_use]
+    pub fn default_ty(self) -> Option<PrimTy> {
+        self.iter().next()
+    }
+}
+
+impl Display for PrimSet {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::INTEGER => write!(f, "{{integer}}"),
+            Self::NUMBER => write!(f, "{{number}}"),
+            Self::SIGNED => write!(f, "{{signed number}}"),
+            Self::BITWISE => write!(f, "{{integer or bool}}"),
+            _ => {
+                let names = self.iter().map(PrimTy::name).collect::<Vec<_>>();
+                write!(f, "{{{}}}", names.join(" or "))
+            }
+        }
+    }
+}
+
+/// A built-in type other than a primitive type.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Adt {
+    /// `Option[T]`, an optional value
+    Option,
+    /// `Result[T, E]`, either a value or an error
+    Result,
+    /// `Args`, the program arguments passed to `main`. Values of this type
+    /// cannot be constructed by the program.
+    Args,
+}
+
+impl Adt {
+    /// All of the built-in types.
+    pub const ALL: [Self; 3] = [Self::Option, Self::Result, Self::Args];
+
+    /// Get the built-in type with the given name, if any.
+    #[must_use]
+    pub fn from_name(name: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|adt| adt.name() == name)
+    }
+
+    /// Get the name of the type.
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Option => "Option",
+            Self::Result => "Result",
+            Self::Args => "Args",
+        }
+    }
+
+    /// The number of type parameters of the type.
+    #[must_use]
+    pub fn params(self) -> usize {
+        match self {
+            Self::Option => 1,
+            Self::Result => 2,
+            Self::Args => 0,
+        }
+    }
+
+    /// The constructors of values of the type, or `None` if its values
+    /// cannot be matched on.
+    #[must_use]
+    pub fn ctors(self) -> Option<&'static [Builtin]> {
+        match self {
+            Self::Option => Some(&[Builtin::Some, Builtin::None]),
+            Self::Result => Some(&[Builtin::Ok, Builtin::Err]),
+            Self::Args => None,
+        }
+    }
+}
+
+impl Display for Adt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+/// Get the type scheme of a built-in.
+#[must_use]
+pub fn builtin_scheme(builtin: Builtin) -> Scheme {
+    let option = || Type::Adt(Adt::Option, vec![Type::Param(0)]);
+    let result = || Type::Adt(Adt::Result, vec![Type::Param(0), Type::Param(1)]);
+    let args = || Type::Adt(Adt::Args, Vec::new());
+    let uint = || Type::Prim(PrimTy::Uint);
+    let (params, ty) = match builtin {
+        Builtin::Some => (1, Type::Fn(vec![Type::Param(0)], Box::new(option()))),
+        Builtin::None => (1, option()),
+        Builtin::Ok => (2, Type::Fn(vec![Type::Param(0)], Box::new(result()))),
+        Builtin::Err => (2, Type::Fn(vec![Type::Param(1)], Box::new(result()))),
+        Builtin::ArgCount => (0, Type::Fn(vec![args()], Box::new(uint()))),
+        Builtin::ArgUint => (
+            0,
+            Type::Fn(
+                vec![args(), uint()],
+                Box::new(Type::Adt(Adt::Option, vec![uint()])),
+            ),
+        ),
+    };
+    Scheme { params, ty }
+}
+
+/// Get the types of the fields of the constructor `ctor`, for a value of the
+/// type that it constructs applied to `args`.
+#[must_use]
+pub fn ctor_fields(ctor: Builtin, args: &[Type]) -> Vec<Type> {
+    match builtin_scheme(ctor).ty {
+        Type::Fn(fields, _) => fields.iter().map(|ty| substitute(ty, args)).collect(),
+        _ => Vec::new(),
+    }
+}
+
+/// An inference variable.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct TyVid(pub(crate) u32);
+
+/// A type.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub enum Type {
+    Prim(PrimTy),
+    /// A tuple type. The empty tuple is the unit type.
+    Tuple(Vec<Type>),
+    /// An array type, with its length if it is known
+    Array(Box<Type>, Option<u64>),
+    /// A function type
+    Fn(Vec<Type>, Box<Type>),
+    /// A built-in type, applied to its type arguments
+    Adt(Adt, Vec<Type>),
+    /// A type parameter of a [`Scheme`], numbered from 0
+    Param(u32),
+    /// A type parameter from the `with` clause of a function item, within
+    /// the function. This is only equal to itself, so the function cannot
+    /// assume anything about it. The number identifies the parameter (see
+    /// [`InferCtxt::new_placeholder`](crate::infer::InferCtxt::new_placeholder)).
+    Placeholder(Symbol, u32),
+    /// An inference variable. These never appear in the results of type
+    /// checking.
+    Var(TyVid),
+    /// The type of an expression with an error that has already been
+    /// reported. This is compatible with every type, so that one error does
+    /// not cause many others.
+    Error,
+}
+
+impl Type {
+    /// The unit type, `()`
+    pub const UNIT: Self = Self::Tuple(Vec::new());
+
+    /// Check if an inference variable occurs in this type.
+    #[must_use]
+    pub fn contains_var(&self, vid: TyVid) -> bool {
+        match self {
+            Self::Prim(_) | Self::Param(_) | Self::Placeholder(..) | Self::Error => false,
+            Self::Tuple(tys) | Self::Adt(_, tys) => tys.iter().any(|ty| ty.contains_var(vid)),
+            Self::Array(elem, _) => elem.contains_var(vid),
+            Self::Fn(params, ret) => {
+                params.iter().any(|ty| ty.contains_var(vid)) || ret.contains_var(vid)
+            }
+            Self::Var(var) => *var == vid,
+        }
+    }
+
+    /// Check if [`Type::Error`] occurs in this type.
+    #[must_use]
+    pub fn contains_error(&self) -> bool {
+        match self {
+            Self::Prim(_) | Self::Param(_) | Self::Placeholder(..) | Self::Var(_) => false,
+            Self::Tuple(tys) | Self::Adt(_, tys) => tys.iter().any(Self::contains_error),
+            Self::Array(elem, _) => elem.contains_error(),
+            Self::Fn(params, ret) => {
+                params.iter().any(Self::contains_error) || ret.contains_error()
+            }
+            Self::Error => true,
+        }
+    }
+
+    /// Write the type, writing inference variables using `var`.
+    pub(crate) fn write_with(
+        &self,
+        f: &mut fmt::Formatter<'_>,
+        var: &dyn Fn(&mut fmt::Formatter<'_>, TyVid) -> fmt::Result,
+    ) -> fmt::Result {
+        let write_list = |f: &mut fmt::Formatter<'_>, tys: &[Type]| {
+            for (idx, ty) in tys.iter().enumerate() {
+                if idx != 0 {
+                    write!(f, ", ")?;
+                }
+                ty.write_with(f, var)?;
+            }
+            Ok(())
+        };
+
+        match self {
+            Self::Prim(prim) => write!(f, "{prim}"),
+            Self::Tuple(tys) => {
+                write!(f, "(")?;
+                write_list(f, tys)?;
+                if tys.len() == 1 {
+                    write!(f, ",")?;
+                }
+                write!(f, ")")
+            }
+            Self::Array(elem, len) => {
+                write!(f, "[")?;
+                elem.write_with(f, var)?;
+                if let Some(len) = len {
+                    write!(f, "; {len}")?;
+                }
+                write!(f, "]")
+            }
+            Self::Fn(params, ret) => {
+                write!(f, "fn(")?;
+                write_list(f, params)?;
+                write!(f, ") -> ")?;
+                ret.write_with(f, var)
+            }
+            Self::Adt(adt, args) if args.is_empty() => write!(f, "{adt}"),
+            Self::Adt(adt, args) => {
+                write!(f, "{adt}[")?;
+                write_list(f, args)?;
+                write!(f, "]")
+            }
+            Self::Param(idx) => write!(f, "{}", param_name(*idx)),
+            Self::Placeholder(name, _) => write!(f, "{name}"),
+            Self::Var(vid) => var(f, *vid),
+            Self::Error => write!(f, "{{error}}"),
+        }
+    }
+}
+
+impl Display for Type {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.write_with(f, &|f, _| write!(f, "_"))
+    }
+}
+
+/// Get the name used to display a type parameter.
+fn param_name(idx: u32) -> String {
+    match idx {
+        0 => "T".to_string(),
+        1 => "U".to_string(),
+        2 => "V".to_string(),
+        3 => "W".to_string(),
+        _ => format!("T{idx}"),
+    }
+}
+
+/// A type scheme, i.e. a type that is generic over some type parameters,
+/// written as e.g. `with(T) fn(T) -> T`.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Scheme {
+    /// The number of type parameters
+    pub params: u32,
+    /// The type, which refers to the parameters using [`Type::Param`]
+    pub ty: Type,
+}
+
+impl Scheme {
+    /// Create a scheme that is not generic.
+    #[must_use]
+    pub fn mono(ty: Type) -> Self {
+        Self { params: 0, ty }
+    }
+}
+
+impl Display for Scheme {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if self.params != 0 {
+            let params = (0..self.params).map(param_name).collect::<Vec<_>>();
+            write!(f, "with({}) ", params.join(", "))?;
+        }
+        write!(f, "{}", self.ty)
+    }
+}
@@ -0,0 +1,182 @@
+use calypso_ast::expr::BinOpKind;
+use calypso_test_support::{check_str, render, Checked};
+use calypso_typeck::{
+    conditions::check_constant_conditions,
+    consteval::{eval_binop, eval_consts, Const, ConstError},
+};
+
+/// Evaluate `src`, returning the value of each top-level expression (or
+/// `-` if it is not constant) and the rendered errors, including type
+/// errors.
+fn eval(src: &str) -> (Vec<String>, Vec<String>) {
+    let Checked {
+        mut grcx,
+        exprs,
+        res,
+        results,
+    } = check_str(src);
+    let consts = eval_consts(&mut grcx, 0, &exprs, &res, &results);
+    let values = exprs
+        .iter()
+        .map(|expr| {
+            consts
+                .get(expr.id)
+                .map_or("-".to_string(), Const::to_string)
+        })
+        .collect();
+    (values, render(grcx.errors()))
+}
+
+fn assert_values(src: &str, expected: &[&str]) {
+    let (values, errors) = eval(src);
+    assert!(errors.is_empty(), "evaluating {src:?}: {errors:#?}");
+    assert_eq!(values, expected, "evaluating {src:?}");
+}
+
+#[test]
+fn arithmetic() {
+    assert_values(
+        "1 + 2 - 3 * 4 / 5; 7s % -2; -7s / 2; 2 ** 10; 1.5 * 2.0; 1f / 0.0",
+        &["1", "1", "-3", "1024", "3.0", "inf"],
+    );
+    assert_values(
+        "0xff & 0b1010 | 1 << 4; !0s; 256u >> 4 ^ 1; -9223372036854775808s",
+        &["26", "-1", "17", "-9223372036854775808"],
+    );
+}
+
+#[test]
+fn comparisons_and_logic() {
+    assert_values(
+        "1 < 2; 2.0 >= 2.5; (1, true) == (1, true); () != (); true && !false || false",
+        &["true", "false", "true", "false", "true"],
+    );
+}
+
+#[test]
+fn bindings_and_matches() {
+    assert_values(
+        "let x = 2 in case (x * 3, true) do (6, b) -> b, _ -> false end",
+        &["true"],
+    );
+    assert_values(
+        "do 1; (2,) end; case 3 do 0 -> 1, n -> n * n end",
+        &["(2,)", "9"],
+    );
+    // Mutable bindings and assignments are not constant.
+    assert_values(
+        "let mut x = 1 in x + 1; let mut y = 1 in do y = 2; y end",
+        &["-", "-"],
+    );
+}
+
+#[test]
+fn failing_operations() {
+    let (values, errors) = eval("1 / 0; 5s % 0; 2 ** -1s; 1 << 64");
+    assert_eq!(values, ["-", "-", "-", "-"]);
+    assert_eq!(errors.len(), 4, "{errors:#?}");
+    assert!(errors[0].contains("This operation will divide by zero."));
+    assert!(errors[0].contains("attempt to divide `1` by zero"));
+    assert!(errors[1].contains("the remainder of `5` with a divisor of zero"));
+    assert!(errors[2].contains("Integers cannot be raised to a negative power."));
+    assert!(errors[3].contains("attempt to compute `1 << 64`, which would overflow"));
+
+    let (_, errors) = eval("18446744073709551615u + 1; -(-9223372036854775807s - 1)");
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    assert!(errors[0].contains("This arithmetic operation will overflow."));
+    assert!(errors[0].contains("attempt to compute `18446744073709551615 + 1`"));
+    assert!(errors[1].contains("attempt to negate `-9223372036854775808`"));
+
+    // Errors are reported even where the value is not constant, but only
+    // once for each failing operation.
+    let (_, errors) = eval("let mut x = 1 in x + 1 / 0 * 2");
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+
+    let (_, errors) = eval("9223372036854775808s; 18446744073709551616");
+    assert_eq!(errors.len(), 2, "{errors:#?}");
+    assert!(errors[0].contains("Literal out of range for `sint`."));
+    assert!(errors[1].contains("the literal `18446744073709551616` does not fit in `uint`"));
+}
+
+#[test]
+fn ill_typed_operations() {
+    // Operations on operands of the wrong types are reported by type
+    // checking, and are not evaluated.
+    let (values, errors) = eval("2 + true; 1 < 1.5; !1.5; -1u; 1 | true; 1 == false; 1 + 2");
+    assert_eq!(values, ["-", "-", "-", "-", "-", "-", "3"]);
+    assert_eq!(errors.len(), 6, "{errors:#?}");
+    assert!(errors
+        .iter()
+        .all(|error| error.contains("Mismatched types")));
+}
+
+#[test]
+fn operations() {
+    assert_eq!(
+        eval_binop(BinOpKind::Divide, &Const::Sint(i64::MIN), &Const::Sint(-1)),
+        Err(ConstError::Overflow)
+    );
+    assert_eq!(
+        eval_binop(BinOpKind::Exponent, &Const::Uint(2), &Const::Uint(64)),
+        Err(ConstError::Overflow)
+    );
+    assert_eq!(
+        eval_binop(BinOpKind::LtEq, &Const::Float(f64::NAN), &Const::Float(1.0)),
+        Ok(Const::Bool(false))
+    );
+    assert_eq!(
+        eval_binop(
+            BinOpKind::LogicalAnd,
+            &Const::Bool(true),
+            &Const::Bool(false)
+        ),
+        Ok(Const::Bool(false))
+    );
+}
+
+/// Check `src` for constant conditions, returning the rendered warnings and
+/// the suggested replacements.
+fn constant_conditions(src: &str) -> (Vec<String>, Vec<String>) {
+    let Checked {
+        mut grcx,
+        exprs,
+        res,
+        results,
+    } = check_str(src);
+    let consts = eval_consts(&mut grcx, 0, &exprs, &res, &results);
+    check_constant_conditions(&mut grcx, 0, src, &exprs, &consts);
+    assert!(grcx.errors().is_empty(), "checking {src:?}");
+    let warnings = grcx.nonfatals();
+    (
+        render(warnings),
+        warnings
+            .iter()
+            .flat_map(|w| w.diagnostics())
+            .flat_map(|diag| diag.suggestions())
+            .map(|suggestion| suggestion.edits[0].replacement.clone())
+            .collect(),
+    )
+}
+
+#[test]
+fn constant_condition() {
+    let (warnings, replacements) =
+        constant_conditions("case 1 + 1 do 2 -> true, _ -> false end; false && 1 > 2");
+    assert_eq!(warnings.len(), 2);
+    assert!(warnings[0].contains("this condition is always `2`"));
+    assert!(warnings[1].contains("this condition is always `false`"));
+    assert_eq!(replacements, ["true", "false"]);
+
+    let (_, replacements) = constant_conditions("!(true || false && true)");
+    assert_eq!(replacements, ["true", "false"]);
+    let (_, replacements) =
+        constant_conditions("true && 1 > 2; case (1, 2) do (1, _) -> 1 + 2, _ -> 3 end");
+    assert_eq!(replacements, ["(1 > 2)", "(1 + 2)"]);
+
+    // Arms that bind variables cannot be substituted, and conditions that
+    // use variables are not linted.
+    let (warnings, replacements) =
+        constant_conditions("case 1 do x -> x end; let x = true in x && false");
+    assert_eq!(warnings.len(), 1);
+    assert!(replacements.is_empty());
+}
@@ -0,0 +1,120 @@
+use calypso_test_support::{check_str, render, Checked};
+use calypso_typeck::exhaustive::check_exhaustiveness;
+
+/// Check the matches in `src`, returning the rendered errors and warnings.
+fn check(src: &str) -> (Vec<String>, Vec<String>) {
+    let Checked {
+        mut grcx,
+        exprs,
+        results,
+        ..
+    } = check_str(src);
+    assert!(grcx.errors().is_empty(), "type checking {src:?}");
+    check_exhaustiveness(&mut grcx, 0, &exprs, &results);
+    (render(grcx.errors()), render(grcx.nonfatals()))
+}
+
+/// Check that the only error in `src` is that `patterns` are not covered.
+fn assert_missing(src: &str, patterns: &str) {
+    let (errors, warnings) = check(src);
+    assert!(warnings.is_empty(), "checking {src:?}: {warnings:#?}");
+    assert_eq!(errors.len(), 1, "checking {src:?}: {errors:#?}");
+    assert!(
+        errors[0].contains(&format!("Non-exhaustive patterns: {patterns} not covered.")),
+        "checking {src:?}: {errors:#?}"
+    );
+}
+
+#[test]
+fn exhaustive() {
+    for src in [
+        "case true do true -> 1, false -> 2 end",
+        "case (1, true) do (_, true) -> 1, (0, false) -> 2, (n, false) -> n end",
+        "case ((), (false,)) do ((), (b,)) -> b end",
+        "let f = case 1.5 do 1.5 -> 1, x -> 2 end in f",
+        // The desugared `&&` and `||` are exhaustive.
+        "true && false || true",
+        "case Some(true) do Some(true) -> 1, Some(false) -> 2, None -> 3 end",
+        "case Ok(1) do Ok(n) -> n, Err(()) -> 0 end",
+        "case 1s do -1 -> 1, 0x1 -> 2, _ -> 3 end",
+    ] {
+        let (errors, warnings) = check(src);
+        assert!(errors.is_empty(), "checking {src:?}: {errors:#?}");
+        assert!(warnings.is_empty(), "checking {src:?}: {warnings:#?}");
+    }
+}
+
+#[test]
+fn missing_patterns() {
+    assert_missing("case true do true -> 1 end", "pattern `false`");
+    assert_missing("case 1 do 0 -> 1, 1 -> 2, 3 -> 3 end", "pattern `2`");
+    assert_missing(
+        "case (true, 1) do (true, _) -> 1, (_, 0) -> 2, (_, 1) -> 3, (_, 2) -> 4 end",
+        "pattern `(false, 3)`",
+    );
+    assert_missing(
+        "case (false, true) do (true, true) -> 1 end",
+        "patterns `(false, _)` and `(true, false)`",
+    );
+    assert_missing(
+        "case (true, true, true) do (true, true, true) -> 1 end",
+        "patterns `(false, _, _)`, `(true, false, _)` and `(true, true, false)`",
+    );
+    assert_missing(
+        "case ((true,), 2.5) do ((true,), _) -> 1 end",
+        "pattern `((false,), _)`",
+    );
+    assert_missing("case 1.5 do 1.5 -> 1 end", "pattern `_`");
+    assert_missing("case 1s do -1 -> 1, 0 -> 2 end", "pattern `1`");
+    assert_missing(
+        "case Some(1) do Some(1) -> 1 end",
+        "patterns `Some(0)` and `None`",
+    );
+    assert_missing(
+        "case (None, Err(true)) do (Some(_), _) -> 1, (_, Ok(_)) -> 2, (_, Err(false)) -> 3 end",
+        "pattern `(None, Err(true))`",
+    );
+
+    let (errors, _) = check("case (1, false) do (0, _) -> 1 end");
+    assert!(errors[0].contains("pattern `(1, _)` not covered"));
+    assert!(errors[0].contains("the matched value has type `(uint, bool)`"));
+    assert!(errors[0].contains("add an arm for `(1, _)`, or an arm with `_`"));
+}
+
+#[test]
+fn unreachable_arms() {
+    let (errors, warnings) = check("case (1, true) do (_, true) -> 1, (2, true) -> 2, _ -> 3 end");
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(warnings.len(), 1, "{warnings:#?}");
+    assert!(warnings[0].contains("unreachable pattern"));
+    assert!(warnings[0].contains("this arm is never reached"));
+    assert!(warnings[0].contains("in this `case` expression"));
+
+    let (errors, warnings) = check(
+        "case true do x -> 1, true -> 2, false -> 3 end; case 1 do 1 -> 1, 1 -> 2, _ -> 3 end",
+    );
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(warnings.len(), 3, "{warnings:#?}");
+
+    // `-0` is the same as `0`, but `-1` is not `1`.
+    let (errors, warnings) = check("case 1s do 0 -> 1, -0 -> 2, 1 -> 3, -1 -> 4, _ -> 5 end");
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert_eq!(warnings.len(), 1, "{warnings:#?}");
+
+    // Arms are reachable as long as they match any value that earlier arms
+    // do not, even if they overlap.
+    let (errors, warnings) =
+        check("case (true, false) do (true, _) -> 1, (_, false) -> 2, (false, true) -> 3 end");
+    assert!(errors.is_empty(), "{errors:#?}");
+    assert!(warnings.is_empty(), "{warnings:#?}");
+}
+
+#[test]
+fn nested_matches() {
+    let (errors, warnings) = check(
+        "case true do true -> case 1 do 0 -> 1 end, _ -> case false do _ -> 2, true -> 3 end end",
+    );
+    assert_eq!(errors.len(), 1, "{errors:#?}");
+    assert!(errors[0].contains("pattern `1` not covered"));
+    assert_eq!(warnings.len(), 1, "{warnings:#?}");
+}
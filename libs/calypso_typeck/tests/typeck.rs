@@ -0,0 +1,502 @@
+use calypso_ast::{
+    expr::{Mutability, Numeral, Radix, Suffix},
+    node::NodeIdGen,
+};
+use calypso_base::{span::Span, symbol::Symbol};
+use calypso_hir::{
+    expr::{Expr, ExprKind, Lit, Param},
+    pat::{Pat, PatKind},
+    ty::{Ty, TyKind},
+};
+use calypso_test_support::{check_hir, check_str, render, Checked};
+use calypso_typeck::entry::check_main;
+
+/// Type check `src`, returning the type of each top-level expression, and
+/// the rendered errors.
+fn check(src: &str) -> (Vec<String>, Vec<String>) {
+    types_and_errors(&check_str(src))
+}
+
+/// Returns the type of each top-level expression in `checked`, and the
+/// rendered errors.
+fn types_and_errors(checked: &Checked) -> (Vec<String>, Vec<String>) {
+    let types = checked
+        .exprs
+        .iter()
+        .map(|expr| checked.results.node_type(expr.id).unwrap().to_string())
+        .collect();
+    (types, render(checked.grcx.errors()))
+}
+
+fn assert_types(src: &str, expected: &[&str]) {
+    let (types, errors) = check(src);
+    assert!(errors.is_empty(), "type checking {src:?}: {errors:#?}");
+    assert_eq!(types, expected, "type checking {src:?}");
+}
+
+#[test]
+fn literals() {
+    assert_types(
+        "1; 1u; 1s; -1; 1.5; 1f; true",
+        &["uint", "uint", "sint", "sint", "float", "float", "bool"],
+    );
+}
+
+#[test]
+fn operators() {
+    assert_types(
+        "1 + 2 * 3; 1.5 % 2.0; 2s ** 3; 1 << 2; !5u; !true; 1 < 2; true == false",
+        &[
+            "uint", "float", "sint", "uint", "uint", "bool", "bool", "bool",
+        ],
+    );
+    assert_types("true && 1 > 2 || false", &["bool"]);
+}
+
+#[test]
+fn annotations() {
+    assert_types(
+        "let x: sint = 1 in x; let y: float = 1.5 in y",
+        &["sint", "float"],
+    );
+    assert_types("let x = 1 in let y: sint = x in x", &["sint"]);
+    assert_types("let mut x = 1 in x = -2", &["sint"]);
+}
+
+#[test]
+fn tuples_and_case() {
+    assert_types("(); (1, true); (1.5,)", &["()", "(uint, bool)", "(float,)"]);
+    assert_types(
+        "case (1s, false) do (0, b) -> b, (_, _) -> true end",
+        &["bool"],
+    );
+
+    assert_types(
+        "case 1 do -1 -> 1, _ -> 2 end; case 1.5 do -1.5 -> true, _ -> false end",
+        &["uint", "bool"],
+    );
+    let (_, errors) = check("case 1u do -1 -> 1, _ -> 2 end; case 1s do -1u -> 1, _ -> 2 end");
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("expected `{signed number}`, found `uint`"));
+    assert!(errors[1].contains("expected `sint`, found `uint`"));
+
+    let (_, errors) = check("case (1, 2) do (a, b, c) -> a end; case true do 1 -> 1 end");
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("expected `(_, _, _)`, found `({integer}, {integer})`"));
+    assert!(errors[1].contains("expected `{integer}`, found `bool`"));
+}
+
+#[test]
+fn mismatches() {
+    let (_, errors) = check("let x: bool = 1 in x");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Mismatched types: expected `bool`, found `{integer}`."));
+    assert!(errors[0].contains("expected due to this type"));
+
+    let (_, errors) = check("1 + true; -1u; 1.5 << 2");
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("expected `{integer}`, found `bool`"));
+    assert!(errors[0].contains("expected because of this operand"));
+    assert!(errors[1].contains("expected `{signed number}`, found `uint`"));
+    assert!(errors[2].contains("expected `{integer}`, found `float`"));
+
+    let (_, errors) = check("1 && true; true || 2");
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("expected `bool`, found `{integer}`"));
+    assert!(errors[1].contains("expected `bool`, found `{integer}`"));
+
+    let (types, errors) = check("let x: [uint; 4] = 1.5 in x");
+    assert_eq!(types, ["[uint; 4]"]);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("expected `[uint; 4]`, found `float`"));
+}
+
+#[test]
+fn array_lengths() {
+    let (types, errors) = check("let x: [bool; 0x10] = 1 in x");
+    assert_eq!(types, ["[bool; 16]"]);
+    assert_eq!(errors.len(), 1);
+
+    let (types, errors) = check("let x: [bool; 18446744073709551616] = 1 in x");
+    assert_eq!(types, ["{error}"]);
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Literal out of range for `uint`."));
+    assert!(errors[0].contains("this array length does not fit in `uint`"));
+}
+
+#[test]
+fn unknown_types() {
+    let (types, errors) = check("let x: unt = 1 in x; let y: (string, bool) = 1 in y");
+    assert_eq!(types, ["{error}", "({error}, bool)"]);
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("Cannot find type `unt` in this scope."));
+    assert!(errors[0].contains("a type with a similar name exists: `uint`"));
+    assert!(errors[1].contains("Cannot find type `string` in this scope."));
+    assert!(!errors[1].contains("similar name"));
+    assert!(errors[2].contains("expected `({error}, bool)`, found `{integer}`"));
+}
+
+/// Builds HIR that can't be written in the surface syntax yet. Spans are
+/// made up, as they are only used for diagnostics.
+struct Builder {
+    ids: NodeIdGen,
+}
+
+impl Builder {
+    fn new() -> Self {
+        Self {
+            ids: NodeIdGen::new(),
+        }
+    }
+
+    fn expr(&self, kind: ExprKind) -> Expr {
+        Expr::new(self.ids.next_id(), Span::new(0, 1), kind)
+    }
+
+    fn var(&self, name: &str) -> Expr {
+        self.expr(ExprKind::Var(Symbol::intern(name)))
+    }
+
+    fn ty(&self, kind: TyKind) -> Ty {
+        Ty::new(self.ids.next_id(), Span::new(0, 1), kind)
+    }
+
+    fn named(&self, name: &str) -> Ty {
+        self.ty(TyKind::Named(Symbol::intern(name), Vec::new()))
+    }
+
+    fn binding(&self, mutability: Mutability, name: &str) -> Pat {
+        Pat::new(
+            self.ids.next_id(),
+            Span::new(0, 1),
+            PatKind::Binding(mutability, Symbol::intern(name)),
+        )
+    }
+
+    fn lambda(&self, params: Vec<(&str, Option<Ty>)>, body: Expr) -> Expr {
+        let params = params
+            .into_iter()
+            .map(|(name, ty)| Param {
+                pat: self.binding(Mutability::Immut, name),
+                ty,
+            })
+            .collect();
+        self.expr(ExprKind::Lambda(params, Box::new(body)))
+    }
+
+    fn call(&self, callee: Expr, args: Vec<Expr>) -> Expr {
+        self.expr(ExprKind::Call(Box::new(callee), args))
+    }
+
+    fn let_(&self, mutability: Mutability, name: &str, value: Expr, body: Expr) -> Expr {
+        self.expr(ExprKind::Let(
+            self.binding(mutability, name),
+            None,
+            Box::new(value),
+            Box::new(body),
+        ))
+    }
+
+    fn lit(&self, src: &str) -> Expr {
+        self.expr(ExprKind::Lit(literal(src)))
+    }
+}
+
+/// Make a `bool` or decimal integer literal from its source.
+fn literal(src: &str) -> Lit {
+    match src {
+        "true" => Lit::Bool(true),
+        "false" => Lit::Bool(false),
+        _ => {
+            let suffix = match src.chars().last() {
+                Some('s') => Some(Suffix::Sint),
+                Some('u') => Some(Suffix::Uint),
+                _ => None,
+            };
+            let numeral = Numeral::Integer {
+                suffix,
+                radix: Radix::None,
+            };
+            Lit::Number(numeral, Symbol::intern(src))
+        }
+    }
+}
+
+#[test]
+fn functions() {
+    let b = Builder::new();
+
+    // `fn(x: (uint, [bool]), y) -> x`
+    let tuple = b.ty(TyKind::Tuple(vec![
+        b.named("uint"),
+        b.ty(TyKind::Array(Box::new(b.named("bool")), None)),
+    ]));
+    let lambda = b.lambda(vec![("x", Some(tuple)), ("y", None)], b.var("x"));
+    let (types, errors) = types_and_errors(&check_hir(vec![lambda.clone()]));
+    assert!(errors.is_empty());
+    assert_eq!(types, ["fn((uint, [bool]), ()) -> (uint, [bool])"]);
+
+    // Calling it with the wrong number of arguments.
+    let call = b.call(lambda, vec![b.var("z")]);
+    let (types, errors) = types_and_errors(&check_hir(vec![call]));
+    assert_eq!(types, ["(uint, [bool])"]);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("Cannot find value `z` in this scope."));
+    assert!(errors[1].contains("Wrong number of arguments to function: expected 2, found 1."));
+    assert!(errors[1].contains("expected 2 arguments"));
+
+    // Calling something that isn't a function.
+    let call = b.call(b.lit("1s"), vec![]);
+    let (_, errors) = types_and_errors(&check_hir(vec![call]));
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("expected `fn() -> _`, found `sint`"));
+}
+
+#[test]
+fn generics() {
+    let b = Builder::new();
+
+    // `let id = fn(x) -> x in do id(1s); id(true) end`
+    let id = b.lambda(vec![("x", None)], b.var("x"));
+    let body = b.expr(ExprKind::Block(vec![
+        b.call(b.var("id"), vec![b.lit("1s")]),
+        b.call(b.var("id"), vec![b.lit("true")]),
+    ]));
+    let let_ = b.let_(Mutability::Immut, "id", id, body);
+    let Checked {
+        grcx,
+        exprs,
+        results,
+        ..
+    } = check_hir(vec![let_]);
+    assert!(grcx.errors().is_empty());
+    let let_ = &exprs[0];
+
+    let ExprKind::Let(pat, _, value, body) = &let_.kind else {
+        unreachable!()
+    };
+    let ExprKind::Block(calls) = &body.kind else {
+        unreachable!()
+    };
+    let ty = |id| results.node_type(id).unwrap().to_string();
+    assert_eq!(
+        results.scheme(pat.id).unwrap().to_string(),
+        "with(T) fn(T) -> T"
+    );
+    assert_eq!(ty(value.id), "fn(T) -> T");
+    assert_eq!(ty(calls[0].id), "sint");
+    assert_eq!(ty(calls[1].id), "bool");
+    assert_eq!(ty(let_.id), "bool");
+
+    // Mutable bindings and bindings of values that aren't syntactic values
+    // are not generalized.
+    for (mutability, value) in [
+        (Mutability::Mut, b.lambda(vec![("x", None)], b.var("x"))),
+        (
+            Mutability::Immut,
+            b.expr(ExprKind::Block(vec![
+                b.lambda(vec![("x", None)], b.var("x"))
+            ])),
+        ),
+    ] {
+        let body = b.expr(ExprKind::Block(vec![
+            b.call(b.var("f"), vec![b.lit("1s")]),
+            b.call(b.var("f"), vec![b.lit("true")]),
+        ]));
+        let (_, errors) = types_and_errors(&check_hir(vec![b.let_(mutability, "f", value, body)]));
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].contains("expected `sint`, found `bool`"));
+    }
+}
+
+#[test]
+fn type_arguments() {
+    let (types, errors) = check("let x: uint[sint, bool] = 1 in x; let y: Option = 1 in y");
+    assert_eq!(types, ["uint", "{error}"]);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("Wrong number of type arguments for `uint`: expected 0, found 2."));
+    assert!(errors[0].contains("expected 0 type arguments"));
+    assert!(errors[1].contains("Wrong number of type arguments for `Option`: expected 1, found 0."));
+
+    let (types, errors) =
+        check("let x: Option[uint, bool] = None in x; let y: Opton[uint] = 1 in y");
+    assert_eq!(types, ["{error}", "{error}"]);
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("Wrong number of type arguments for `Option`: expected 1, found 2."));
+    assert!(errors[0].contains("expected 1 type argument"));
+    assert!(errors[1].contains("Cannot find type `Opton` in this scope."));
+    assert!(errors[1].contains("a type with a similar name exists: `Option`"));
+}
+
+#[test]
+fn loops() {
+    // A loop that never breaks can have any type.
+    assert_types(
+        "loop do break 1s end; loop do break end; let x: bool = loop do () end in x",
+        &["sint", "()", "bool"],
+    );
+
+    let (_, errors) = check("loop do break 1s; break true end");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("expected `sint`, found `bool`"));
+
+    // A function body is not part of the loop that the function is in.
+    let (_, errors) = check("break 1; loop do fn f() -> break; f() end");
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("`break` outside of a loop."));
+    assert!(errors[1].contains("cannot `break` outside of a loop"));
+}
+
+#[test]
+fn fn_items() {
+    assert_types("fn id(x) -> x; (id(1s), id(true))", &["()", "(sint, bool)"]);
+    assert_types(
+        "fn twice(f, x) -> f(f(x)); fn inc(x: uint) -> x + 1; twice(inc, 2)",
+        &["()", "()", "uint"],
+    );
+
+    // Function items can be used before they are defined, and can call
+    // each other.
+    assert_types(
+        "even(4);
+        fn even(n) -> case n do 0 -> true, _ -> odd(n - 1) end;
+        fn odd(n) -> case n do 0 -> false, _ -> even(n - 1) end",
+        &["bool", "()", "()"],
+    );
+
+    let (_, errors) = check("fn add(x, y) -> x + y; add(1); do fn f(x) -> x; f(1) end; f(2)");
+    assert_eq!(errors.len(), 2);
+    assert!(errors[0].contains("Cannot find value `f` in this scope."));
+    assert!(errors[1].contains("Wrong number of arguments to function: expected 2, found 1."));
+
+    let (_, errors) = check("fn f(x): bool -> x + 1");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("expected `bool`, found `{integer}`"));
+    assert!(errors[0].contains("expected due to this return type"));
+}
+
+#[test]
+fn generic_fn_items() {
+    let src = "with(T, U) fn swap(p: (T, U)): (U, T) -> case p do (a, b) -> (b, a) end;
+        fn first(x, y) -> x;
+        (swap((1s, true)), first(1.5, ()), first(true, 1))";
+    let Checked {
+        grcx,
+        exprs,
+        results,
+        ..
+    } = check_str(src);
+    assert!(grcx.errors().is_empty());
+
+    let scheme = |expr: &Expr| {
+        let ExprKind::Fn(def) = &expr.kind else {
+            unreachable!()
+        };
+        results.scheme(def.name.id).unwrap().to_string()
+    };
+    assert_eq!(scheme(&exprs[0]), "with(T, U) fn((T, U)) -> (U, T)");
+    assert_eq!(scheme(&exprs[1]), "with(T, U) fn(T, U) -> T");
+    assert_eq!(
+        results.node_type(exprs[2].id).unwrap().to_string(),
+        "((bool, sint), float, bool)"
+    );
+
+    // Type parameters can't be assumed to be any particular type, and are
+    // only in scope in their function.
+    let (types, errors) =
+        check("with(T) fn f(x: T): uint -> x; with(T) fn g(x: T): T -> 1; let y: T = 1 in y");
+    assert_eq!(types, ["()", "()", "{error}"]);
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("expected `uint`, found `T`"));
+    assert!(errors[1].contains("expected `T`, found `{integer}`"));
+    assert!(errors[2].contains("Cannot find type `T` in this scope."));
+}
+
+#[test]
+fn builtin_types() {
+    // Type variables that are never constrained default to `()`.
+    assert_types(
+        "Some(1s); None; Ok(true); Err",
+        &[
+            "Option[sint]",
+            "Option[()]",
+            "Result[bool, ()]",
+            "fn(()) -> Result[(), ()]",
+        ],
+    );
+    assert_types(
+        "let x: Option[uint] = None in x; let y: Result[uint, bool] = Ok(1) in y",
+        &["Option[uint]", "Result[uint, bool]"],
+    );
+    assert_types(
+        "fn get(o, default) -> case o do Some(x) -> x, None -> default end;
+        (get(Some(1s), 2), get(None, true))",
+        &["()", "(sint, bool)"],
+    );
+
+    let (_, errors) = check(
+        "let x: Option[bool] = Some(1) in x; case None do Some -> 1, Some(a, b) -> 2, _ -> 3 end",
+    );
+    assert_eq!(errors.len(), 3);
+    assert!(errors[0].contains("expected `Option[bool]`, found `Option[{integer}]`"));
+    assert!(
+        errors[1].contains("Wrong number of fields for constructor `Some`: expected 1, found 0.")
+    );
+    assert!(errors[1].contains("expected 1 field"));
+    assert!(
+        errors[2].contains("Wrong number of fields for constructor `Some`: expected 1, found 2.")
+    );
+
+    let (_, errors) = check("case Some(1) do Sme(x) -> x, _ -> 0 end");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("Cannot find value `Sme` in this scope."));
+}
+
+#[test]
+fn main_function() {
+    assert_types(
+        "fn main(args: Args) -> case arg_uint(args, 0) do Some(n) -> n, None -> arg_count(args) end;
+        arg_uint",
+        &["()", "fn(Args, uint) -> Option[uint]"],
+    );
+
+    let check_main_errors = |src: &str| {
+        let Checked {
+            mut grcx,
+            exprs,
+            results,
+            ..
+        } = check_str(src);
+        assert!(grcx.errors().is_empty(), "type checking {src:?}");
+        check_main(&mut grcx, 0, &exprs, &results);
+        render(grcx.errors())
+    };
+    // `main` may return an integer or `()`, and its parameter does not have
+    // to be annotated.
+    assert!(check_main_errors("fn main(args: Args) -> 1s").is_empty());
+    assert!(check_main_errors("fn main(args) -> ()").is_empty());
+    assert!(check_main_errors("fn other(x: uint) -> x").is_empty());
+
+    let errors = check_main_errors("fn main(args: Args) -> true");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("`main` function has the wrong type: found `fn(Args) -> bool`."));
+    let errors = check_main_errors("fn main(n: uint) -> n");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("found `fn(uint) -> uint`"));
+}
+
+#[test]
+fn mutable_bindings_are_not_generalized() {
+    // A function that assigns to a mutable variable cannot make the
+    // variable's type generic.
+    let (_, errors) =
+        check("let mut r = None in do fn set(v) -> r = Some(v); set(1); set(true); r end");
+    assert_eq!(errors.len(), 1);
+    assert!(errors[0].contains("expected `{integer}`, found `bool`"));
+
+    assert_types(
+        "let mut r = None in do fn set(v) -> r = Some(v); \
+         fn get(d) -> case r do Some(x) -> x, None -> d end; set(1); get(1) + 1 end",
+        &["uint"],
+    );
+}
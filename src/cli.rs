@@ -5,8 +5,16 @@ use std::{
 };
 
 use calypso_base::ui::{self, atty::Stream, termcolor::ColorChoice};
+use calypso_diagnostic::{report::ErrorFormat, reporting::term::Chars};
 
 #[derive(Debug, Parser)]
+#[clap(after_help = "EXIT STATUS:
+    0      Success
+    1      The input had errors, or the command failed
+    2      The command line was invalid
+    101    The compiler crashed (internal compiler error)
+
+`calypso run` exits with the status returned by the program's `main` function, if it has one.")]
 pub struct Args {
     /// Set how color is displayed, if at all. By default this is set to
     /// `auto`.
@@ -65,19 +73,155 @@ pub struct Args {
     )]
     pub log_format: LogFormat,
 
+    /// The format to emit diagnostics in.
+    ///
+    /// Formats available:
+    ///
+    /// - `human`: Rich diagnostics with source snippets. Syntax errors are
+    ///   shown on a single line, unless `--rich-errors` is given.
+    ///
+    /// - `short`: One line per diagnostic, with its location and message.
+    ///
+    /// - `json`: One JSON object per line for each diagnostic, with its
+    ///   severity, code, message, notes and labels, followed by a summary
+    ///   record with the number of errors and warnings. Intended for machine
+    ///   interpretation.
+    ///
+    /// - `sarif`: A single SARIF 2.1.0 log containing every diagnostic, for
+    ///   code scanning tools.
+    #[clap(
+        long,
+        possible_values = &[
+            "human",
+            "short",
+            "json",
+            "sarif"
+        ],
+        default_value = "human",
+        parse(from_str = parse_error_format)
+    )]
+    pub error_format: ErrorFormat,
+
+    /// Show syntax errors with source snippets, like other diagnostics,
+    /// rather than on a single line.
+    #[clap(long)]
+    pub rich_errors: bool,
+
+    /// The number of columns that tabs are shown as in source snippets.
+    #[clap(long, value_name = "COLUMNS", default_value = "4")]
+    pub tab_width: usize,
+
+    /// The characters used to draw source snippets.
+    ///
+    /// Possible values:
+    ///
+    /// - `box`: Unicode box drawing characters.
+    ///
+    /// - `ascii`: ASCII characters only, for terminals that cannot show
+    ///   Unicode.
+    #[clap(
+        long,
+        possible_values = &[
+            "box",
+            "ascii"
+        ],
+        default_value = "box",
+        parse(from_str = parse_diagnostic_chars)
+    )]
+    pub diagnostic_chars: Chars,
+
+    /// The number of lines of source to show after the start and before the
+    /// end of labels that span multiple lines. By default, 3 lines are shown
+    /// after the start and 1 before the end.
+    #[clap(long, value_name = "LINES")]
+    pub context_lines: Option<usize>,
+
+    /// Set the level of a lint to `allow`, so that it is not reported.
+    ///
+    /// Lint names may use either `-` or `_` as a separator. If a lint is
+    /// given to more than one of `--allow`, `--warn`, `--deny` and
+    /// `--forbid`, the strictest level is used.
+    #[clap(short = 'A', long, value_name = "LINT")]
+    pub allow: Vec<String>,
+
+    /// Set the level of a lint to `warn`, so that it is reported as a
+    /// warning.
+    #[clap(short = 'W', long, value_name = "LINT")]
+    pub warn: Vec<String>,
+
+    /// Set the level of a lint to `deny`, so that it is reported as an error.
+    #[clap(short = 'D', long, value_name = "LINT")]
+    pub deny: Vec<String>,
+
+    /// Set the level of a lint to `forbid`, so that it is reported as an
+    /// error and its level cannot be lowered by attributes.
+    #[clap(short = 'F', long, value_name = "LINT")]
+    pub forbid: Vec<String>,
+
     #[clap(subcommand)]
     pub cmd: Command,
 }
 
 #[derive(Debug, Subcommand)]
 pub enum Command {
-    /// Explain an error that has detailed information on troubleshooting.
+    /// Check Calypso source files for errors and lints, without running
+    /// them.
+    Check {
+        /// The input files to check. Use the file name `-` (without
+        /// backticks) to check standard input.
+        #[clap(parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+    },
+    /// Explain an error that has detailed information on troubleshooting,
+    /// or a lint.
     #[clap(visible_aliases = &["expl", "exp", "ex"])]
     Explain {
-        /// The error to get information for. This must be the error code of
-        /// the error, which is of the form `EXXXX` (e.g. E0591).
-        #[clap(name = "EXXXX")]
-        ecode: String,
+        /// The error or lint to get information for. This must be either the
+        /// error code of the error, which is of the form `EXXXX` (e.g.
+        /// E0591), or the name of the lint (e.g. `unused_mut`).
+        #[clap(
+            name = "EXXXX|LINT",
+            required_unless_present = "list",
+            conflicts_with = "list"
+        )]
+        ecode: Option<String>,
+        /// List every error code and lint, with a short description.
+        #[clap(long)]
+        list: bool,
+    },
+    /// Apply the fixes suggested by diagnostics that can be applied
+    /// automatically, such as removing unnecessary parentheses.
+    ///
+    /// Files are only changed if none of them have errors.
+    Fix {
+        /// The input files to fix. Use the file name `-` (without backticks)
+        /// to fix standard input to standard output.
+        #[clap(parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+    },
+    /// Format Calypso source files.
+    Fmt {
+        /// Do not write the formatted files, but exit with a nonzero exit
+        /// code if any of them are not formatted.
+        #[clap(long)]
+        check: bool,
+        /// The input files to format. Use the file name `-` (without
+        /// backticks) to format standard input to standard output.
+        #[clap(parse(from_os_str), required = true)]
+        inputs: Vec<PathBuf>,
+    },
+    /// Check and run a Calypso source file. If it has a `main` function,
+    /// call it with the program arguments and exit with the status that it
+    /// returns; otherwise, print the value of its last expression if it is
+    /// not `()`.
+    Run {
+        /// The input file to run. Use the file name `-` (without backticks)
+        /// to run standard input.
+        #[clap(parse(from_os_str))]
+        input: PathBuf,
+        /// Arguments to pass to the program's `main` function, after `--`.
+        #[clap(last = true)]
+        args: Vec<String>,
     },
     /// Commands used for debugging Calypso's internals and implementation.
     #[clap(visible_alias = "int")]
@@ -110,9 +254,22 @@ pub enum InternalCmd {
         /// - `toks`: Token list
         ///
         /// - `ast`: Abstract syntax tree (AST)
+        ///
+        /// - `toks-json`: Token list, as JSON
+        ///
+        /// - `ast-json`: Abstract syntax tree (AST), as JSON
+        ///
+        /// - `hir`: High-level intermediate representation (HIR), i.e. the
+        ///   AST after desugaring
+        ///
+        /// The JSON formats include the byte span and the (1-based) line and
+        /// column of the start and end of each token or node.
         #[clap(possible_values = &[
             "toks",
-            "ast"
+            "ast",
+            "toks-json",
+            "ast-json",
+            "hir"
         ], parse(from_str = parse_unpretty))]
         format: UnprettyFormat,
         /// The input file to run transformations on. Use the file name `-`
@@ -126,6 +283,19 @@ pub enum InternalCmd {
 pub enum UnprettyFormat {
     TokenList,
     Ast,
+    TokenListJson,
+    AstJson,
+    Hir,
+}
+
+impl UnprettyFormat {
+    /// Returns `true` if this format outputs JSON.
+    pub fn is_json(self) -> bool {
+        matches!(
+            self,
+            UnprettyFormat::TokenListJson | UnprettyFormat::AstJson
+        )
+    }
 }
 
 impl Display for UnprettyFormat {
@@ -133,6 +303,9 @@ impl Display for UnprettyFormat {
         match *self {
             UnprettyFormat::Ast => write!(f, "ast"),
             UnprettyFormat::TokenList => write!(f, "toks"),
+            UnprettyFormat::TokenListJson => write!(f, "toks-json"),
+            UnprettyFormat::AstJson => write!(f, "ast-json"),
+            UnprettyFormat::Hir => write!(f, "hir"),
         }
     }
 }
@@ -155,6 +328,27 @@ fn parse_unpretty(s: &str) -> UnprettyFormat {
     match s {
         "toks" => UnprettyFormat::TokenList,
         "ast" => UnprettyFormat::Ast,
+        "toks-json" => UnprettyFormat::TokenListJson,
+        "ast-json" => UnprettyFormat::AstJson,
+        "hir" => UnprettyFormat::Hir,
+        _ => unreachable!(),
+    }
+}
+
+fn parse_error_format(s: &str) -> ErrorFormat {
+    match s {
+        "human" => ErrorFormat::Human,
+        "short" => ErrorFormat::Short,
+        "json" => ErrorFormat::Json,
+        "sarif" => ErrorFormat::Sarif,
+        _ => unreachable!(),
+    }
+}
+
+fn parse_diagnostic_chars(s: &str) -> Chars {
+    match s {
+        "box" => Chars::box_drawing(),
+        "ascii" => Chars::ascii(),
         _ => unreachable!(),
     }
 }
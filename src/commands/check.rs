@@ -0,0 +1,33 @@
+use std::{path::PathBuf, sync::Arc};
+
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::prelude::*;
+
+use crate::{
+    commands::{compile, emit_diagnostics, emit_summary, read_input},
+    status::Status,
+};
+
+pub fn check(gcx: &Arc<GlobalCtxt>, inputs: &[PathBuf]) -> CalResult<Status> {
+    let mut errors = 0;
+    let mut warnings = 0;
+    for path in inputs {
+        let Some((file_name, contents)) = read_input(gcx, path)? else {
+            errors += 1;
+            continue;
+        };
+        let file_id = gcx.sourcemgr.write().add(file_name, contents);
+
+        compile(gcx, file_id)?;
+        emit_diagnostics(gcx)?;
+
+        let mut grcx_write = gcx.grcx.write();
+        errors += grcx_write.errors().len() + usize::from(grcx_write.fatal().is_some());
+        warnings += grcx_write.nonfatals().len();
+        grcx_write.clear();
+        grcx_write.lints_mut().clear_scopes();
+    }
+
+    emit_summary(gcx, errors, warnings)?;
+    Ok(Status::from_success(errors == 0))
+}
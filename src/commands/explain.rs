@@ -1,13 +1,23 @@
 use std::sync::Arc;
 
 use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::lint::LINTS;
 use calypso_diagnostic::prelude::*;
 use calypso_diagnostic::types;
 
-pub fn explain(gcx: &Arc<GlobalCtxt>, error_code: &str) -> CalResult<()> {
-    if let Some(diagnostic) = types::DIAGNOSTICS.get(error_code) {
+use crate::status::Status;
+
+pub fn explain(gcx: &Arc<GlobalCtxt>, error_code: &str) -> CalResult<Status> {
+    if let Some(lint) = lint::find(error_code) {
+        println!("{}: {}", lint.name, lint.description);
+        println!("default level: {}", lint.default_level);
+        println!();
+        print!("{}", lint.explanation);
+        Ok(Status::Success)
+    } else if let Some(diagnostic) = types::DIAGNOSTICS.get(error_code) {
         if let Some(information) = diagnostic {
             print!("{}", information);
+            Ok(Status::Success)
         } else {
             let mut emit = gcx.emit.write();
             let err = &mut emit.err;
@@ -18,6 +28,7 @@ pub fn explain(gcx: &Arc<GlobalCtxt>, error_code: &str) -> CalResult<()> {
                 Some(&format!("`{}`", error_code)),
             )?
             .flush()?;
+            Ok(Status::Failure)
         }
     } else {
         let mut emit = gcx.emit.write();
@@ -25,10 +36,30 @@ pub fn explain(gcx: &Arc<GlobalCtxt>, error_code: &str) -> CalResult<()> {
 
         err.error(
             None,
-            "error code is invalid",
+            "error code or lint name is invalid",
             Some(&format!("`{}`", error_code)),
         )?
         .flush()?;
+        Ok(Status::Usage)
+    }
+}
+
+/// List every registered error code with its short message, and every lint
+/// with its description. The placeholders in short messages are shown as
+/// `{…}`, which can't be mistaken for the punctuation around them.
+pub fn list() -> Status {
+    let mut codes = types::SHORT_MESSAGES.iter().collect::<Vec<_>>();
+    codes.sort_unstable();
+    println!("Error codes:");
+    for (code, message) in codes {
+        println!("    {code}  {}", message.replace("{}", "{…}"));
+    }
+
+    let width = LINTS.iter().map(|lint| lint.name.len()).max().unwrap_or(0);
+    println!();
+    println!("Lints:");
+    for lint in LINTS {
+        println!("    {:width$}  {}", lint.name, lint.description);
     }
-    Ok(())
+    Status::Success
 }
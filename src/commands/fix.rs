@@ -0,0 +1,209 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use calypso_base::span::Span;
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::{
+    diagnostic::{Applicability, Diagnostic, Edit, EnsembleDiagnostic},
+    prelude::*,
+    report::GlobalReportingCtxt,
+};
+
+use crate::{
+    commands::{compile, emit_diagnostics, emit_summary, read_input},
+    status::Status,
+};
+
+/// The maximum number of times that fixes are applied to a file. Applying
+/// fixes may lead to more suggestions, e.g. removing the parentheses in
+/// `((x))` once leaves `(x)`, so files are checked again after they are
+/// fixed.
+const MAX_PASSES: usize = 4;
+
+/// A file that has been fixed.
+struct Fixed {
+    path: PathBuf,
+    file_name: String,
+    source: String,
+    fixes: usize,
+}
+
+/// Apply all machine-applicable suggestions to `inputs`. Files are only
+/// written if every file could be fixed, i.e. none of them had errors, and
+/// each file is replaced atomically.
+pub fn fix(gcx: &Arc<GlobalCtxt>, inputs: &[PathBuf]) -> CalResult<Status> {
+    let mut fixed = Vec::new();
+    let mut errors = 0;
+    let mut warnings = 0;
+    for path in inputs {
+        match fix_file(gcx, path, &mut warnings)? {
+            Some(file) => fixed.push(file),
+            None => errors += 1,
+        }
+    }
+
+    if errors > 0 {
+        if fixed.iter().any(|file| file.fixes > 0) {
+            gcx.emit
+                .write()
+                .err
+                .note("no files were changed, as some files had errors", None)?
+                .flush()?;
+        }
+        emit_summary(gcx, errors, warnings)?;
+        return Ok(Status::Failure);
+    }
+
+    write_fixed(&fixed)?;
+    for file in &fixed {
+        if file.fixes > 0 {
+            let plural = if file.fixes == 1 { "" } else { "es" };
+            gcx.emit
+                .write()
+                .err
+                .info(
+                    "fixed",
+                    Some(&format!(
+                        "`{}` ({} fix{plural})",
+                        file.file_name, file.fixes
+                    )),
+                )?
+                .flush()?;
+        }
+    }
+    emit_summary(gcx, errors, warnings)?;
+    Ok(Status::Success)
+}
+
+/// Fix a single file, adding the number of warnings that could not be fixed
+/// to `warnings`. If the file could not be read or has errors, they are
+/// emitted and `None` is returned.
+fn fix_file(gcx: &Arc<GlobalCtxt>, path: &Path, warnings: &mut usize) -> CalResult<Option<Fixed>> {
+    let Some((file_name, mut source)) = read_input(gcx, path)? else {
+        return Ok(None);
+    };
+
+    let mut fixes = 0;
+    for pass in 1..=MAX_PASSES {
+        let file_id = gcx.sourcemgr.write().add(file_name.clone(), source.clone());
+        compile(gcx, file_id)?;
+
+        let (has_errors, suggestions) = {
+            let grcx_read = gcx.grcx.read();
+            let has_errors = !grcx_read.errors().is_empty() || grcx_read.fatal().is_some();
+            (has_errors, applicable_edits(&grcx_read, file_id))
+        };
+        if has_errors {
+            if fixes == 0 {
+                emit_diagnostics(gcx)?;
+            } else {
+                // The errors are in the fixed source, which was not written,
+                // and they are caused by a wrong suggestion.
+                gcx.emit
+                    .write()
+                    .err
+                    .error(
+                        None,
+                        "applying fixes introduced errors",
+                        Some(&format!("`{file_name}` was not fixed")),
+                    )?
+                    .flush()?;
+            }
+            clear(gcx);
+            return Ok(None);
+        } else if suggestions.is_empty() || pass == MAX_PASSES {
+            emit_diagnostics(gcx)?;
+            *warnings += gcx.grcx.read().nonfatals().len();
+            clear(gcx);
+            break;
+        }
+        clear(gcx);
+
+        fixes += suggestions.len();
+        let mut edits = suggestions.concat();
+        edits.sort_by_key(|edit| std::cmp::Reverse(edit.span));
+        for edit in edits {
+            source.replace_range(edit.span.into_range(), &edit.replacement);
+        }
+    }
+
+    Ok(Some(Fixed {
+        path: path.to_path_buf(),
+        file_name,
+        source,
+        fixes,
+    }))
+}
+
+fn clear(gcx: &GlobalCtxt) {
+    let mut grcx_write = gcx.grcx.write();
+    grcx_write.clear();
+    grcx_write.lints_mut().clear_scopes();
+}
+
+/// Get the edits of every machine-applicable suggestion for `file_id` that
+/// can be applied together. If the edits of two suggestions conflict, only
+/// the one that was reported first is used.
+fn applicable_edits(grcx: &GlobalReportingCtxt, file_id: usize) -> Vec<Vec<Edit>> {
+    let conflicts = |a: Span, b: Span| a.overlaps(b) || a.lo() == b.lo();
+    let mut suggestions: Vec<Vec<Edit>> = Vec::new();
+    for suggestion in grcx
+        .nonfatals()
+        .iter()
+        .chain(grcx.errors())
+        .flat_map(EnsembleDiagnostic::diagnostics)
+        .flat_map(Diagnostic::suggestions)
+    {
+        let applicable = suggestion.applicability == Applicability::MachineApplicable
+            && suggestion.edits.iter().all(|edit| {
+                edit.file_id == file_id
+                    && suggestions
+                        .iter()
+                        .flatten()
+                        .all(|other| !conflicts(edit.span, other.span))
+            });
+        if applicable {
+            suggestions.push(suggestion.edits.clone());
+        }
+    }
+    suggestions
+}
+
+/// Write the fixed files. Each file is first written to a temporary file
+/// next to it, and the temporary files are only renamed over the originals
+/// once all of them have been written. Standard input is fixed to standard
+/// output.
+fn write_fixed(fixed: &[Fixed]) -> CalResult<()> {
+    let mut written = Vec::new();
+    let mut result = Ok(());
+    for file in fixed {
+        if file.path == Path::new("-") {
+            print!("{}", file.source);
+            continue;
+        } else if file.fixes == 0 {
+            continue;
+        }
+        let mut name = file.path.file_name().unwrap_or_default().to_os_string();
+        name.push(".fix.tmp");
+        let temp = file.path.with_file_name(name);
+        result = fs::write(&temp, &file.source);
+        written.push((temp, &file.path));
+        if result.is_err() {
+            break;
+        }
+    }
+
+    if let Err(err) = result {
+        for (temp, _) in written {
+            let _ = fs::remove_file(temp);
+        }
+        return Err(err.into());
+    }
+    for (temp, path) in written {
+        fs::rename(temp, path)?;
+    }
+    Ok(())
+}
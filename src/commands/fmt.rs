@@ -0,0 +1,60 @@
+use std::{
+    fs,
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::prelude::*;
+use calypso_fmt::Config;
+
+use crate::{
+    commands::{emit_diagnostics, read_input},
+    status::Status,
+};
+
+pub fn fmt(gcx: &Arc<GlobalCtxt>, inputs: &[PathBuf], check: bool) -> CalResult<Status> {
+    let config = Config::default();
+    let mut success = true;
+    for path in inputs {
+        success &= fmt_file(gcx, &config, path, check)?;
+    }
+    Ok(Status::from_success(success))
+}
+
+/// Format a single file, returning `false` if it could not be formatted, or
+/// if it is not formatted and `check` is `true`.
+fn fmt_file(gcx: &Arc<GlobalCtxt>, config: &Config, path: &Path, check: bool) -> CalResult<bool> {
+    let Some((file_name, contents)) = read_input(gcx, path)? else {
+        return Ok(false);
+    };
+    let file_id = gcx.sourcemgr.write().add(file_name.clone(), contents);
+
+    let Some(formatted) = calypso_fmt::format(gcx, file_id, config)? else {
+        emit_diagnostics(gcx)?;
+        gcx.grcx.write().clear();
+        return Ok(false);
+    };
+
+    let sourcemgr = gcx.sourcemgr.read();
+    let unchanged = formatted == *sourcemgr.source(file_id).unwrap();
+
+    if unchanged && path != Path::new("-") {
+        Ok(true)
+    } else if check {
+        if !unchanged {
+            gcx.emit
+                .write()
+                .err
+                .warn("would reformat", Some(&format!("`{file_name}`")))?
+                .flush()?;
+        }
+        Ok(unchanged)
+    } else if path == Path::new("-") {
+        print!("{formatted}");
+        Ok(true)
+    } else {
+        fs::write(path, formatted)?;
+        Ok(true)
+    }
+}
@@ -7,13 +7,14 @@ use calypso_diagnostic::prelude::*;
 use crate::buildinfo::BUILD_INFO;
 use crate::cli::InternalCmd;
 use crate::commands::internal::unpretty::unpretty;
+use crate::status::Status;
 
 pub mod unpretty;
 
 #[allow(clippy::single_match)]
-pub fn internal(gcx: &Arc<GlobalCtxt>, cmd: &InternalCmd) -> CalResult<()> {
+pub fn internal(gcx: &Arc<GlobalCtxt>, cmd: &InternalCmd) -> CalResult<Status> {
     match cmd {
-        InternalCmd::BuildInfo => buildinfo(gcx),
+        InternalCmd::BuildInfo => buildinfo(gcx).map(|()| Status::Success),
         InternalCmd::Panic => panic!("Intentional panic to test ICE handling, please ignore."),
         InternalCmd::Unpretty {
             format,
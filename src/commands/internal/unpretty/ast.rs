@@ -1,6 +1,6 @@
 use std::sync::Arc;
 
-use calypso_ast::{pretty::PrettyPrinter, traverse::Visitor};
+use calypso_ast::{arena::Arena, node::NodeIdGen, pretty::PrettyPrinter, traverse::Visitor};
 use calypso_common::gcx::GlobalCtxt;
 use calypso_diagnostic::prelude::*;
 use calypso_parsing::{
@@ -8,7 +8,14 @@ use calypso_parsing::{
     parser::grammar::ExprsParser,
 };
 
-pub fn run_parser(gcx: &Arc<GlobalCtxt>, file_name: String, contents: String) -> CalResult<()> {
+use crate::status::Status;
+
+pub fn run_parser(
+    gcx: &Arc<GlobalCtxt>,
+    file_name: String,
+    contents: String,
+    json: bool,
+) -> CalResult<Status> {
     let file_id = gcx.sourcemgr.write().add(file_name, contents);
 
     let sourcemgr = gcx.sourcemgr.read();
@@ -45,12 +52,17 @@ pub fn run_parser(gcx: &Arc<GlobalCtxt>, file_name: String, contents: String) ->
     // drop(grcx_read);
 
     let parser = ExprsParser::new();
+    let ids = NodeIdGen::new();
+    let arena = Arena::new();
+    let mut parsed = Vec::new();
+    let mut status = Status::Success;
 
     loop {
         if tokens.peek().is_none() {
             break;
         }
-        match parser.parse(file_id, &mut tokens) {
+        match parser.parse(file_id, &ids, &arena, &mut tokens) {
+            Ok(exprs) if json => parsed.extend(exprs),
             Ok(exprs) => {
                 for expr in exprs {
                     let mut printer = PrettyPrinter::default();
@@ -63,10 +75,15 @@ pub fn run_parser(gcx: &Arc<GlobalCtxt>, file_name: String, contents: String) ->
                 emit.err
                     .error(None, "Parse error", Some(&format!("{:#?}", err)))?
                     .flush()?;
+                status = Status::Failure;
                 break;
             }
         };
     }
 
-    Ok(())
+    if json {
+        println!("{}", super::json::to_json(&sourcemgr, file_id, &parsed)?);
+    }
+
+    Ok(status)
 }
@@ -0,0 +1,32 @@
+use std::sync::Arc;
+
+use calypso_ast::{arena::Arena, node::NodeIdGen};
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::prelude::*;
+use calypso_hir::lower::lower;
+use calypso_parsing::parser;
+
+use crate::{commands::emit_diagnostics, status::Status};
+
+pub fn run_lowering(
+    gcx: &Arc<GlobalCtxt>,
+    file_name: String,
+    contents: String,
+) -> CalResult<Status> {
+    let file_id = gcx.sourcemgr.write().add(file_name, contents);
+
+    let ids = NodeIdGen::new();
+    let arena = Arena::new();
+    let Some(exprs) = parser::parse(gcx, file_id, &ids, &arena)? else {
+        emit_diagnostics(gcx)?;
+        return Ok(Status::Failure);
+    };
+
+    let sourcemgr = gcx.sourcemgr.read();
+    let source = sourcemgr.source(file_id).unwrap();
+    for expr in lower(source, &ids, &exprs) {
+        println!("{expr}");
+    }
+
+    Ok(Status::Success)
+}
@@ -0,0 +1,57 @@
+use serde::Serialize;
+use serde_json::{json, Map, Value};
+
+use calypso_diagnostic::{calypso_error::eyre, diagnostic::SourceMgr, prelude::*};
+
+/// Serialize a value to pretty-printed JSON, adding the line and column of
+/// the start and end of each span within it.
+pub fn to_json<T: Serialize>(
+    sourcemgr: &SourceMgr,
+    file_id: usize,
+    value: &T,
+) -> CalResult<String> {
+    let mut value = serde_json::to_value(value).map_err(eyre::Report::from)?;
+    add_positions(sourcemgr, file_id, &mut value)?;
+    Ok(serde_json::to_string_pretty(&value).map_err(eyre::Report::from)?)
+}
+
+fn add_positions(sourcemgr: &SourceMgr, file_id: usize, value: &mut Value) -> CalResult<()> {
+    match value {
+        Value::Array(values) => values
+            .iter_mut()
+            .try_for_each(|value| add_positions(sourcemgr, file_id, value)),
+        Value::Object(map) => {
+            if let Some((lo, hi)) = span_bounds(map) {
+                let start = position(sourcemgr, file_id, lo)?;
+                let end = position(sourcemgr, file_id, hi)?;
+                map.insert("start".to_string(), start);
+                map.insert("end".to_string(), end);
+                Ok(())
+            } else {
+                map.values_mut()
+                    .try_for_each(|value| add_positions(sourcemgr, file_id, value))
+            }
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Spans are serialized as objects with exactly the fields `lo` and `hi`.
+fn span_bounds(map: &Map<String, Value>) -> Option<(usize, usize)> {
+    if map.len() != 2 {
+        return None;
+    }
+    let lo = usize::try_from(map.get("lo")?.as_u64()?).ok()?;
+    let hi = usize::try_from(map.get("hi")?.as_u64()?).ok()?;
+    Some((lo, hi))
+}
+
+fn position(sourcemgr: &SourceMgr, file_id: usize, byte_index: usize) -> CalResult<Value> {
+    let location = sourcemgr
+        .location(file_id, byte_index)
+        .map_err(eyre::Report::from)?;
+    Ok(json!({
+        "line": location.line_number,
+        "column": location.column_number,
+    }))
+}
@@ -1,17 +1,14 @@
-use std::{
-    fs,
-    io::{self, prelude::*},
-    path::Path,
-    sync::Arc,
-};
+use std::{path::Path, sync::Arc};
 
 use calypso_common::gcx::GlobalCtxt;
 use calypso_diagnostic::prelude::*;
 use calypso_repl::Repl;
 
-use crate::{buildinfo::BUILD_INFO, cli::UnprettyFormat};
+use crate::{buildinfo::BUILD_INFO, cli::UnprettyFormat, commands::read_input, status::Status};
 
 pub mod ast;
+pub mod hir;
+pub mod json;
 pub mod toks;
 
 pub fn unpretty(
@@ -19,64 +16,33 @@ pub fn unpretty(
     format: UnprettyFormat,
     path: &Path,
     repl: bool,
-) -> CalResult<()> {
-    let (file_name, contents) = if path == Path::new("-") {
-        if repl {
-            run_repl(gcx, format);
-            return Ok(());
-        }
-
-        let stdin = io::stdin();
-        let mut contents = String::new();
-        if let Err(err) = stdin.lock().read_to_string(&mut contents) {
-            gcx.emit
-                .write()
-                .err
-                .error(None, "while reading from stdin:", None)?
-                .error(None, &format!("{}", err), None)?
-                .flush()?;
-            return Ok(());
-        }
-
-        ("<stdin>".to_string(), contents)
-    } else {
-        if !path.exists() {
-            gcx.emit
-                .write()
-                .err
-                .error(
-                    None,
-                    "file does not exist",
-                    Some(&format!("`{}`", path.display())),
-                )?
-                .flush()?;
-            return Ok(());
-        }
+) -> CalResult<Status> {
+    if path == Path::new("-") && repl {
+        run_repl(gcx, format);
+        return Ok(Status::Success);
+    }
 
-        (
-            path.display().to_string(),
-            match fs::read_to_string(&path) {
-                Ok(v) => v,
-                Err(err) => {
-                    gcx.emit
-                        .write()
-                        .err
-                        .error(
-                            None,
-                            "while reading file",
-                            Some(&format!("`{}`:", path.display())),
-                        )?
-                        .error(None, &format!("{}", err), None)?
-                        .flush()?;
-                    return Ok(());
-                }
-            },
-        )
+    let Some((file_name, contents)) = read_input(gcx, path)? else {
+        return Ok(Status::Failure);
     };
 
+    run(gcx, format, file_name, contents)
+}
+
+fn run(
+    gcx: &Arc<GlobalCtxt>,
+    format: UnprettyFormat,
+    file_name: String,
+    contents: String,
+) -> CalResult<Status> {
     match format {
-        UnprettyFormat::Ast => ast::run_parser(gcx, file_name, contents),
-        UnprettyFormat::TokenList => toks::run_lexer(gcx, file_name, contents),
+        UnprettyFormat::Ast | UnprettyFormat::AstJson => {
+            ast::run_parser(gcx, file_name, contents, format.is_json())
+        }
+        UnprettyFormat::Hir => hir::run_lowering(gcx, file_name, contents),
+        UnprettyFormat::TokenList | UnprettyFormat::TokenListJson => {
+            toks::run_lexer(gcx, file_name, contents, format.is_json())
+        }
     }
 }
 
@@ -88,16 +54,9 @@ pub fn run_repl(gcx: &Arc<GlobalCtxt>, format: UnprettyFormat) {
     let repl_gcx = Arc::clone(gcx);
     let mut repl = Repl::new(
         Box::new(move |rcx: &mut ReplCtx, contents| {
-            let res = match format {
-                UnprettyFormat::Ast => {
-                    ast::run_parser(&repl_gcx, format!("<repl:{}>", rcx.line), contents)
-                }
-                UnprettyFormat::TokenList => {
-                    toks::run_lexer(&repl_gcx, format!("<repl:{}>", rcx.line), contents)
-                }
-            }
-            .ok()
-            .map(|_| String::new());
+            let res = run(&repl_gcx, format, format!("<repl:{}>", rcx.line), contents)
+                .ok()
+                .map(|_| String::new());
             rcx.line += 1;
             repl_gcx.grcx.write().clear();
             res
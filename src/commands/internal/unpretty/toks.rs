@@ -4,7 +4,14 @@ use calypso_common::gcx::GlobalCtxt;
 use calypso_diagnostic::prelude::*;
 use calypso_parsing::{lexer, pretty::Printer};
 
-pub fn run_lexer(gcx: &Arc<GlobalCtxt>, file_name: String, contents: String) -> CalResult<()> {
+use crate::{commands::emit_diagnostics, status::Status};
+
+pub fn run_lexer(
+    gcx: &Arc<GlobalCtxt>,
+    file_name: String,
+    contents: String,
+    json: bool,
+) -> CalResult<Status> {
     let file_id = gcx.sourcemgr.write().add(file_name, contents);
 
     let sourcemgr = gcx.sourcemgr.read();
@@ -12,26 +19,14 @@ pub fn run_lexer(gcx: &Arc<GlobalCtxt>, file_name: String, contents: String) ->
     let mut printer = Printer::new(file_id, Arc::clone(gcx));
     let tokens = lexer::tokens(source, file_id, Arc::clone(gcx)).collect::<Vec<_>>();
 
+    emit_diagnostics(gcx)?;
     let grcx_read = gcx.grcx.read();
-    if let Some(fatal) = grcx_read.fatal() {
-        let mut emit = gcx.emit.write();
-        let mut buf = emit.err.buffer();
-        fatal.render(&mut buf, &sourcemgr, None)?;
-        emit.err.emit(&buf)?.flush()?;
-    } else {
-        grcx_read
-            .errors()
-            .iter()
-            .try_for_each(|e| -> CalResult<()> {
-                let mut emit = gcx.emit.write();
-                let mut buf = emit.err.buffer();
-                e.render(&mut buf, &sourcemgr, None)?;
-                emit.err.emit(&buf)?;
-                Ok(())
-            })?;
-        gcx.emit.write().err.flush()?;
+    let status = Status::from_success(grcx_read.errors().is_empty() && grcx_read.fatal().is_none());
+
+    if json {
+        println!("{}", super::json::to_json(&sourcemgr, file_id, &tokens)?);
+        return Ok(status);
     }
-    drop(grcx_read);
 
     let tokens = tokens
         .iter()
@@ -45,8 +40,9 @@ pub fn run_lexer(gcx: &Arc<GlobalCtxt>, file_name: String, contents: String) ->
                 .err
                 .error(None, "while pretty-printing tokens:", None)?
                 .error(None, &format!("{}", err), None)?;
+            return Ok(Status::Failure);
         }
     }
 
-    Ok(())
+    Ok(status)
 }
@@ -0,0 +1,165 @@
+//! Diagnostics as JSON, for `--error-format json`.
+//!
+//! Each diagnostic is emitted as a single line containing an object of the
+//! form:
+//!
+//! ```json
+//! {
+//!   "type": "diagnostic",
+//!   "severity": "error",
+//!   "code": "E0005",
+//!   "message": "...",
+//!   "labels": [
+//!     {
+//!       "style": "primary",
+//!       "message": "...",
+//!       "file": "main.cal",
+//!       "span": {
+//!         "lo": 4,
+//!         "hi": 5,
+//!         "start": { "line": 1, "column": 5 },
+//!         "end": { "line": 1, "column": 6 }
+//!       }
+//!     }
+//!   ],
+//!   "notes": ["..."],
+//!   "suggestions": [
+//!     {
+//!       "message": "...",
+//!       "applicability": "machine-applicable",
+//!       "edits": [
+//!         {
+//!           "file": "main.cal",
+//!           "span": { ... },
+//!           "replacement": "..."
+//!         }
+//!       ]
+//!     }
+//!   ],
+//!   "children": []
+//! }
+//! ```
+//!
+//! The `applicability` of a suggestion is either `machine-applicable`, if
+//! it can be applied automatically, or `maybe-incorrect`.
+//! The `code` is `null` if the diagnostic has none. Ensemble diagnostics are
+//! emitted as their first diagnostic, with the others in `children`, which
+//! have the same form but without `type` and `children`. Lines and columns
+//! are 1-based. After all diagnostics, a summary record of the form
+//! `{"type": "summary", "errors": 1, "warnings": 0}` is emitted.
+
+use std::ops::Range;
+
+use serde_json::{json, Value};
+
+use calypso_diagnostic::{
+    calypso_error::eyre,
+    diagnostic::{Applicability, Diagnostic, EnsembleDiagnostic, LabelStyle, Severity, SourceMgr},
+    prelude::*,
+};
+
+/// Convert an ensemble diagnostic to JSON.
+pub fn ensemble(sourcemgr: &SourceMgr, ensemble: &EnsembleDiagnostic) -> CalResult<Value> {
+    let (first, rest) = ensemble
+        .diagnostics()
+        .split_first()
+        .expect("ensemble diagnostics are not empty");
+    let mut value = diagnostic(sourcemgr, first)?;
+    value["type"] = json!("diagnostic");
+    value["children"] = rest
+        .iter()
+        .map(|diag| diagnostic(sourcemgr, diag))
+        .collect::<CalResult<_>>()?;
+    Ok(value)
+}
+
+/// The summary record emitted after all diagnostics.
+pub fn summary(errors: usize, warnings: usize) -> Value {
+    json!({
+        "type": "summary",
+        "errors": errors,
+        "warnings": warnings,
+    })
+}
+
+fn diagnostic(sourcemgr: &SourceMgr, diagnostic: &Diagnostic) -> CalResult<Value> {
+    let diag = diagnostic.codespan();
+    let severity = match diag.severity {
+        Severity::Bug => "bug",
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note => "note",
+        Severity::Help => "help",
+    };
+    let labels = diag
+        .labels
+        .iter()
+        .map(|label| {
+            let style = match label.style {
+                LabelStyle::Primary => "primary",
+                LabelStyle::Secondary => "secondary",
+            };
+            let file = sourcemgr.name(label.file_id).map_err(eyre::Report::from)?;
+            Ok(json!({
+                "style": style,
+                "message": label.message,
+                "file": file,
+                "span": span(sourcemgr, label.file_id, label.range.clone())?,
+            }))
+        })
+        .collect::<CalResult<Vec<_>>>()?;
+    let suggestions = diagnostic
+        .suggestions()
+        .iter()
+        .map(|suggestion| {
+            let applicability = match suggestion.applicability {
+                Applicability::MachineApplicable => "machine-applicable",
+                Applicability::MaybeIncorrect => "maybe-incorrect",
+            };
+            let edits = suggestion
+                .edits
+                .iter()
+                .map(|edit| {
+                    let file = sourcemgr.name(edit.file_id).map_err(eyre::Report::from)?;
+                    Ok(json!({
+                        "file": file,
+                        "span": span(sourcemgr, edit.file_id, edit.span.into_range())?,
+                        "replacement": edit.replacement,
+                    }))
+                })
+                .collect::<CalResult<Vec<_>>>()?;
+            Ok(json!({
+                "message": suggestion.message,
+                "applicability": applicability,
+                "edits": edits,
+            }))
+        })
+        .collect::<CalResult<Vec<_>>>()?;
+    Ok(json!({
+        "severity": severity,
+        "code": diag.code,
+        "message": diag.message,
+        "labels": labels,
+        "notes": diag.notes,
+        "suggestions": suggestions,
+    }))
+}
+
+fn span(sourcemgr: &SourceMgr, file_id: usize, range: Range<usize>) -> CalResult<Value> {
+    Ok(json!({
+        "lo": range.start,
+        "hi": range.end,
+        "start": position(sourcemgr, file_id, range.start)?,
+        "end": position(sourcemgr, file_id, range.end)?,
+    }))
+}
+
+pub fn position(sourcemgr: &SourceMgr, file_id: usize, byte_index: usize) -> CalResult<Value> {
+    let location = sourcemgr
+        .location(file_id, byte_index)
+        .map_err(eyre::Report::from)?;
+    Ok(json!({
+        "line": location.line_number,
+        "column": location.column_number,
+    }))
+}
@@ -1,5 +1,231 @@
+use std::{
+    fs,
+    io::{self, prelude::*},
+    path::Path,
+    sync::Arc,
+};
+
+use calypso_ast::{
+    arena::Arena, attr::register_lint_levels, node::NodeIdGen, parens::check_parens,
+};
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::{
+    prelude::*,
+    report::ErrorFormat,
+    reporting::term::{Config, DisplayStyle},
+};
+use calypso_hir::{
+    bindings::check_bindings,
+    expr::Expr,
+    lower::lower,
+    mutability::check_mutability,
+    reachability::check_reachability,
+    resolve::{resolve, Resolutions},
+};
+use calypso_parsing::parser;
+use calypso_typeck::{
+    check::{typeck, TypeckResults},
+    conditions::check_constant_conditions,
+    consteval::eval_consts,
+    entry::check_main,
+    exhaustive::check_exhaustiveness,
+};
+
+pub use check::check;
 pub use explain::explain;
+pub use fix::fix;
+pub use fmt::fmt;
 pub use internal::internal;
+pub use run::run;
 
+pub mod check;
 pub mod explain;
+pub mod fix;
+pub mod fmt;
 pub mod internal;
+pub mod json;
+pub mod run;
+pub mod sarif;
+
+/// Read a source file, or standard input if the path is `-`, returning the
+/// file name and its contents. If the file could not be read, the error is
+/// emitted and `None` is returned.
+pub fn read_input(gcx: &GlobalCtxt, path: &Path) -> CalResult<Option<(String, String)>> {
+    if path == Path::new("-") {
+        let stdin = io::stdin();
+        let mut contents = String::new();
+        if let Err(err) = stdin.lock().read_to_string(&mut contents) {
+            gcx.emit
+                .write()
+                .err
+                .error(None, "while reading from stdin:", None)?
+                .error(None, &format!("{err}"), None)?
+                .flush()?;
+            return Ok(None);
+        }
+
+        return Ok(Some(("<stdin>".to_string(), contents)));
+    }
+
+    if !path.exists() {
+        gcx.emit
+            .write()
+            .err
+            .error(
+                None,
+                "file does not exist",
+                Some(&format!("`{}`", path.display())),
+            )?
+            .flush()?;
+        return Ok(None);
+    }
+
+    match fs::read_to_string(path) {
+        Ok(contents) => Ok(Some((path.display().to_string(), contents))),
+        Err(err) => {
+            gcx.emit
+                .write()
+                .err
+                .error(
+                    None,
+                    "while reading file",
+                    Some(&format!("`{}`:", path.display())),
+                )?
+                .error(None, &format!("{err}"), None)?
+                .flush()?;
+            Ok(None)
+        }
+    }
+}
+
+/// A source file that has been checked without errors.
+pub struct Compiled {
+    pub exprs: Vec<Expr>,
+    pub res: Resolutions,
+    pub results: TypeckResults,
+}
+
+/// Run every check on the source file `file_id`, reporting errors and lints
+/// to the global reporting context. Returns `None` if there were any errors.
+pub fn compile(gcx: &Arc<GlobalCtxt>, file_id: usize) -> CalResult<Option<Compiled>> {
+    let ids = NodeIdGen::new();
+    let arena = Arena::new();
+    let Some(ast) = parser::parse(gcx, file_id, &ids, &arena)? else {
+        return Ok(None);
+    };
+
+    let sourcemgr = gcx.sourcemgr.read();
+    let src = sourcemgr.source(file_id).unwrap();
+    let mut grcx_write = gcx.grcx.write();
+    register_lint_levels(&mut grcx_write, file_id, &ast);
+    check_parens(&mut grcx_write, file_id, src, &ast);
+
+    let exprs = lower(src, &ids, &ast);
+    let res = resolve(&mut grcx_write, file_id, &exprs);
+    check_mutability(&mut grcx_write, file_id, &exprs, &res);
+    check_bindings(&mut grcx_write, file_id, &exprs, &res);
+    check_reachability(&mut grcx_write, file_id, &exprs);
+    let results = typeck(&mut grcx_write, file_id, &exprs, &res);
+    check_main(&mut grcx_write, file_id, &exprs, &results);
+    check_exhaustiveness(&mut grcx_write, file_id, &exprs, &results);
+    // Constant evaluation assumes that the expressions are well-typed.
+    if !grcx_write.errors().is_empty() {
+        return Ok(None);
+    }
+    let consts = eval_consts(&mut grcx_write, file_id, &exprs, &res, &results);
+    check_constant_conditions(&mut grcx_write, file_id, src, &exprs, &consts);
+
+    Ok(grcx_write.errors().is_empty().then_some(Compiled {
+        exprs,
+        res,
+        results,
+    }))
+}
+
+/// Emit all diagnostics in the global reporting context: warnings, then
+/// errors, then the fatal error if there is one. They are emitted in the
+/// error format set in the global reporting context, except for SARIF, for
+/// which they are deferred until the log is emitted once the command has
+/// finished. Syntax errors are shown on a single line, with what was
+/// expected, in the human and short formats, unless rich errors were
+/// requested.
+pub fn emit_diagnostics(gcx: &GlobalCtxt) -> CalResult<()> {
+    if gcx.grcx.read().emit_config().format == ErrorFormat::Sarif {
+        gcx.grcx.write().defer();
+        return Ok(());
+    }
+
+    let sourcemgr = gcx.sourcemgr.read();
+    let grcx_read = gcx.grcx.read();
+    let emit_config = grcx_read.emit_config();
+    let format = emit_config.format;
+    let short = Config {
+        display_style: DisplayStyle::Short,
+        ..emit_config.term.clone()
+    };
+    let rich = Config {
+        display_style: DisplayStyle::Rich,
+        ..emit_config.term.clone()
+    };
+    grcx_read
+        .nonfatals()
+        .iter()
+        .chain(grcx_read.errors())
+        .chain(grcx_read.fatal())
+        .try_for_each(|e| -> CalResult<()> {
+            let mut emit = gcx.emit.write();
+            let mut buf = emit.err.buffer();
+            if format == ErrorFormat::Json {
+                writeln!(buf, "{}", json::ensemble(&sourcemgr, e)?)?;
+            } else if e.is_syntax_error() && !emit_config.rich_errors {
+                e.render_compact(&mut buf, &sourcemgr, &emit_config.term)?;
+            } else if format == ErrorFormat::Short {
+                e.render(&mut buf, &sourcemgr, Some(&short))?;
+            } else {
+                e.render(&mut buf, &sourcemgr, Some(&rich))?;
+            }
+            emit.err.emit(&buf)?;
+            Ok(())
+        })?;
+    gcx.emit.write().err.flush()?;
+    Ok(())
+}
+
+/// Emit a summary of the number of errors and warnings that were emitted,
+/// e.g. "aborting due to 3 previous errors; 2 warnings emitted". Nothing is
+/// emitted if there were neither, unless the error format is JSON, in which
+/// case a summary record is always emitted. Nothing is emitted for SARIF, as
+/// the log contains the results.
+pub fn emit_summary(gcx: &GlobalCtxt, errors: usize, warnings: usize) -> CalResult<()> {
+    let format = gcx.grcx.read().emit_config().format;
+    let mut emit = gcx.emit.write();
+    if format == ErrorFormat::Sarif {
+        return Ok(());
+    } else if format == ErrorFormat::Json {
+        let summary = json::summary(errors, warnings).to_string();
+        emit.err.print(&summary)?.newline()?.flush()?;
+        return Ok(());
+    }
+
+    let plural = |n: usize| if n == 1 { "" } else { "s" };
+    let warnings_emitted = format!("{warnings} warning{} emitted", plural(warnings));
+    if errors > 0 {
+        let mut summary = format!(
+            "aborting due to {} error{}",
+            if errors == 1 {
+                "previous".to_string()
+            } else {
+                format!("{errors} previous")
+            },
+            plural(errors)
+        );
+        if warnings > 0 {
+            summary.push_str("; ");
+            summary.push_str(&warnings_emitted);
+        }
+        emit.err.error(None, &summary, None)?.flush()?;
+    } else if warnings > 0 {
+        emit.err.warn(&warnings_emitted, None)?.flush()?;
+    }
+    Ok(())
+}
@@ -0,0 +1,114 @@
+use std::{panic, path::Path, sync::Arc, thread};
+
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::{prelude::*, report::ErrorFormat};
+use calypso_interp::eval::{interpret, interpret_main};
+use calypso_typeck::entry::find_main;
+
+use crate::{
+    commands::{compile, emit_diagnostics, emit_summary, read_input},
+    status::Status,
+};
+
+/// The size of the stack that programs are run on, which is enough for
+/// [`MAX_CALL_DEPTH`](calypso_interp::eval::MAX_CALL_DEPTH) nested calls in
+/// debug builds.
+const STACK_SIZE: usize = 256 * 1024 * 1024;
+
+/// What a program produced when it was run.
+enum Outcome {
+    /// The program's `main` function returned this exit status.
+    Exit(u8),
+    /// The program has no `main` function, and its last expression
+    /// evaluated to this value.
+    Value(String),
+}
+
+pub fn run(gcx: &Arc<GlobalCtxt>, input: &Path, args: &[String]) -> CalResult<Status> {
+    let Some((file_name, contents)) = read_input(gcx, input)? else {
+        return Ok(Status::Failure);
+    };
+    let file_id = gcx.sourcemgr.write().add(file_name, contents);
+
+    let Some(compiled) = compile(gcx, file_id)? else {
+        return finish(gcx, None);
+    };
+    let main = find_main(&compiled.exprs);
+    if main.is_none() && !args.is_empty() {
+        gcx.emit
+            .write()
+            .err
+            .warn(
+                "program arguments were given, but will be ignored",
+                Some("the program has no `main` function to pass them to"),
+            )?
+            .flush()?;
+    }
+
+    let outcome = with_stack(|| {
+        let mut grcx_write = gcx.grcx.write();
+        match main {
+            Some(main) => interpret_main(
+                &mut grcx_write,
+                file_id,
+                &compiled.exprs,
+                main,
+                args,
+                &compiled.res,
+                &compiled.results,
+            )
+            .map(Outcome::Exit),
+            None => interpret(
+                &mut grcx_write,
+                file_id,
+                &compiled.exprs,
+                &compiled.res,
+                &compiled.results,
+            )
+            .map(|value| Outcome::Value(value.to_string())),
+        }
+    });
+    finish(gcx, outcome)
+}
+
+/// Call `f` on a new thread with a stack of [`STACK_SIZE`] bytes.
+fn with_stack<T: Send>(f: impl FnOnce() -> T + Send) -> T {
+    thread::scope(|scope| {
+        thread::Builder::new()
+            .stack_size(STACK_SIZE)
+            .spawn_scoped(scope, f)
+            .expect("failed to spawn a thread to run the program on")
+            .join()
+            .unwrap_or_else(|payload| panic::resume_unwind(payload))
+    })
+}
+
+/// Emit the diagnostics from compiling and running the program, then show
+/// its outcome, if it did not fail.
+fn finish(gcx: &GlobalCtxt, outcome: Option<Outcome>) -> CalResult<Status> {
+    emit_diagnostics(gcx)?;
+    let (errors, warnings) = {
+        let grcx_read = gcx.grcx.read();
+        (
+            grcx_read.errors().len() + usize::from(grcx_read.fatal().is_some()),
+            grcx_read.nonfatals().len(),
+        )
+    };
+    emit_summary(gcx, errors, warnings)?;
+
+    match outcome {
+        None => Ok(Status::Failure),
+        Some(Outcome::Exit(code)) => Ok(Status::Exit(code)),
+        Some(Outcome::Value(value)) => {
+            // A SARIF log must be the only thing on stdout for other tools
+            // to read it, so the value goes to stderr instead.
+            let sarif = gcx.grcx.read().emit_config().format == ErrorFormat::Sarif;
+            if value != "()" && sarif {
+                eprintln!("{value}");
+            } else if value != "()" {
+                println!("{value}");
+            }
+            Ok(Status::Success)
+        }
+    }
+}
@@ -0,0 +1,233 @@
+//! Diagnostics as a SARIF 2.1.0 log, for `--error-format sarif`.
+//!
+//! Unlike the other error formats, SARIF is a single document, so emitting
+//! diagnostics defers them in the global reporting context, and the log is
+//! emitted once the command has finished. Commands that never emit
+//! diagnostics, e.g. `explain`, do not emit a log.
+//!
+//! Each ensemble diagnostic is one result. Its rule is the lint that
+//! reported it, or otherwise its error code, and the rules are every
+//! registered error code and every lint, with their extended information as
+//! help text. The primary labels of the first diagnostic are the result's
+//! locations, and all other labels are related locations. Notes,
+//! suggestions and the messages of the other diagnostics in the ensemble
+//! are added to the result's message, and suggestions are also added as the
+//! result's fixes.
+
+use serde_json::{json, Value};
+
+use calypso_common::gcx::GlobalCtxt;
+use calypso_diagnostic::{
+    calypso_error::eyre,
+    diagnostic::{Diagnostic, EnsembleDiagnostic, LabelStyle, Severity, SourceMgr, Suggestion},
+    lint::{Level, LINTS},
+    prelude::*,
+    reporting::diagnostic::Label,
+    types::DIAGNOSTICS,
+};
+
+use crate::{buildinfo::BUILD_INFO, commands::json::position};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Emit the log, with the diagnostics that were deferred while the command
+/// ran. Nothing is emitted if the command did not emit any diagnostics.
+pub fn emit_log(gcx: &GlobalCtxt) -> CalResult<()> {
+    let Some(deferred) = gcx.grcx.write().take_deferred() else {
+        return Ok(());
+    };
+    let results = {
+        let sourcemgr = gcx.sourcemgr.read();
+        deferred
+            .iter()
+            .map(|ensemble| result(&sourcemgr, ensemble))
+            .collect::<CalResult<Vec<_>>>()?
+    };
+    let log = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": "calypso",
+                    "version": BUILD_INFO.version,
+                    "informationUri": "https://github.com/calypso-lang/calypso",
+                    "rules": rules(),
+                }
+            },
+            "columnKind": "unicodeCodePoints",
+            "results": results,
+        }],
+    });
+    let log = serde_json::to_string_pretty(&log).map_err(eyre::Report::from)?;
+    gcx.emit.write().err.print(&log)?.newline()?.flush()?;
+    Ok(())
+}
+
+/// Convert an ensemble diagnostic to a result of the log.
+fn result(sourcemgr: &SourceMgr, ensemble: &EnsembleDiagnostic) -> CalResult<Value> {
+    let (first, rest) = ensemble
+        .diagnostics()
+        .split_first()
+        .expect("ensemble diagnostics are not empty");
+    let rule_id = first
+        .lint()
+        .map(|lint| lint.name)
+        .or(first.codespan().code.as_deref());
+    let level = match first.codespan().severity {
+        Severity::Bug | Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Note | Severity::Help => "note",
+    };
+
+    let mut message = vec![first.codespan().message.clone()];
+    message.extend(first.codespan().notes.iter().cloned());
+    message.extend(first.suggestions().iter().map(Suggestion::to_note));
+    for diagnostic in rest {
+        let diag = diagnostic.codespan();
+        let severity = match diag.severity {
+            Severity::Bug => "bug",
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Note => "note",
+            Severity::Help => "help",
+        };
+        message.push(format!("{severity}: {}", diag.message));
+        message.extend(diag.notes.iter().cloned());
+        message.extend(diagnostic.suggestions().iter().map(Suggestion::to_note));
+    }
+
+    let mut locations = Vec::new();
+    let mut related = Vec::new();
+    for (idx, diag) in ensemble.diagnostics().iter().enumerate() {
+        for label in &diag.codespan().labels {
+            let location = location(sourcemgr, label)?;
+            if idx == 0 && label.style == LabelStyle::Primary {
+                locations.push(location);
+            } else {
+                related.push(location);
+            }
+        }
+    }
+    for (id, location) in related.iter_mut().enumerate() {
+        location["id"] = json!(id);
+    }
+    let fixes = ensemble
+        .diagnostics()
+        .iter()
+        .flat_map(Diagnostic::suggestions)
+        .map(|suggestion| fix(sourcemgr, suggestion))
+        .collect::<CalResult<Vec<_>>>()?;
+
+    let mut result = json!({
+        "level": level,
+        "message": { "text": message.join("\n") },
+        "locations": locations,
+        "relatedLocations": related,
+        "fixes": fixes,
+    });
+    if let Some(rule_id) = rule_id {
+        result["ruleId"] = json!(rule_id);
+        if let Some(idx) = rule_ids().iter().position(|id| *id == rule_id) {
+            result["ruleIndex"] = json!(idx);
+        }
+    }
+    Ok(result)
+}
+
+/// The IDs of the rules, in order: the registered error codes, then the
+/// lints.
+fn rule_ids() -> Vec<&'static str> {
+    let mut codes = DIAGNOSTICS.keys().copied().collect::<Vec<_>>();
+    codes.sort_unstable();
+    codes.extend(LINTS.iter().map(|lint| lint.name));
+    codes
+}
+
+fn rules() -> Vec<Value> {
+    rule_ids()
+        .into_iter()
+        .map(|id| {
+            let mut rule = json!({ "id": id });
+            if let Some(lint) = lint::find(id) {
+                let level = match lint.default_level {
+                    Level::Allow => "none",
+                    Level::Warn => "warning",
+                    Level::Deny | Level::Forbid => "error",
+                };
+                rule["shortDescription"] = json!({ "text": lint.description });
+                rule["help"] = help(lint.explanation);
+                rule["defaultConfiguration"] = json!({ "level": level });
+            } else if let Some(Some(information)) = DIAGNOSTICS.get(id) {
+                rule["help"] = help(information);
+            }
+            rule
+        })
+        .collect()
+}
+
+fn help(markdown: &str) -> Value {
+    json!({ "text": markdown, "markdown": markdown })
+}
+
+fn fix(sourcemgr: &SourceMgr, suggestion: &Suggestion) -> CalResult<Value> {
+    let mut file_ids = suggestion
+        .edits
+        .iter()
+        .map(|edit| edit.file_id)
+        .collect::<Vec<_>>();
+    file_ids.sort_unstable();
+    file_ids.dedup();
+    let changes = file_ids
+        .into_iter()
+        .map(|file_id| {
+            let file = sourcemgr.name(file_id).map_err(eyre::Report::from)?;
+            let replacements = suggestion
+                .edits
+                .iter()
+                .filter(|edit| edit.file_id == file_id)
+                .map(|edit| {
+                    let range = edit.span.into_range();
+                    json!({
+                        "deletedRegion": {
+                            "byteOffset": range.start,
+                            "byteLength": range.len(),
+                        },
+                        "insertedContent": { "text": edit.replacement },
+                    })
+                })
+                .collect::<Vec<_>>();
+            Ok(json!({
+                "artifactLocation": { "uri": file },
+                "replacements": replacements,
+            }))
+        })
+        .collect::<CalResult<Vec<_>>>()?;
+    Ok(json!({
+        "description": { "text": suggestion.message },
+        "artifactChanges": changes,
+    }))
+}
+
+fn location(sourcemgr: &SourceMgr, label: &Label<usize>) -> CalResult<Value> {
+    let file = sourcemgr.name(label.file_id).map_err(eyre::Report::from)?;
+    let start = position(sourcemgr, label.file_id, label.range.start)?;
+    let end = position(sourcemgr, label.file_id, label.range.end)?;
+    let mut location = json!({
+        "physicalLocation": {
+            "artifactLocation": { "uri": file },
+            "region": {
+                "startLine": start["line"],
+                "startColumn": start["column"],
+                "endLine": end["line"],
+                "endColumn": end["column"],
+                "byteOffset": label.range.start,
+                "byteLength": label.range.len(),
+            },
+        },
+    });
+    if !label.message.is_empty() {
+        location["message"] = json!({ "text": label.message });
+    }
+    Ok(location)
+}
@@ -2,6 +2,7 @@
 #![warn(clippy::pedantic)]
 
 use std::panic;
+use std::process::{self, ExitCode};
 use std::sync::Arc;
 
 use clap::StructOpt;
@@ -11,14 +12,21 @@ use tracing_subscriber::EnvFilter;
 use calypso_base::ui::Emitters;
 use calypso_common::{gcx::GlobalCtxt, parking_lot::RwLock};
 use calypso_diagnostic::prelude::*;
-use calypso_diagnostic::{diagnostic::SourceMgr, report::GlobalReportingCtxt};
+use calypso_diagnostic::{
+    diagnostic::SourceMgr,
+    lint::Level,
+    report::{EmitConfig, ErrorFormat, GlobalReportingCtxt},
+    reporting::term::Config,
+};
 
 mod buildinfo;
 mod cli;
 mod commands;
+mod status;
 
 use buildinfo::BUILD_INFO;
 use cli::{Args, Command, LogFormat};
+use status::Status;
 
 #[cfg(feature = "mimalloc")]
 use mimalloc::MiMalloc;
@@ -44,6 +52,7 @@ fn init_panic_hook(gcx: &Arc<GlobalCtxt>) {
         panic::set_hook(Box::new(move |info| {
             let gcx = Arc::clone(&gcx);
             report_ice(&*gcx, info, BUG_REPORT_URL).unwrap();
+            process::exit(Status::Ice.code().into());
         }));
         hook
     });
@@ -80,7 +89,50 @@ fn report_ice(gcx: &GlobalCtxt, info: &panic::PanicInfo<'_>, report_url: &str) -
     Ok(())
 }
 
-fn main() {
+/// Set the lint levels given on the command line. Flags are applied from the
+/// least to the most strict level, so the strictest level given for a lint
+/// is used.
+fn set_lint_levels(gcx: &GlobalCtxt, args: &Args) -> CalResult<()> {
+    for (names, level) in [
+        (&args.allow, Level::Allow),
+        (&args.warn, Level::Warn),
+        (&args.deny, Level::Deny),
+        (&args.forbid, Level::Forbid),
+    ] {
+        for name in names {
+            if let Some(lint) = lint::find(name) {
+                gcx.grcx.write().lints_mut().set_command_line(lint, level);
+            } else {
+                gcx.emit
+                    .write()
+                    .err
+                    .warn("unknown lint", Some(&format!("`{name}`")))?
+                    .flush()?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The configuration of how diagnostics are emitted, from the command line.
+fn emit_config(args: &Args) -> EmitConfig {
+    let mut term = Config {
+        tab_width: args.tab_width,
+        chars: args.diagnostic_chars.clone(),
+        ..Config::default()
+    };
+    if let Some(lines) = args.context_lines {
+        term.start_context_lines = lines;
+        term.end_context_lines = lines;
+    }
+    EmitConfig {
+        format: args.error_format,
+        rich_errors: args.rich_errors,
+        term,
+    }
+}
+
+fn main() -> ExitCode {
     let args = Args::parse();
 
     let gcx = Arc::new(GlobalCtxt {
@@ -89,10 +141,11 @@ fn main() {
         sourcemgr: RwLock::new(SourceMgr::new()),
     });
 
+    gcx.grcx.write().set_emit_config(emit_config(&args));
     init_panic_hook(&gcx);
     let mut trace = tracing_subscriber::fmt::fmt().with_env_filter(EnvFilter::default());
 
-    if let Some(log) = args.log {
+    if let Some(log) = &args.log {
         trace = trace.with_env_filter(EnvFilter::new(log));
     }
 
@@ -108,17 +161,34 @@ fn main() {
         }
     }
 
-    let res = match args.cmd {
-        Command::Explain { ecode } => commands::explain(&gcx, &ecode),
+    let res = set_lint_levels(&gcx, &args).and_then(|()| match args.cmd {
+        Command::Check { inputs } => commands::check(&gcx, &inputs),
+        Command::Explain {
+            ecode: Some(ecode), ..
+        } => commands::explain(&gcx, &ecode),
+        Command::Explain { ecode: None, .. } => Ok(commands::explain::list()),
+        Command::Fix { inputs } => commands::fix(&gcx, &inputs),
+        Command::Fmt { check, inputs } => commands::fmt(&gcx, &inputs, check),
+        Command::Run { input, args } => commands::run(&gcx, &input, &args),
         Command::Internal { cmd } => commands::internal(&gcx, &cmd),
-    };
-    if let Err(e) = res {
-        gcx.emit
-            .write()
-            .err
-            .error(None, &e.to_string(), None)
-            .unwrap()
-            .flush()
-            .unwrap();
+    });
+    let res = res.and_then(|status| {
+        if args.error_format == ErrorFormat::Sarif {
+            commands::sarif::emit_log(&gcx)?;
+        }
+        Ok(status)
+    });
+    match res {
+        Ok(status) => status.into(),
+        Err(e) => {
+            gcx.emit
+                .write()
+                .err
+                .error(None, &e.to_string(), None)
+                .unwrap()
+                .flush()
+                .unwrap();
+            Status::Failure.into()
+        }
     }
 }
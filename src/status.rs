@@ -0,0 +1,48 @@
+use std::process::ExitCode;
+
+/// How the compiler exited, which determines its exit code.
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum Status {
+    /// Everything succeeded. Exit code 0.
+    Success,
+    /// The input had errors, or a command failed, e.g. because a file could
+    /// not be read or a program failed at runtime. Exit code 1.
+    Failure,
+    /// The command line was invalid. Exit code 2, which is also used by
+    /// `clap` for invalid arguments.
+    Usage,
+    /// The compiler crashed (an ICE). Exit code 101, which is also used by
+    /// Rust for panics.
+    Ice,
+    /// A program run with `calypso run` returned this exit code from its
+    /// `main` function.
+    Exit(u8),
+}
+
+impl Status {
+    /// The exit code for this status.
+    pub fn code(self) -> u8 {
+        match self {
+            Status::Success => 0,
+            Status::Failure => 1,
+            Status::Usage => 2,
+            Status::Ice => 101,
+            Status::Exit(code) => code,
+        }
+    }
+
+    /// `Success` if `success` is `true`, and `Failure` otherwise.
+    pub fn from_success(success: bool) -> Self {
+        if success {
+            Status::Success
+        } else {
+            Status::Failure
+        }
+    }
+}
+
+impl From<Status> for ExitCode {
+    fn from(status: Status) -> Self {
+        ExitCode::from(status.code())
+    }
+}